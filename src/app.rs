@@ -1,35 +1,152 @@
 use eframe::{App, CreationContext};
-use egui::{Context, FontData, FontDefinitions, FontFamily}; // Removed ScrollArea
+use egui::Context; // Removed ScrollArea
 use egui_graphs::{Graph, events::Event}; // Removed GraphView, SettingsStyle, SettingsNavigation, SettingsInteraction
-use std::collections::HashMap;
-use std::fs;
-use std::sync::Arc;
+use std::collections::{HashMap, HashSet};
 use petgraph::stable_graph::{StableGraph, DefaultIx, NodeIndex, EdgeIndex};
 use petgraph::{Directed, Undirected, EdgeType};
 use rand::{Rng, rngs::ThreadRng};
 use fdg::{ForceGraph, Force};
-use fdg::fruchterman_reingold::FruchtermanReingold;
+use fdg::fruchterman_reingold::FruchtermanReingoldConfiguration;
+use crate::physics::WeightedFruchtermanReingold;
+use crate::convergence::ConvergenceMonitor;
+use crate::constraints::{Constraint, ConstraintKind, ConstraintSet, Axis};
+use crate::overlap::OverlapRemoval;
+use crate::view3d::OrbitCamera;
+use crate::shapes::{AppNodeShape, AppEdgeShape, NodeStyle, EdgeStyle, NodeOverrides, EdgeOverrides, NodeShapeKind, NodeShapeSettings, EdgeRenderSettings};
+use crate::visual_mapping::VisualMapping;
+use crate::search::SearchFilter;
+use crate::selection::CanvasSelection;
+use crate::theme::{ThemeSettings, THEME_STORAGE_KEY};
+use crate::fonts::{FontSettings, FONT_STORAGE_KEY};
+use crate::i18n::{self, tr, trf, Language, LANGUAGE_STORAGE_KEY};
+use crate::keymap::{Keymap, KEYMAP_STORAGE_KEY};
+use crate::minimap::Minimap;
+use crate::groups::{Collapsed, GroupSet, HiddenEdge, HiddenNode, NodeGroup};
+use crate::focus::{neighbourhood, EgoFocus};
+use crate::bulk_edit::BulkEditor;
+use crate::attributes::{AttributeDraft, Attributes};
+use crate::ids::{EdgeId, GraphIds, NodeId};
+use crate::project::ProjectFile;
 use crossbeam_channel::{unbounded, Sender, Receiver};
 
 // Moved from main.rs
 pub const DEFAULT_NODE_COUNT: usize = 15;
 pub const DEFAULT_EDGE_COUNT: usize = 20;
+// Simulation steps run at once after a reset or from "run layout"
+const LAYOUT_BURST_STEPS: usize = 100;
 
 #[derive(Clone, Debug, Default)]
 pub struct NodePayload {
+    pub id: NodeId, // Stable identity; the label is only display text
     pub label: String,
     pub weight: f32,
+    pub pinned: bool, // Pinned nodes are fixed anchors for the force model
+    pub style: NodeStyle, // Written by the visual mapping, read by AppNodeShape
+    pub shape: Option<NodeShapeKind>, // Per-node override of the global shape
+    pub icon_path: Option<String>,    // Per-node override of the global icon file
+    pub overrides: NodeOverrides,     // Hand-set style, applied on top of `style`
+    pub attributes: Attributes,       // Imported or user-defined data, typed
 }
 
 #[derive(Clone, Debug, Default)]
 pub struct EdgePayload {
+    pub id: EdgeId,
     pub label: String,
     pub weight: f32,
+    pub style: EdgeStyle, // Written by the visual mapping, read by AppEdgeShape
+    pub overrides: EdgeOverrides,
+    pub attributes: Attributes,
 }
 
+// egui graphs drawn with our own node/edge shapes
+pub type AppGraphOf<Ty> = Graph<NodePayload, EdgePayload, Ty, DefaultIx, AppNodeShape, AppEdgeShape>;
+
 pub enum AppGraph {
-    Directed(Graph<NodePayload, EdgePayload, Directed>),
-    Undirected(Graph<NodePayload, EdgePayload, Undirected>),
+    Directed(AppGraphOf<Directed>),
+    Undirected(AppGraphOf<Undirected>),
+}
+
+// Read-only helpers so callers don't have to match on the graph direction every time.
+impl AppGraph {
+    pub fn node_indices(&self) -> Vec<NodeIndex<DefaultIx>> {
+        match self {
+            AppGraph::Directed(g) => g.g.node_indices().collect(),
+            AppGraph::Undirected(g) => g.g.node_indices().collect(),
+        }
+    }
+
+    pub fn selected_nodes(&self) -> Vec<NodeIndex<DefaultIx>> {
+        match self {
+            AppGraph::Directed(g) => g.selected_nodes().to_vec(),
+            AppGraph::Undirected(g) => g.selected_nodes().to_vec(),
+        }
+    }
+
+    pub fn node_payload(&self, idx: NodeIndex<DefaultIx>) -> Option<&NodePayload> {
+        match self {
+            AppGraph::Directed(g) => g.node(idx).map(|n| n.payload()),
+            AppGraph::Undirected(g) => g.node(idx).map(|n| n.payload()),
+        }
+    }
+
+    pub fn edge_payload(&self, idx: EdgeIndex<DefaultIx>) -> Option<&EdgePayload> {
+        match self {
+            AppGraph::Directed(g) => g.edge(idx).map(|e| e.payload()),
+            AppGraph::Undirected(g) => g.edge(idx).map(|e| e.payload()),
+        }
+    }
+
+    pub fn node_location(&self, idx: NodeIndex<DefaultIx>) -> Option<egui::Pos2> {
+        match self {
+            AppGraph::Directed(g) => g.node(idx).map(|n| n.location()),
+            AppGraph::Undirected(g) => g.node(idx).map(|n| n.location()),
+        }
+    }
+
+    pub fn edge_endpoints(&self, idx: EdgeIndex<DefaultIx>) -> Option<(NodeIndex<DefaultIx>, NodeIndex<DefaultIx>)> {
+        match self {
+            AppGraph::Directed(g) => g.g.edge_endpoints(idx),
+            AppGraph::Undirected(g) => g.g.edge_endpoints(idx),
+        }
+    }
+
+    pub fn find_edge(&self, source: NodeIndex<DefaultIx>, target: NodeIndex<DefaultIx>) -> Option<EdgeIndex<DefaultIx>> {
+        match self {
+            AppGraph::Directed(g) => g.g.find_edge(source, target),
+            AppGraph::Undirected(g) => g.g.find_edge(source, target),
+        }
+    }
+
+    // (source, target, payload) of every edge
+    pub fn edges(&self) -> Vec<(NodeIndex<DefaultIx>, NodeIndex<DefaultIx>, EdgePayload)> {
+        fn collect<Ty: EdgeType>(g: &AppGraphOf<Ty>) -> Vec<(NodeIndex<DefaultIx>, NodeIndex<DefaultIx>, EdgePayload)> {
+            g.g.edge_indices()
+                .filter_map(|idx| {
+                    let (source, target) = g.g.edge_endpoints(idx)?;
+                    Some((source, target, g.edge(idx)?.payload().clone()))
+                })
+                .collect()
+        }
+        match self {
+            AppGraph::Directed(g) => collect(g),
+            AppGraph::Undirected(g) => collect(g),
+        }
+    }
+}
+
+/// What the canvas context menu was opened on.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ContextTarget {
+    Background(egui::Pos2), // Canvas position of the click
+    Node(NodeIndex<DefaultIx>),
+    Edge(EdgeIndex<DefaultIx>),
+}
+
+/// A node or edge label being edited in place on the canvas.
+pub struct InlineEdit {
+    pub target: ContextTarget,
+    pub text: String,
+    pub error: Option<String>, // Why the last commit was rejected
 }
 
 pub struct BasicApp {
@@ -48,19 +165,54 @@ pub struct BasicApp {
     pub ia_edge_selection_multi_enabled: bool,
 
     pub sim: ForceGraph<f32, 2, NodePayload, EdgePayload>,
-    pub force_algo: FruchtermanReingold<f32, 2>,
+    pub force_algo: WeightedFruchtermanReingold<2>,
+
+    // 3D mode runs its own simulation, a `map` of `sim`. Structural edits made in 3D mark it stale
+    // and it is refreshed (by node id) at the start of the next frame.
+    pub view_3d: bool,
+    pub sim_3d: ForceGraph<f32, 3, NodePayload, EdgePayload>,
+    pub sim_3d_stale: bool,
+    pub force_algo_3d: WeightedFruchtermanReingold<3>,
+    pub camera: OrbitCamera,
     
     pub sim_dt: f32,
     pub sim_cooloff_factor: f32,
     pub sim_scale: f32,
     pub simulation_stopped: bool,
+    pub convergence: ConvergenceMonitor,
+    pub constraints: ConstraintSet,
+    pub show_constraint_guides: bool,
+    pub overlap: OverlapRemoval,
+    pub visual_mapping: VisualMapping,
+    pub node_shapes: NodeShapeSettings,
+    pub edge_render: EdgeRenderSettings,
+    pub theme: ThemeSettings,
+    pub fonts: FontSettings,
+    pub language: Language,
+    pub search: SearchFilter,
+    pub selection: CanvasSelection,
+    pub keymap: Keymap,
+    pub minimap: Minimap,
+    pub groups: GroupSet,
+    pub focus: EgoFocus,
+    pub bulk_edit: BulkEditor,
+    pub attribute_draft: AttributeDraft,
+    pub project: ProjectFile,
 
     pub graph_nodes_count: usize,
     pub graph_edges_count: usize,
     pub rng: ThreadRng,
     pub event_publisher: Sender<Event>,
     pub event_consumer: Receiver<Event>,
-    pub node_label_to_index_map: HashMap<String, NodeIndex<DefaultIx>>,
+    pub ids: GraphIds, // Node/edge id -> index in the egui graph and in the simulation
+    pub context_target: Option<ContextTarget>, // Element under the cursor when the context menu was opened
+    pub context_text: String, // Rename / label field of the context menu
+    pub context_weight: f32,
+    pub edge_draft_source: Option<NodeIndex<DefaultIx>>, // "Start edge from here": waiting for the target click
+    pub pending_zoom: Option<egui::Rect>, // Canvas rect the view should frame on the next frame
+    pub clipboard_text: Option<String>, // Last subgraph copied or pasted here, for the menu's paste item
+    pub inline_edit: Option<InlineEdit>,
+    pub edit_error: Option<String>, // Why the last add/remove by label in the side panel was refused
 
     // Fields for UI state that will be managed by settings_panel
     // These will be passed to the settings_panel drawing function
@@ -73,33 +225,32 @@ pub struct BasicApp {
     pub input_node_to_remove: String,
     pub input_node_weight: f32, // New field for node weight input
     pub input_edge_weight: f32, // New field for edge weight input
+    pub input_constraint_distance: f32, // Separation / gap used when creating constraints
 }
 
 impl BasicApp {
     pub fn new(cc: &CreationContext<'_>) -> Self {
-        let mut fonts = FontDefinitions::default();
-        let font_path = "C:\\Windows\\Fonts\\simsun.ttc";
-        match fs::read(font_path) {
-            Ok(font_bytes) => {
-                let mut loaded_font_data = FontData::from_owned(font_bytes);
-                loaded_font_data.tweak.scale = 0.8;
-                fonts.font_data.insert("my_chinese_font".to_owned(), Arc::new(loaded_font_data));
-                fonts.families.entry(FontFamily::Proportional).or_default().insert(0, "my_chinese_font".to_owned());
-                fonts.families.entry(FontFamily::Monospace).or_default().insert(0, "my_chinese_font".to_owned());
-                println!("Successfully loaded font: {}", font_path);
-            }
-            Err(e) => {
-                eprintln!("Error loading font file at '{}': {}. Chinese characters might not display correctly.", font_path, e);
-            }
-        }
-        cc.egui_ctx.set_fonts(fonts);
+        let mut fonts: FontSettings = cc.storage.and_then(|storage| eframe::get_value(storage, FONT_STORAGE_KEY)).unwrap_or_default();
+        fonts.install(&cc.egui_ctx);
+        // Needed before the initial graph is generated, its labels are localised
+        let language: Language = cc.storage.and_then(|storage| eframe::get_value(storage, LANGUAGE_STORAGE_KEY)).unwrap_or_default();
+        i18n::set_language(language);
+        cc.egui_ctx.send_viewport_cmd(egui::ViewportCommand::Title(i18n::tr("app.title").to_owned()));
+        // Theme, fonts, language and keymap are the settings that survive a restart
+        let theme = cc.storage.and_then(|storage| eframe::get_value(storage, THEME_STORAGE_KEY)).unwrap_or_default();
+        let keymap = cc.storage.and_then(|storage| eframe::get_value::<Keymap>(storage, KEYMAP_STORAGE_KEY)).unwrap_or_default().with_missing_defaults();
+        Self::with_settings(theme, fonts, language, keymap)
+    }
 
+    /// The app with the given persisted settings and a freshly generated graph.
+    /// Needs no egui context, the settings are expected to be installed already.
+    pub fn with_settings(theme: ThemeSettings, fonts: FontSettings, language: Language, keymap: Keymap) -> Self {
         let (event_publisher, event_consumer) = unbounded();
         let rng = rand::rngs::ThreadRng::default();
         
         let initial_is_directed = true;
         let initial_petgraph = StableGraph::<NodePayload, EdgePayload, Directed>::new();
-        let initial_egui_graph = Graph::<NodePayload, EdgePayload, Directed>::from(&initial_petgraph);
+        let initial_egui_graph = AppGraphOf::<Directed>::from(&initial_petgraph);
 
         let mut app = Self {
             g: AppGraph::Directed(initial_egui_graph),
@@ -116,28 +267,56 @@ impl BasicApp {
             ia_edge_selection_enabled: true,
             ia_edge_selection_multi_enabled: true,
             sim: fdg::ForceGraph::new(), 
-            force_algo: fdg::fruchterman_reingold::FruchtermanReingold {
-                conf: fdg::fruchterman_reingold::FruchtermanReingoldConfiguration { 
-                    dt: 0.035, cooloff_factor: 0.95, scale: 100.0 
-                },
-                velocities: HashMap::default(),
-            },
+            force_algo: WeightedFruchtermanReingold::new(0.035, 0.95, 100.0),
+            view_3d: false,
+            sim_3d: fdg::ForceGraph::new(),
+            sim_3d_stale: false,
+            force_algo_3d: WeightedFruchtermanReingold::new(0.035, 0.95, 100.0),
+            camera: OrbitCamera::default(),
             sim_dt: 0.035,
             sim_cooloff_factor: 0.95,
             sim_scale: 100.0,
             simulation_stopped: false,
+            convergence: ConvergenceMonitor::default(),
+            constraints: ConstraintSet::default(),
+            show_constraint_guides: true,
+            overlap: OverlapRemoval::default(),
+            visual_mapping: VisualMapping::default(),
+            node_shapes: NodeShapeSettings::default(),
+            edge_render: EdgeRenderSettings::default(),
+            theme,
+            fonts,
+            language,
+            minimap: Minimap::default(),
+            groups: GroupSet::default(),
+            focus: EgoFocus::default(),
+            bulk_edit: BulkEditor::default(),
+            attribute_draft: AttributeDraft::default(),
+            project: ProjectFile::default(),
+            search: SearchFilter::default(),
+            selection: CanvasSelection::default(),
+            keymap,
             graph_nodes_count: DEFAULT_NODE_COUNT,
             graph_edges_count: DEFAULT_EDGE_COUNT,
             rng,
             event_publisher,
             event_consumer,
-            node_label_to_index_map: HashMap::new(),
+            ids: GraphIds::default(),
+            context_target: None,
+            context_text: String::new(),
+            context_weight: 1.0,
+            edge_draft_source: None,
+            pending_zoom: None,
+            clipboard_text: None,
+            inline_edit: None,
+            edit_error: None,
             input_node_from: String::new(),
             input_node_to: String::new(),
             input_node_to_add: String::new(),
             input_node_to_remove: String::new(),
             input_node_weight: 1.0, // Default weight
             input_edge_weight: 1.0, // Default weight
+            input_constraint_distance: 50.0,
         };
 
         app.reset_graph_and_simulation();
@@ -145,24 +324,35 @@ impl BasicApp {
         app
     }
 
+    /// Default settings and no nodes, the starting point of unit tests.
+    #[cfg(test)]
+    pub fn empty_for_tests() -> Self {
+        let mut app = Self::with_settings(Default::default(), Default::default(), Default::default(), Default::default());
+        app.replace_graph(true, Vec::new(), Vec::new());
+        app
+    }
+
     pub fn reset_graph_and_simulation(&mut self) {
-        self.node_label_to_index_map.clear();
+        self.constraints.clear(); // Node ids of the old graph mean nothing in the new one
+        self.groups.clear();
+        self.focus.exit();
+        self.bulk_edit.clear_undo();
         let petgraph_graph_for_fdg: StableGraph<NodePayload, EdgePayload, Directed>;
 
         if self.is_directed {
             let mut pet_graph_directed = StableGraph::<NodePayload, EdgePayload, Directed>::new();
-            Self::populate_graph_data(&mut pet_graph_directed, self.graph_nodes_count, self.graph_edges_count, &mut self.rng, &mut self.node_label_to_index_map);
+            Self::populate_graph_data(&mut pet_graph_directed, self.graph_nodes_count, self.graph_edges_count, &mut self.rng, &mut self.ids);
             
-            let mut egui_graph = Graph::<NodePayload, EdgePayload, Directed>::from(&pet_graph_directed);
+            let mut egui_graph = AppGraphOf::<Directed>::from(&pet_graph_directed);
             Self::initialize_egui_node_positions(&mut egui_graph, &pet_graph_directed, &mut self.rng);
 
             self.g = AppGraph::Directed(egui_graph);
             petgraph_graph_for_fdg = pet_graph_directed;
         } else {
             let mut pet_graph_undirected = StableGraph::<NodePayload, EdgePayload, Undirected>::default();
-            Self::populate_graph_data(&mut pet_graph_undirected, self.graph_nodes_count, self.graph_edges_count, &mut self.rng, &mut self.node_label_to_index_map);
+            Self::populate_graph_data(&mut pet_graph_undirected, self.graph_nodes_count, self.graph_edges_count, &mut self.rng, &mut self.ids);
 
-            let mut egui_graph = Graph::<NodePayload, EdgePayload, Undirected>::from(&pet_graph_undirected);
+            let mut egui_graph = AppGraphOf::<Undirected>::from(&pet_graph_undirected);
             Self::initialize_egui_node_positions(&mut egui_graph, &pet_graph_undirected, &mut self.rng);
             self.g = AppGraph::Undirected(egui_graph);
 
@@ -189,17 +379,59 @@ impl BasicApp {
         
         self.sim = fdg::init_force_graph_uniform(petgraph_graph_for_fdg, 100.0);
 
-        self.force_algo = fdg::fruchterman_reingold::FruchtermanReingold {
-            conf: fdg::fruchterman_reingold::FruchtermanReingoldConfiguration {
-                dt: self.sim_dt,
-                cooloff_factor: self.sim_cooloff_factor,
-                scale: self.sim_scale,
-            },
-            velocities: HashMap::default(),
+        // Keep the weight options chosen by the user, only reset the integrator state
+        self.force_algo.conf = FruchtermanReingoldConfiguration {
+            dt: self.sim_dt,
+            cooloff_factor: self.sim_cooloff_factor,
+            scale: self.sim_scale,
         };
+        self.force_algo.velocities.clear();
         
-        for _ in 0..100 { Force::apply(&mut self.force_algo, &mut self.sim); }
-        Self::sync_node_positions_to_egui(&self.sim, &mut self.g, &self.node_label_to_index_map);
+        for _ in 0..LAYOUT_BURST_STEPS { Force::apply(&mut self.force_algo, &mut self.sim); }
+        self.convergence.clear();
+        if self.view_3d {
+            self.rebuild_3d_simulation();
+        }
+        self.ids.rebuild(&self.g, &self.sim);
+        Self::sync_node_positions_to_egui(&self.sim, &mut self.g, &self.ids);
+    }
+
+    /// Replaces the graph with loaded nodes and edges, keeping their ids. Edges whose endpoints
+    /// are missing are dropped. Like a reset, this ends everything tied to the old graph.
+    pub fn replace_graph(&mut self, is_directed: bool, nodes: Vec<(NodePayload, egui::Pos2)>, edges: Vec<(NodeId, NodeId, EdgePayload)>) {
+        self.constraints.clear();
+        self.groups.clear();
+        self.focus.exit();
+        self.bulk_edit.clear_undo();
+        self.context_target = None;
+        self.edge_draft_source = None;
+        self.inline_edit = None;
+
+        self.is_directed = is_directed;
+        self.g = if is_directed {
+            AppGraph::Directed(AppGraphOf::<Directed>::from(&StableGraph::<NodePayload, EdgePayload, Directed>::new()))
+        } else {
+            AppGraph::Undirected(AppGraphOf::<Undirected>::from(&StableGraph::<NodePayload, EdgePayload, Undirected>::default()))
+        };
+        self.sim = fdg::ForceGraph::new();
+        self.ids.rebuild(&self.g, &self.sim); // Forget the old indices, keep the id allocator
+
+        for (payload, pos) in nodes {
+            self.insert_node(payload, pos);
+        }
+        for (source, target, payload) in edges {
+            if let (Some(source), Some(target)) = (self.ids.egui_node(source), self.ids.egui_node(target)) {
+                self.insert_edge(source, target, payload);
+            }
+        }
+        self.ids.rebuild(&self.g, &self.sim); // Loaded ids must not be handed out again
+        self.graph_nodes_count = self.ids.node_count();
+        self.graph_edges_count = self.ids.edge_count();
+        self.force_algo.velocities.clear();
+        self.convergence.clear();
+        if self.view_3d {
+            self.rebuild_3d_simulation();
+        }
     }
 
     pub fn convert_graph_direction(&mut self) {
@@ -242,8 +474,6 @@ impl BasicApp {
             }
         }
         
-        // Clear current node_label_to_index_map, it will be rebuilt
-        self.node_label_to_index_map.clear();
         let mut new_petgraph_for_fdg = StableGraph::<NodePayload, EdgePayload, Directed>::new();
         let mut old_idx_to_new_idx_map = HashMap::new(); // For fdg graph construction
 
@@ -251,7 +481,6 @@ impl BasicApp {
             let mut new_graph_directed = StableGraph::<NodePayload, EdgePayload, Directed>::new();
             for (old_idx, payload, _loc) in &old_nodes {
                 let new_idx = new_graph_directed.add_node(payload.clone());
-                self.node_label_to_index_map.insert(payload.label.clone(), new_idx);
                 old_idx_to_new_idx_map.insert(*old_idx, new_idx); // Map old egui_graph idx to new petgraph idx
             }
             for (old_src_idx, old_dst_idx, payload) in &old_edges {
@@ -260,12 +489,10 @@ impl BasicApp {
                 }
             }
             
-            let mut new_egui_graph = Graph::<NodePayload, EdgePayload, Directed>::from(&new_graph_directed);
+            let mut new_egui_graph = AppGraphOf::<Directed>::from(&new_graph_directed);
             // Apply old locations
             for (old_node_idx_orig_graph, _payload, loc) in &old_nodes {
-                 // Find the corresponding new_node_idx in new_egui_graph using the label via node_label_to_index_map
-                 // This assumes labels are unique and correctly mapped during node addition above.
-                 // OR, more robustly, use the old_idx_to_new_idx_map
+                 // Find the corresponding new_node_idx in new_egui_graph via old_idx_to_new_idx_map
                 if let Some(new_node_idx_in_petgraph) = old_idx_to_new_idx_map.get(old_node_idx_orig_graph) {
                     if let Some(node_mut) = new_egui_graph.node_mut(*new_node_idx_in_petgraph) {
                         node_mut.set_location(*loc);
@@ -280,7 +507,6 @@ impl BasicApp {
             let mut new_graph_undirected = StableGraph::<NodePayload, EdgePayload, Undirected>::default();
             for (old_idx, payload, _loc) in &old_nodes {
                 let new_idx = new_graph_undirected.add_node(payload.clone());
-                self.node_label_to_index_map.insert(payload.label.clone(), new_idx);
                 old_idx_to_new_idx_map.insert(*old_idx, new_idx);
             }
             for (old_src_idx, old_dst_idx, payload) in &old_edges {
@@ -289,7 +515,7 @@ impl BasicApp {
                 }
             }
 
-            let mut new_egui_graph = Graph::<NodePayload, EdgePayload, Undirected>::from(&new_graph_undirected);
+            let mut new_egui_graph = AppGraphOf::<Undirected>::from(&new_graph_undirected);
             // Apply old locations
             for (old_node_idx_orig_graph, _payload, loc) in &old_nodes {
                 if let Some(new_node_idx_in_petgraph) = old_idx_to_new_idx_map.get(old_node_idx_orig_graph) {
@@ -328,20 +554,23 @@ impl BasicApp {
         // We need to re-apply fdg algorithm to settle the new graph
         // Or, better, try to map old fdg positions to new fdg graph if node indices are preserved/mapped.
         // For now, let's just re-run the simulation briefly.
-        self.force_algo = fdg::fruchterman_reingold::FruchtermanReingold {
-            conf: fdg::fruchterman_reingold::FruchtermanReingoldConfiguration {
-                dt: self.sim_dt,
-                cooloff_factor: self.sim_cooloff_factor,
-                scale: self.sim_scale,
-            },
-            velocities: HashMap::default(), // Reset velocities
+        self.force_algo.conf = FruchtermanReingoldConfiguration {
+            dt: self.sim_dt,
+            cooloff_factor: self.sim_cooloff_factor,
+            scale: self.sim_scale,
         };
+        self.force_algo.velocities.clear(); // Reset velocities
         // DO NOT run simulation immediately after conversion to keep positions stable.
         // Instead, sync the (preserved) egui positions TO the new fdg simulation.
         // The old egui positions were already applied to the new self.g.
         // Now, update self.sim to match self.g positions.
 
+        self.ids.rebuild(&self.g, &self.sim); // Ids travel in the payloads, indices were reassigned
         self.sync_egui_positions_to_fdg();
+        self.convergence.clear();
+        if self.view_3d {
+            self.rebuild_3d_simulation();
+        }
         
         // Optional: if simulation was running, maybe stop it or reset forces,
         // as the graph structure changed. For now, just ensure positions are synced.
@@ -350,36 +579,12 @@ impl BasicApp {
 
     // New method to sync positions from egui_graphs to fdg_simulation
     fn sync_egui_positions_to_fdg(&mut self) {
-        match &self.g {
-            AppGraph::Directed(g_directed) => {
-                for node_idx_egui in g_directed.g.node_indices() {
-                    if let Some(egui_node) = g_directed.node(node_idx_egui) {
-                        let egui_pos = egui_node.location();
-                        // Find the corresponding node in self.sim and update its Point
-                        // This assumes self.node_label_to_index_map maps label to egui_node_idx (which is also petgraph idx for egui_graph)
-                        // And fdg graph's nodes can be identified by this same NodeIndex if structure was preserved,
-                        // or by payload label if fdg's indices are different.
-                        // Since fdg::init_force_graph_uniform rebuilds fdg's graph, its internal NodeIndices might be new.
-                        // We stored old_idx_to_new_idx_map during convert_graph_direction, which maps old egui_graph NodeIndex
-                        // to the new petgraph NodeIndex used to build the current egui_graph and new_petgraph_for_fdg.
-                        // So, node_idx_egui IS the index used in new_petgraph_for_fdg.
-                        if let Some((_payload_in_sim, point_in_sim)) = self.sim.node_weight_mut(node_idx_egui) {
-                            point_in_sim.coords.x = egui_pos.x;
-                            point_in_sim.coords.y = egui_pos.y;
-                        }
-                    }
-                }
-            }
-            AppGraph::Undirected(g_undirected) => {
-                for node_idx_egui in g_undirected.g.node_indices() {
-                    if let Some(egui_node) = g_undirected.node(node_idx_egui) {
-                        let egui_pos = egui_node.location();
-                        if let Some((_payload_in_sim, point_in_sim)) = self.sim.node_weight_mut(node_idx_egui) {
-                            point_in_sim.coords.x = egui_pos.x;
-                            point_in_sim.coords.y = egui_pos.y;
-                        }
-                    }
-                }
+        for node_idx_egui in self.g.node_indices() {
+            // The simulation node is found through the payload id, its index may differ
+            let (Some(sim_idx), Some(egui_pos)) = (self.sim_node(node_idx_egui), self.g.node_location(node_idx_egui)) else { continue };
+            if let Some((_payload_in_sim, point_in_sim)) = self.sim.node_weight_mut(sim_idx) {
+                point_in_sim.coords.x = egui_pos.x;
+                point_in_sim.coords.y = egui_pos.y;
             }
         }
         // After syncing positions to fdg, also clear velocities in fdg to prevent immediate movement if simulation is on.
@@ -391,13 +596,12 @@ impl BasicApp {
         node_count: usize,
         edge_count: usize,
         rng: &mut ThreadRng,
-        node_label_to_index_map: &mut HashMap<String, NodeIndex<DefaultIx>>,
+        ids: &mut GraphIds,
     ) {
         for i in 0..node_count {
-            let label_str = format!("节点{}", i);
-            let payload = NodePayload { label: label_str.clone(), weight: rng.random_range(1.0_f32..10.0_f32) };
-            let node_idx = graph_data.add_node(payload);
-            node_label_to_index_map.insert(label_str, node_idx);
+            let label_str = trf("gen.node_label", &[&i]);
+            let payload = NodePayload { id: ids.new_node_id(), label: label_str, weight: rng.random_range(1.0_f32..10.0_f32), pinned: false, style: NodeStyle::default(), shape: None, icon_path: None, overrides: NodeOverrides::default(), attributes: Attributes::new() };
+            graph_data.add_node(payload);
         }
 
         if node_count > 0 {
@@ -409,8 +613,8 @@ impl BasicApp {
                     let target_node_index = NodeIndex::new(target_idx_val);
                     
                     if graph_data.node_weight(source_node_index).is_some() && graph_data.node_weight(target_node_index).is_some() {
-                        let edge_label_str = format!("边 {}-{}", source_idx_val, target_idx_val);
-                        let edge_payload = EdgePayload { label: edge_label_str, weight: rng.random_range(1.0_f32..5.0_f32) };
+                        let edge_label_str = trf("gen.edge_label", &[&source_idx_val, &target_idx_val]);
+                        let edge_payload = EdgePayload { id: ids.new_edge_id(), label: edge_label_str, weight: rng.random_range(1.0_f32..5.0_f32), style: EdgeStyle::default(), overrides: EdgeOverrides::default(), attributes: Attributes::new() };
                         graph_data.add_edge(source_node_index, target_node_index, edge_payload);
                     }
                 }
@@ -419,7 +623,7 @@ impl BasicApp {
     }
 
     fn initialize_egui_node_positions<Ty: EdgeType>(
-        egui_graph: &mut Graph<NodePayload, EdgePayload, Ty>,
+        egui_graph: &mut AppGraphOf<Ty>,
         petgraph_graph: &StableGraph<NodePayload, EdgePayload, Ty>,
         rng: &mut ThreadRng,
     ) {
@@ -448,25 +652,25 @@ impl BasicApp {
     fn sync_node_positions_to_egui(
         sim_g: &fdg::ForceGraph<f32, 2, NodePayload, EdgePayload>,
         app_g: &mut AppGraph,
-        node_label_to_index_map: &HashMap<String, NodeIndex<DefaultIx>>
+        ids: &GraphIds
     ) {
         match app_g {
             AppGraph::Directed(g_directed) => {
-                Self::sync_specific_graph(sim_g, g_directed, node_label_to_index_map);
+                Self::sync_specific_graph(sim_g, g_directed, ids);
             }
             AppGraph::Undirected(g_undirected) => {
-                Self::sync_specific_graph(sim_g, g_undirected, node_label_to_index_map);
+                Self::sync_specific_graph(sim_g, g_undirected, ids);
             }
         }
     }
     
     fn sync_specific_graph<Ty: EdgeType>(
         sim_g: &fdg::ForceGraph<f32, 2, NodePayload, EdgePayload>,
-        egui_g_specific: &mut Graph<NodePayload, EdgePayload, Ty>,
-        node_label_to_index_map: &HashMap<String, NodeIndex<DefaultIx>>
+        egui_g_specific: &mut AppGraphOf<Ty>,
+        ids: &GraphIds
     ) {
         for (node_payload_from_sim, sim_pos_point) in sim_g.node_weights() {
-            if let Some(&node_idx_for_egui) = node_label_to_index_map.get(&node_payload_from_sim.label) {
+            if let Some(node_idx_for_egui) = ids.egui_node(node_payload_from_sim.id) {
                 if let Some(node_widget_in_egui) = egui_g_specific.node_mut(node_idx_for_egui) {
                     node_widget_in_egui.set_location(eframe::egui::Pos2::new(sim_pos_point.coords.x, sim_pos_point.coords.y));
                 }
@@ -475,84 +679,749 @@ impl BasicApp {
     }
 
     pub fn update_simulation(&mut self) {
-        if !self.simulation_stopped {
+        if self.view_3d {
+            if !self.simulation_stopped && !self.convergence.converged {
+                Force::apply(&mut self.force_algo_3d, &mut self.sim_3d);
+                self.convergence.record(self.force_algo_3d.last_energy, self.force_algo_3d.last_mean_displacement);
+            }
+            return;
+        }
+        if !self.simulation_stopped && !self.convergence.converged {
             Force::apply(&mut self.force_algo, &mut self.sim); 
+            self.constraints.project(&mut self.sim, &self.ids);
+            if self.overlap.continuous {
+                self.overlap.apply(&mut self.sim, 1);
+            }
+            self.convergence.record(self.force_algo.last_energy, self.force_algo.last_mean_displacement);
         }
     }
 
-    pub fn handle_events(&mut self) {
-        while let Ok(event) = self.event_consumer.try_recv() {
-            match event {
-                Event::NodeMove(payload) => {
-                    let node_idx = NodeIndex::new(payload.id);
-                    if let Some(node_weight_tuple_in_sim) = self.sim.node_weight_mut(node_idx) {
-                        node_weight_tuple_in_sim.1.coords.x = payload.new_pos[0];
-                        node_weight_tuple_in_sim.1.coords.y = payload.new_pos[1];
-                        self.force_algo.velocities.remove(&node_idx);
-                    }
+    // One-shot overlap removal, works whether or not the simulation is running
+    pub fn remove_overlaps(&mut self, ctx: &Context) {
+        self.overlap.measure(ctx, &self.sim, self.style_labels_always, self.theme.active().label_size);
+        let remaining = self.overlap.apply(&mut self.sim, self.overlap.max_passes);
+        self.constraints.project(&mut self.sim, &self.ids);
+        Self::sync_node_positions_to_egui(&self.sim, &mut self.g, &self.ids);
+        if remaining > 0 {
+            println!("Overlap removal stopped with {} overlaps left.", remaining);
+        }
+    }
+
+    // Lifts the 2D layout into 3D: x/y are kept, z gets a small random offset so the
+    // simulation can unfold along the new axis. Indices are preserved by `map`.
+    pub fn rebuild_3d_simulation(&mut self) {
+        let rng = &mut self.rng;
+        self.sim_3d = self.sim.map(
+            |_, (payload, p)| (payload.clone(), fdg::nalgebra::Point3::new(p.coords.x, p.coords.y, rng.random_range(-50.0..50.0))),
+            |_, payload| payload.clone(),
+        );
+        self.sync_force_settings_to_3d();
+        self.force_algo_3d.velocities.clear();
+        self.sim_3d_stale = false;
+    }
+
+    // Brings the 3D simulation back in line with `sim` after nodes or edges were added or removed.
+    // Nodes keep their 3D position, found by id since the old indices may have been reused;
+    // new nodes are lifted from 2D like in `rebuild_3d_simulation`.
+    pub fn refresh_3d_simulation(&mut self) {
+        let previous: HashMap<NodeId, fdg::nalgebra::Point3<f32>> = self.sim_3d.node_weights().map(|(payload, point)| (payload.id, *point)).collect();
+        let rng = &mut self.rng;
+        self.sim_3d = self.sim.map(
+            |_, (payload, p)| {
+                let point = previous.get(&payload.id).copied().unwrap_or_else(|| fdg::nalgebra::Point3::new(p.coords.x, p.coords.y, rng.random_range(-50.0..50.0)));
+                (payload.clone(), point)
+            },
+            |_, payload| payload.clone(),
+        );
+        self.force_algo_3d.velocities.clear();
+        self.sim_3d_stale = false;
+        self.wake_simulation();
+    }
+
+    // A stale 3D simulation no longer shares the simulation indices; it is refreshed
+    // from `sim` anyway, so payload edits only need to reach it while in step
+    fn sim_3d_node_mut(&mut self, sim_idx: NodeIndex) -> Option<&mut (NodePayload, fdg::nalgebra::Point3<f32>)> {
+        if self.sim_3d_stale {
+            return None;
+        }
+        self.sim_3d.node_weight_mut(sim_idx)
+    }
+
+    // Called by every edit that adds or removes nodes or edges
+    fn structure_changed(&mut self) {
+        if self.view_3d {
+            self.sim_3d_stale = true;
+        }
+        self.wake_simulation();
+    }
+
+    // The settings panel edits `force_algo`; the 3D model mirrors it
+    pub fn sync_force_settings_to_3d(&mut self) {
+        self.force_algo_3d.conf = FruchtermanReingoldConfiguration {
+            dt: self.force_algo.conf.dt,
+            cooloff_factor: self.force_algo.conf.cooloff_factor,
+            scale: self.force_algo.conf.scale,
+        };
+        self.force_algo_3d.edge_effect = self.force_algo.edge_effect;
+        self.force_algo_3d.edge_mapping = self.force_algo.edge_mapping;
+        self.force_algo_3d.node_effect = self.force_algo.node_effect;
+        self.force_algo_3d.node_mapping = self.force_algo.node_mapping;
+    }
+
+    pub fn enter_3d_mode(&mut self) {
+        self.rebuild_3d_simulation();
+        self.camera = OrbitCamera::default();
+        self.view_3d = true;
+        self.convergence.clear();
+    }
+
+    // Flattens the 3D layout with the current camera rotation (without perspective),
+    // so the 2D graph looks like what was on screen.
+    pub fn leave_3d_mode(&mut self) {
+        for (payload, point_3d) in self.sim_3d.node_weights() {
+            let projected = self.camera.to_camera_space(point_3d);
+            // Matched by id: a node removed in 3D may have left its index to another one
+            let Some(sim_idx) = self.ids.sim_node(payload.id) else { continue };
+            if let Some((_payload, point)) = self.sim.node_weight_mut(sim_idx) {
+                point.coords.x = projected.x;
+                point.coords.y = projected.y;
+            }
+        }
+        self.force_algo.velocities.clear();
+        self.view_3d = false;
+        self.convergence.clear();
+        Self::sync_node_positions_to_egui(&self.sim, &mut self.g, &self.ids);
+    }
+
+    pub fn set_language(&mut self, ctx: &Context, language: Language) {
+        self.language = language;
+        i18n::set_language(language);
+        ctx.send_viewport_cmd(egui::ViewportCommand::Title(i18n::tr("app.title").to_owned()));
+    }
+
+    pub fn node_label(&self, node_idx: NodeIndex) -> Option<String> {
+        self.g.node_payload(node_idx).map(|p| p.label.clone())
+    }
+
+    pub fn node_id(&self, node_idx: NodeIndex) -> Option<NodeId> {
+        self.g.node_payload(node_idx).map(|p| p.id)
+    }
+
+    pub fn selected_node_ids(&self) -> Vec<NodeId> {
+        self.g.selected_nodes().into_iter().filter_map(|idx| self.node_id(idx)).collect()
+    }
+
+    /// The simulation node of an egui node. Indices of the two graphs are not assumed to match.
+    pub fn sim_node(&self, node_idx: NodeIndex) -> Option<NodeIndex> {
+        self.ids.sim_node(self.node_id(node_idx)?)
+    }
+
+    pub fn sim_edge(&self, edge_idx: EdgeIndex) -> Option<EdgeIndex> {
+        self.ids.sim_edge(self.g.edge_payload(edge_idx)?.id)
+    }
+
+    /// The one visible node carrying `label`. Labels may repeat, so text input that names
+    /// a shared label is refused rather than guessed.
+    pub fn find_node_by_label(&self, label: &str) -> Result<NodeIndex, String> {
+        let found: Vec<NodeIndex> = self.g.node_indices().into_iter().filter(|idx| self.g.node_payload(*idx).is_some_and(|p| p.label == label)).collect();
+        match found.as_slice() {
+            [] => Err(trf("edit.label_not_found", &[&label])),
+            [idx] => Ok(*idx),
+            _ => Err(trf("edit.label_ambiguous", &[&found.len(), &label])),
+        }
+    }
+
+    pub fn remove_node_by_index(&mut self, node_idx: NodeIndex) {
+        let Some(id) = self.node_id(node_idx) else { return };
+        if self.detach_node(node_idx) {
+            self.groups.node_removed(id);
+            self.constraints.remove_node(id);
+        }
+    }
+
+    /// Renames a node in both payloads and the drawn label. Labels are display text only,
+    /// so duplicates are accepted.
+    pub fn rename_node(&mut self, node_idx: NodeIndex, new_label: String) -> Result<(), String> {
+        let new_label = new_label.trim().to_owned();
+        let Some(old_label) = self.node_label(node_idx) else { return Err(tr("rename.missing").to_owned()) };
+        if new_label == old_label {
+            return Ok(());
+        }
+        if new_label.is_empty() {
+            return Err(tr("rename.empty").to_owned());
+        }
+        match &mut self.g {
+            AppGraph::Directed(g) => {
+                if let Some(node) = g.node_mut(node_idx) {
+                    node.payload_mut().label = new_label.clone();
+                    node.set_label(new_label.clone());
                 }
-                _ => {}
+            }
+            AppGraph::Undirected(g) => {
+                if let Some(node) = g.node_mut(node_idx) {
+                    node.payload_mut().label = new_label.clone();
+                    node.set_label(new_label.clone());
+                }
+            }
+        }
+        if let Some(sim_idx) = self.sim_node(node_idx) {
+            if let Some((payload_in_sim, _point)) = self.sim.node_weight_mut(sim_idx) {
+                payload_in_sim.label = new_label.clone();
+            }
+            if let Some((payload_in_sim, _point)) = self.sim_3d_node_mut(sim_idx) {
+                payload_in_sim.label = new_label;
             }
         }
+        Ok(())
     }
 
-    // Updated to accept weight
-    pub fn add_node_ui(&mut self, label: String, weight: f32) {
-        if label.is_empty() {
-            println!("Node label cannot be empty.");
+    /// Like node labels, any text (including duplicates) is accepted.
+    pub fn rename_edge(&mut self, edge_idx: EdgeIndex, new_label: String) {
+        let Some(payload) = self.get_edge_payload_mut(edge_idx) else { return };
+        payload.label = new_label.trim().to_owned();
+        let payload = payload.clone();
+        self.update_fdg_edge_payload(edge_idx, payload);
+    }
+
+    /// Commits the inline label editor. On a rejected node label the editor stays open with the reason.
+    pub fn commit_inline_edit(&mut self) {
+        let Some(edit) = self.inline_edit.take() else { return };
+        let result = match edit.target {
+            ContextTarget::Node(node_idx) => self.rename_node(node_idx, edit.text.clone()),
+            ContextTarget::Edge(edge_idx) => {
+                self.rename_edge(edge_idx, edit.text.clone());
+                Ok(())
+            }
+            ContextTarget::Background(_) => Ok(()),
+        };
+        if let Err(error) = result {
+            self.inline_edit = Some(InlineEdit { error: Some(error), ..edit });
+        }
+    }
+
+    pub fn set_node_weight(&mut self, node_idx: NodeIndex, weight: f32) {
+        let Some(payload) = self.get_node_payload_mut(node_idx) else { return };
+        payload.weight = weight;
+        let payload = payload.clone();
+        self.update_fdg_node_payload(node_idx, payload);
+    }
+
+    /// Adds everything within `hops` steps of `start` to the selection. `direction` limits the
+    /// walk to descendants (`Outgoing`) or ancestors (`Incoming`).
+    pub fn select_neighbourhood(&mut self, start: &[NodeIndex], hops: usize, direction: Option<petgraph::Direction>) {
+        let mut selected = self.g.selected_nodes();
+        let mut found: Vec<NodeIndex> = neighbourhood(&self.g, start, hops, direction).into_iter().filter(|idx| !selected.contains(idx)).collect();
+        found.sort();
+        selected.extend(found);
+        self.select_nodes(selected);
+    }
+
+    /// Lays out only `nodes`, holding the rest of the graph in place, then frames them.
+    pub fn run_local_layout(&mut self, nodes: &HashSet<NodeIndex>) {
+        if self.view_3d || nodes.is_empty() {
             return;
         }
-        if self.node_label_to_index_map.contains_key(&label) {
-            println!("Node with label '{}' already exists.", label);
+        let moved: HashSet<NodeIndex> = nodes.iter().filter_map(|idx| self.sim_node(*idx)).collect();
+        let held: Vec<NodeIndex> = self.sim.node_indices().filter(|idx| !moved.contains(idx) && !self.sim[*idx].0.pinned).collect();
+        for idx in &held {
+            if let Some((payload, _)) = self.sim.node_weight_mut(*idx) {
+                payload.pinned = true;
+            }
+        }
+        for _ in 0..LAYOUT_BURST_STEPS {
+            Force::apply(&mut self.force_algo, &mut self.sim);
+            self.constraints.project(&mut self.sim, &self.ids);
+        }
+        for idx in &held {
+            if let Some((payload, _)) = self.sim.node_weight_mut(*idx) {
+                payload.pinned = false;
+            }
+        }
+        Self::sync_node_positions_to_egui(&self.sim, &mut self.g, &self.ids);
+        let mut points = nodes.iter().filter_map(|idx| self.g.node_location(*idx));
+        if let Some(first) = points.next() {
+            self.pending_zoom = Some(points.fold(egui::Rect::from_min_max(first, first), |r, p| r.union(egui::Rect::from_min_max(p, p))));
+        }
+        self.wake_simulation();
+    }
+
+    /// Adds a node with a generated label at a canvas position.
+    pub fn add_node_at(&mut self, pos: egui::Pos2) {
+        let taken = self.taken_labels();
+        let label = (self.g.node_indices().len()..).map(|n| trf("gen.node_label", &[&n])).find(|candidate| !taken.contains(candidate)).unwrap_or_default();
+        let payload = NodePayload { label, weight: self.input_node_weight, ..Default::default() };
+        self.insert_node(payload, pos);
+    }
+
+    /// Moves a node in both the egui graph and the simulation, which would otherwise move it back.
+    pub fn set_node_location(&mut self, node_idx: NodeIndex, pos: egui::Pos2) {
+        match &mut self.g {
+            AppGraph::Directed(g) => { if let Some(node) = g.node_mut(node_idx) { node.set_location(pos); } }
+            AppGraph::Undirected(g) => { if let Some(node) = g.node_mut(node_idx) { node.set_location(pos); } }
+        }
+        let Some(sim_idx) = self.sim_node(node_idx) else { return };
+        if let Some((_, point)) = self.sim.node_weight_mut(sim_idx) {
+            point.coords.x = pos.x;
+            point.coords.y = pos.y;
+        }
+    }
+
+    /// `label`, or `label (2)`, `label (3)`... whichever no node uses yet. Labels may repeat,
+    /// but generated and pasted ones are kept distinct so they can be told apart on screen.
+    pub fn unique_node_label(&self, label: &str) -> String {
+        let taken = self.taken_labels();
+        if !taken.contains(label) {
+            return label.to_owned();
+        }
+        (2..).map(|n| format!("{} ({})", label, n)).find(|candidate| !taken.contains(candidate)).unwrap_or_default()
+    }
+
+    // Labels of visible nodes and of nodes inside collapsed groups
+    fn taken_labels(&self) -> HashSet<String> {
+        let visible: Vec<String> = self.g.node_indices().into_iter().filter_map(|idx| self.node_label(idx)).collect();
+        visible.into_iter().chain(self.groups.hidden_labels().map(str::to_owned)).collect()
+    }
+
+    /// Adds a node to both graphs at a canvas position. A payload without an id gets a new one;
+    /// one that has an id (a node coming back from a collapsed group) keeps it.
+    pub fn insert_node(&mut self, mut payload: NodePayload, pos: egui::Pos2) -> NodeIndex {
+        if payload.id == NodeId::default() {
+            payload.id = self.ids.new_node_id();
+        }
+        fn add<Ty: EdgeType>(g: &mut AppGraphOf<Ty>, payload: NodePayload, pos: egui::Pos2) -> NodeIndex {
+            let label = payload.label.clone();
+            let node_idx = g.add_node(payload);
+            if let Some(node_mut) = g.node_mut(node_idx) {
+                node_mut.set_location(pos);
+                node_mut.set_label(label);
+            }
+            node_idx
+        }
+        let node_idx = match &mut self.g {
+            AppGraph::Directed(g) => add(g, payload.clone(), pos),
+            AppGraph::Undirected(g) => add(g, payload.clone(), pos),
+        };
+        let id = payload.id;
+        let sim_idx = self.sim.add_node((payload, fdg::nalgebra::Point2::new(pos.x, pos.y)));
+        self.ids.insert_node(id, node_idx, sim_idx);
+        self.graph_nodes_count = self.ids.node_count();
+        self.structure_changed();
+        node_idx
+    }
+
+    /// Adds an edge with a ready-made payload to both graphs. A payload without an id gets a new one.
+    pub fn insert_edge(&mut self, source: NodeIndex, target: NodeIndex, mut payload: EdgePayload) -> Option<EdgeIndex> {
+        let (Some(sim_source), Some(sim_target)) = (self.sim_node(source), self.sim_node(target)) else { return None };
+        if payload.id == EdgeId::default() {
+            payload.id = self.ids.new_edge_id();
+        }
+        let edge_idx = match &mut self.g {
+            AppGraph::Directed(g) => g.add_edge(source, target, payload.clone()),
+            AppGraph::Undirected(g) => g.add_edge(source, target, payload.clone()),
+        };
+        let id = payload.id;
+        let sim_edge_idx = self.sim.add_edge(sim_source, sim_target, payload);
+        self.ids.insert_edge(id, edge_idx, sim_edge_idx);
+        self.graph_edges_count = match &self.g {
+            AppGraph::Directed(g) => g.edge_count(),
+            AppGraph::Undirected(g) => g.edge_count(),
+        };
+        self.structure_changed();
+        Some(edge_idx)
+    }
+
+    /// Selects exactly `nodes`.
+    pub fn select_nodes(&mut self, nodes: Vec<NodeIndex>) {
+        self.clear_selection();
+        fn select<Ty: EdgeType>(g: &mut AppGraphOf<Ty>, nodes: Vec<NodeIndex>) {
+            for idx in &nodes {
+                if let Some(node) = g.node_mut(*idx) {
+                    node.set_selected(true);
+                }
+            }
+            g.set_selected_nodes(nodes);
+        }
+        match &mut self.g {
+            AppGraph::Directed(g) => select(g, nodes),
+            AppGraph::Undirected(g) => select(g, nodes),
+        }
+    }
+
+    pub fn remove_edge(&mut self, edge_idx: EdgeIndex) {
+        let Some(id) = self.g.edge_payload(edge_idx).map(|p| p.id) else { return };
+        match &mut self.g {
+            AppGraph::Directed(g) => { g.remove_edge(edge_idx); }
+            AppGraph::Undirected(g) => { g.remove_edge(edge_idx); }
+        }
+        if let Some(sim_edge_idx) = self.ids.sim_edge(id) {
+            self.sim.remove_edge(sim_edge_idx);
+        }
+        self.ids.remove_edge(id);
+        self.graph_edges_count = match &self.g {
+            AppGraph::Directed(g) => g.edge_count(),
+            AppGraph::Undirected(g) => g.edge_count(),
+        };
+        self.structure_changed();
+    }
+
+    /// Flips a directed edge, keeping its id. A generated label is regenerated for the new direction.
+    pub fn reverse_edge(&mut self, edge_idx: EdgeIndex) {
+        if !self.is_directed {
             return;
         }
+        let Some((source, target)) = self.g.edge_endpoints(edge_idx) else { return };
+        let Some(mut payload) = self.g.edge_payload(edge_idx).cloned() else { return };
+        if let (Some(source_label), Some(target_label)) = (self.node_label(source), self.node_label(target)) {
+            if payload.label == trf("gen.edge_between", &[&source_label, &target_label]) {
+                payload.label = trf("gen.edge_between", &[&target_label, &source_label]);
+            }
+        }
+        self.remove_edge(edge_idx);
+        self.insert_edge(target, source, payload);
+    }
+
+    pub fn set_edge_weight_and_label(&mut self, edge_idx: EdgeIndex, weight: f32, label: String) {
+        let Some(payload) = self.get_edge_payload_mut(edge_idx) else { return };
+        payload.weight = weight;
+        payload.label = label;
+        let payload = payload.clone();
+        self.update_fdg_edge_payload(edge_idx, payload);
+    }
+
+    /// Frames every node on the next frame, like fit-to-screen but once.
+    pub fn zoom_to_fit(&mut self) {
+        let mut points = self.g.node_indices().into_iter().filter_map(|idx| self.g.node_location(idx));
+        if let Some(first) = points.next() {
+            self.pending_zoom = Some(points.fold(egui::Rect::from_min_max(first, first), |r, p| r.union(egui::Rect::from_min_max(p, p))));
+        }
+    }
 
-        let payload = NodePayload { label: label.clone(), weight };
-        let new_node_idx: NodeIndex<DefaultIx>;
+    /// Runs a burst of layout steps right away and restarts the simulation.
+    pub fn run_layout(&mut self) {
+        for _ in 0..LAYOUT_BURST_STEPS {
+            Force::apply(&mut self.force_algo, &mut self.sim);
+            self.constraints.project(&mut self.sim, &self.ids);
+        }
+        self.simulation_stopped = false;
+        self.wake_simulation();
+    }
 
-        // Add to egui_graphs Graph and fdg::ForceGraph
+    pub fn select_all(&mut self) {
+        fn select<Ty: EdgeType>(g: &mut AppGraphOf<Ty>) {
+            let nodes: Vec<NodeIndex> = g.g.node_indices().collect();
+            for idx in &nodes {
+                if let Some(node) = g.node_mut(*idx) {
+                    node.set_selected(true);
+                }
+            }
+            g.set_selected_nodes(nodes);
+        }
         match &mut self.g {
-            AppGraph::Directed(g) => {
-                new_node_idx = g.add_node(payload.clone());
-                // Initialize position for the new node in egui_graph
-                if let Some(node_mut) = g.node_mut(new_node_idx) {
-                    let x = self.rng.random_range(-50.0..50.0); // Or some other logic
-                    let y = self.rng.random_range(-50.0..50.0);
-                    node_mut.set_location(eframe::egui::Pos2::new(x, y));
-                    node_mut.set_label(label.clone());
+            AppGraph::Directed(g) => select(g),
+            AppGraph::Undirected(g) => select(g),
+        }
+    }
+
+    pub fn clear_selection(&mut self) {
+        fn clear<Ty: EdgeType>(g: &mut AppGraphOf<Ty>) {
+            let nodes: Vec<NodeIndex> = g.g.node_indices().collect();
+            for idx in nodes {
+                if let Some(node) = g.node_mut(idx) {
+                    node.set_selected(false);
                 }
             }
-            AppGraph::Undirected(g) => {
-                new_node_idx = g.add_node(payload.clone());
-                if let Some(node_mut) = g.node_mut(new_node_idx) {
-                    let x = self.rng.random_range(-50.0..50.0);
-                    let y = self.rng.random_range(-50.0..50.0);
-                    node_mut.set_location(eframe::egui::Pos2::new(x, y));
-                    node_mut.set_label(label.clone());
+            let edges: Vec<EdgeIndex> = g.g.edge_indices().collect();
+            for idx in edges {
+                if let Some(edge) = g.edge_mut(idx) {
+                    edge.set_selected(false);
                 }
             }
+            g.set_selected_nodes(Default::default());
+            g.set_selected_edges(Default::default());
         }
-        
-        // Add to fdg simulation graph.
-        // fdg::ForceGraph::add_node takes N (NodePayload) and Point.
-        // We need to decide an initial position for the simulation as well.
-        // For simplicity, let's use the same random position as for egui_graph.
-        // However, fdg positions are relative to its own coordinate system, often centered at (0,0).
-        // The scale factor in FruchtermanReingold also plays a role.
-        // Let's use a small random offset for fdg as well.
-        let sim_pos_x = self.rng.random_range(-1.0..1.0); // Small initial displacement for simulation
-        let sim_pos_y = self.rng.random_range(-1.0..1.0);
-        // fdg expects fdg::nalgebra::Point2<f32> or similar
-        let sim_point = fdg::nalgebra::Point2::new(sim_pos_x, sim_pos_y);
-        // fdg::ForceGraph::add_node returns its own NodeIndex.
-        // We rely on labels for mapping between egui_graphs and fdg for now.
-        // The new_node_idx from egui_graphs is what we store in node_label_to_index_map.
-        let _fdg_node_idx = self.sim.add_node((payload, sim_point));
-
-
-        self.node_label_to_index_map.insert(label.clone(), new_node_idx);
-        self.graph_nodes_count += 1;
+        match &mut self.g {
+            AppGraph::Directed(g) => clear(g),
+            AppGraph::Undirected(g) => clear(g),
+        }
+    }
+
+    /// Removes the selected edges, then the selected nodes with their remaining edges.
+    pub fn delete_selection(&mut self) {
+        let has_selected_edges = match &self.g {
+            AppGraph::Directed(g) => !g.selected_edges().is_empty(),
+            AppGraph::Undirected(g) => !g.selected_edges().is_empty(),
+        };
+        if has_selected_edges {
+            self.remove_selected_edges_ui();
+        }
+        for node_idx in self.g.selected_nodes() {
+            self.remove_node_by_index(node_idx);
+        }
+        self.clear_selection();
+    }
+
+    /// Turns the selected nodes into a new (expanded) group.
+    pub fn group_selected_nodes(&mut self) {
+        let members = self.selected_node_ids();
+        if members.is_empty() {
+            println!("Select the nodes to group first.");
+            return;
+        }
+        let name = match self.groups.input_name.trim() {
+            "" => trf("group.default_name", &[&(self.groups.groups.len() + 1)]),
+            name => name.to_owned(),
+        };
+        self.groups.groups.push(NodeGroup { name, members, collapsed: None });
+        self.groups.input_name.clear();
+    }
+
+    /// Replaces the group's visible members with one meta-node. Edges leaving the group are merged per
+    /// neighbour (and direction) into one edge whose weight is the sum of the merged weights.
+    pub fn collapse_group(&mut self, group_idx: usize) {
+        let Some(group) = self.groups.groups.get(group_idx).filter(|g| g.collapsed.is_none()) else { return };
+        let name = group.name.clone();
+        // Members inside an already collapsed (nested) group are represented by its meta-node
+        let mut members: Vec<(NodeId, NodeIndex)> = Vec::new();
+        for id in &group.members {
+            let Some(rep) = self.groups.representative(*id, |id| self.ids.egui_node(id).is_some()) else { continue };
+            // Only a group nested inside this one may stand in for members, never an enclosing one
+            let nested = rep == *id || self.groups.group_of_meta(rep).is_some_and(|i| self.groups.groups[i].members.iter().all(|m| group.members.contains(m)));
+            if !nested {
+                continue;
+            }
+            let Some(idx) = self.ids.egui_node(rep) else { continue };
+            if members.iter().all(|(m, _)| *m != rep) {
+                members.push((rep, idx));
+            }
+        }
+        if members.is_empty() {
+            return;
+        }
+
+        let locations: Vec<egui::Pos2> = members.iter().map(|(_, idx)| self.g.node_location(*idx).unwrap_or_default()).collect();
+        let centre = (locations.iter().fold(egui::Vec2::ZERO, |sum, p| sum + p.to_vec2()) / locations.len() as f32).to_pos2();
+        let nodes: Vec<HiddenNode> = members
+            .iter()
+            .zip(&locations)
+            .filter_map(|((_, idx), location)| Some(HiddenNode { payload: self.g.node_payload(*idx)?.clone(), offset: *location - centre }))
+            .collect();
+        let weight: f32 = nodes.iter().map(|n| n.payload.weight).sum();
+
+        let member_set: HashSet<NodeIndex> = members.iter().map(|(_, idx)| *idx).collect();
+        let mut edges = Vec::new();
+        // (neighbour, whether the group is the source) -> (summed weight, merged edge count)
+        let mut external: Vec<((NodeIndex, bool), f32, usize)> = Vec::new();
+        for (source, target, payload) in self.g.edges() {
+            let (source_in, target_in) = (member_set.contains(&source), member_set.contains(&target));
+            if !source_in && !target_in {
+                continue;
+            }
+            if source_in != target_in {
+                let key = if source_in { (target, true) } else { (source, !self.is_directed) };
+                match external.iter_mut().find(|(k, _, _)| *k == key) {
+                    Some((_, sum, count)) => {
+                        *sum += payload.weight;
+                        *count += 1;
+                    }
+                    None => external.push((key, payload.weight, 1)),
+                }
+            }
+            let id_of = |idx: NodeIndex| self.node_id(idx).unwrap_or_default();
+            edges.push(HiddenEdge { source: id_of(source), target: id_of(target), payload });
+        }
+
+        for (_, idx) in &members {
+            self.detach_node(*idx);
+        }
+        let meta = self.ids.new_node_id();
+        let label = self.unique_node_label(&format!("[{}]", name));
+        let meta_idx = self.insert_node(NodePayload { id: meta, label, weight, shape: Some(NodeShapeKind::Hexagon), ..Default::default() }, centre);
+        for ((neighbour, group_is_source), weight, count) in external {
+            let (source, target) = if group_is_source { (meta_idx, neighbour) } else { (neighbour, meta_idx) };
+            let label = trf("group.edge_label", &[&count]);
+            self.insert_edge(source, target, EdgePayload { label, weight, ..Default::default() });
+        }
+        if let Some(group) = self.groups.groups.get_mut(group_idx) {
+            group.collapsed = Some(Collapsed { meta, nodes, edges });
+        }
+        self.clear_selection();
+    }
+
+    /// Puts the members back around the meta-node. Hidden edges whose other end is now inside
+    /// another collapsed group are handed to that group and merged into its meta-node's edge.
+    pub fn expand_group(&mut self, group_idx: usize) {
+        let Some(meta) = self.groups.groups.get(group_idx).and_then(|g| g.collapsed.as_ref()).map(|c| c.meta) else { return };
+        // A group nested in a collapsed group has to wait for the outer one
+        let Some(meta_idx) = self.ids.egui_node(meta) else { return };
+        let Some(collapsed) = self.groups.groups[group_idx].collapsed.take() else { return };
+        let centre = self.g.node_location(meta_idx).unwrap_or_default();
+        self.detach_node(meta_idx);
+        // Other groups may have hidden the merged edges of the meta-node, which is gone now
+        for group in &mut self.groups.groups {
+            if let Some(other) = group.collapsed.as_mut() {
+                other.edges.retain(|e| e.source != meta && e.target != meta);
+            }
+        }
+
+        // The hidden payloads come back whole: id, pin, shape, overrides and attributes
+        for node in collapsed.nodes {
+            self.insert_node(node.payload, centre + node.offset);
+        }
+
+        for edge in collapsed.edges {
+            let is_visible = |id: NodeId| self.ids.egui_node(id).is_some();
+            let reps = (self.groups.representative(edge.source, is_visible), self.groups.representative(edge.target, is_visible));
+            let (Some(source_rep), Some(target_rep)) = reps else { continue };
+            let (Some(source_idx), Some(target_idx)) = (self.ids.egui_node(source_rep), self.ids.egui_node(target_rep)) else { continue };
+            if source_rep == edge.source && target_rep == edge.target {
+                self.insert_edge(source_idx, target_idx, edge.payload);
+                continue;
+            }
+            if source_rep == target_rep {
+                continue;
+            }
+            let weight = edge.payload.weight;
+            let owner = self.groups.group_of_meta(if source_rep != edge.source { source_rep } else { target_rep });
+            if let Some(other) = owner.and_then(|i| self.groups.groups[i].collapsed.as_mut()) {
+                other.edges.push(edge);
+            }
+            match self.g.find_edge(source_idx, target_idx) {
+                Some(existing) => {
+                    if let Some(payload) = self.get_edge_payload_mut(existing) {
+                        payload.weight += weight;
+                        let payload = payload.clone();
+                        self.update_fdg_edge_payload(existing, payload);
+                    }
+                }
+                None => {
+                    let label = trf("group.edge_label", &[&1]);
+                    self.insert_edge(source_idx, target_idx, EdgePayload { label, weight, ..Default::default() });
+                }
+            }
+        }
+        self.wake_simulation();
+    }
+
+    /// Removes the group but keeps its nodes, expanding it first if needed.
+    pub fn dissolve_group(&mut self, group_idx: usize) {
+        if self.groups.groups.get(group_idx).is_some_and(|g| g.collapsed.is_some()) {
+            self.expand_group(group_idx);
+        }
+        if self.groups.groups.get(group_idx).is_some_and(|g| g.collapsed.is_none()) {
+            self.groups.groups.remove(group_idx);
+        }
+    }
+
+    // Restart an auto-stopped simulation; a manual stop via the checkbox is left alone.
+    pub fn wake_simulation(&mut self) {
+        self.convergence.wake();
+    }
+
+    pub fn handle_events(&mut self, ctx: &Context) {
+        let shift_held = ctx.input(|i| i.modifiers.shift);
+        while let Ok(event) = self.event_consumer.try_recv() {
+            match event {
+                Event::NodeMove(payload) => {
+                    let node_idx = NodeIndex::new(payload.id);
+                    if let Some(sim_idx) = self.sim_node(node_idx) {
+                        if let Some(node_weight_tuple_in_sim) = self.sim.node_weight_mut(sim_idx) {
+                            node_weight_tuple_in_sim.1.coords.x = payload.new_pos[0];
+                            node_weight_tuple_in_sim.1.coords.y = payload.new_pos[1];
+                            self.force_algo.velocities.remove(&sim_idx);
+                        }
+                    }
+                    self.wake_simulation();
+                    // Shift+drag drops the node as a fixed anchor
+                    if shift_held {
+                        self.set_node_pinned(node_idx, true);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    // Pinning is stored in both payloads: the egui one for drawing, the fdg one for the force model.
+    pub fn set_node_pinned(&mut self, node_idx: NodeIndex, pinned: bool) {
+        if let Some(payload) = self.get_node_payload_mut(node_idx) {
+            payload.pinned = pinned;
+        }
+        if let Some(sim_idx) = self.sim_node(node_idx) {
+            if let Some((payload_in_sim, _point)) = self.sim.node_weight_mut(sim_idx) {
+                payload_in_sim.pinned = pinned;
+            }
+            if let Some((payload_in_sim, _point)) = self.sim_3d_node_mut(sim_idx) {
+                payload_in_sim.pinned = pinned;
+            }
+            self.force_algo.velocities.remove(&sim_idx);
+        }
+        self.wake_simulation();
+    }
+
+    pub fn toggle_node_pinned(&mut self, node_idx: NodeIndex) {
+        let pinned = self.g.node_payload(node_idx).is_some_and(|p| p.pinned);
+        self.set_node_pinned(node_idx, !pinned);
+    }
+
+    pub fn set_selected_nodes_pinned(&mut self, pinned: bool) {
+        for node_idx in self.g.selected_nodes() {
+            self.set_node_pinned(node_idx, pinned);
+        }
+    }
+
+    pub fn add_alignment_constraint(&mut self, axis: Axis) {
+        self.add_constraint_from_selection(ConstraintKind::Align(axis));
+    }
+
+    pub fn add_separation_constraint(&mut self) {
+        self.add_constraint_from_selection(ConstraintKind::MinSeparation { distance: self.input_constraint_distance });
+    }
+
+    // Fixes the selected nodes to the region they currently occupy, padded a little
+    pub fn add_bounding_box_constraint(&mut self) {
+        let selected: Vec<NodeIndex> = self.g.selected_nodes().into_iter().filter_map(|idx| self.sim_node(idx)).collect();
+        let Some(bounds) = crate::constraints::nodes_bounds(&self.sim, &selected) else {
+            println!("Select at least one node to create a region constraint.");
+            return;
+        };
+        self.add_constraint_from_selection(ConstraintKind::BoundingBox(bounds.expand(20.0)));
+    }
+
+    // Uses the staged groups A and B: A before B along the axis
+    pub fn add_order_constraint(&mut self, axis: Axis) {
+        if self.constraints.group_a.is_empty() || self.constraints.group_b.is_empty() {
+            println!("Both group A and group B must be set to create an order constraint.");
+            return;
+        }
+        let kind = ConstraintKind::Order { axis, after: self.constraints.group_b.clone(), gap: self.input_constraint_distance };
+        self.constraints.add(Constraint::new(kind, self.constraints.group_a.clone()));
+        self.wake_simulation();
+    }
+
+    fn add_constraint_from_selection(&mut self, kind: ConstraintKind) {
+        let selected = self.selected_node_ids();
+        let min_nodes = if matches!(kind, ConstraintKind::BoundingBox(_)) { 1 } else { 2 };
+        if selected.len() < min_nodes {
+            println!("Select at least {} nodes to create this constraint.", min_nodes);
+            return;
+        }
+        self.constraints.add(Constraint::new(kind, selected));
+        self.wake_simulation();
+    }
+
+    // Updated to accept weight
+    pub fn add_node_ui(&mut self, label: String, weight: f32) -> Option<NodeIndex> {
+        if label.is_empty() {
+            println!("Node label cannot be empty.");
+            return None;
+        }
+        // Labels may repeat; the new node gets its own id either way
+        let x = self.rng.random_range(-50.0..50.0);
+        let y = self.rng.random_range(-50.0..50.0);
+        let payload = NodePayload { label: label.clone(), weight, ..Default::default() };
+        let new_node_idx = self.insert_node(payload, eframe::egui::Pos2::new(x, y));
         println!("Node '{}' added with index {:?}.", label, new_node_idx);
+        Some(new_node_idx)
     }
         // Methods to get mutable payloads for selected elements
         pub fn get_node_payload_mut(&mut self, node_idx: NodeIndex) -> Option<&mut NodePayload> {
@@ -572,87 +1441,96 @@ impl BasicApp {
         // Methods to update fdg simulation payloads if they are distinct
         // For fdg, the NodePayload is part of a tuple (NodePayload, Point)
         // We need to find the node in self.sim and update its NodePayload part.
-        pub fn update_fdg_node_payload(&mut self, node_idx: NodeIndex, new_payload: NodePayload) {
-            if let Some((payload_in_sim, _point)) = self.sim.node_weight_mut(node_idx) {
+        // The weighted force model reads weights straight from these payloads every step,
+        // so a changed weight is picked up by the layout on the next frame.
+        // The payload id finds the simulation node; `node_idx` is only the egui index.
+        pub fn update_fdg_node_payload(&mut self, _node_idx: NodeIndex, new_payload: NodePayload) {
+            let Some(sim_idx) = self.ids.sim_node(new_payload.id) else { return };
+            if let Some((payload_in_sim, _point)) = self.sim_3d_node_mut(sim_idx) {
+                *payload_in_sim = new_payload.clone();
+            }
+            if let Some((payload_in_sim, _point)) = self.sim.node_weight_mut(sim_idx) {
                 *payload_in_sim = new_payload;
             }
+            self.wake_simulation();
         }
         
         // For fdg, EdgePayload is stored directly.
-        pub fn update_fdg_edge_payload(&mut self, edge_idx: EdgeIndex, new_payload: EdgePayload) {
-            if let Some(payload_in_sim) = self.sim.edge_weight_mut(edge_idx) {
+        pub fn update_fdg_edge_payload(&mut self, _edge_idx: EdgeIndex, new_payload: EdgePayload) {
+            let Some(sim_idx) = self.ids.sim_edge(new_payload.id) else { return };
+            let in_step = !self.sim_3d_stale;
+            if let Some(payload_in_sim) = self.sim_3d.edge_weight_mut(sim_idx).filter(|_| in_step) {
+                *payload_in_sim = new_payload.clone();
+            }
+            if let Some(payload_in_sim) = self.sim.edge_weight_mut(sim_idx) {
                 *payload_in_sim = new_payload;
             }
+            self.wake_simulation();
         }
     
-    pub fn remove_node_ui(&mut self, label: String) {
+    pub fn remove_node_ui(&mut self, label: String) -> Result<(), String> {
         if label.is_empty() {
-            println!("Node label to remove cannot be empty.");
-            return;
+            return Err(tr("rename.empty").to_owned());
         }
         
-        if let Some(&node_idx_to_remove) = self.node_label_to_index_map.get(&label) {
-            // Remove from egui_graphs Graph
-            let _ = match &mut self.g { // Explicitly ignore Option<NodePayload>
-                AppGraph::Directed(g) => { g.remove_node(node_idx_to_remove).map(|_| ()); },
-                AppGraph::Undirected(g) => { g.remove_node(node_idx_to_remove).map(|_| ()); },
-            };
-    
-            // Remove from fdg::ForceGraph
-            // fdg::ForceGraph::remove_node takes a NodeIndex and returns Option<(N, Point)>
-            let _removed_node_fdg = self.sim.remove_node(node_idx_to_remove);
-            if _removed_node_fdg.is_none() {
-                println!("Warning: Node {:?} not found in fdg simulation or already removed.", node_idx_to_remove);
+        let node_idx = self.find_node_by_label(&label)?;
+        self.remove_node_by_index(node_idx);
+        Ok(())
+    }
+
+    // Removes a node and its edges from both graphs without touching its groups; collapsing a group relies on that
+    fn detach_node(&mut self, node_idx_to_remove: NodeIndex) -> bool {
+        let Some(payload) = self.g.node_payload(node_idx_to_remove).cloned() else { return false };
+        let incident: Vec<EdgeId> = self.g.edges().into_iter().filter(|(s, t, _)| *s == node_idx_to_remove || *t == node_idx_to_remove).map(|(_, _, p)| p.id).collect();
+        // Remove from egui_graphs Graph
+        let _ = match &mut self.g { // Explicitly ignore Option<NodePayload>
+            AppGraph::Directed(g) => { g.remove_node(node_idx_to_remove).map(|_| ()); },
+            AppGraph::Undirected(g) => { g.remove_node(node_idx_to_remove).map(|_| ()); },
+        };
+
+        // Remove from fdg::ForceGraph, which drops the node's edges there as well
+        match self.ids.sim_node(payload.id) {
+            Some(sim_idx) => {
+                self.sim.remove_node(sim_idx);
+                self.force_algo.velocities.remove(&sim_idx);
             }
-    
-            self.node_label_to_index_map.remove(&label);
-            self.graph_nodes_count = self.node_label_to_index_map.len(); // Update count based on map
-            
-            // Also update edge count as edges connected to this node are removed automatically by petgraph
-             self.graph_edges_count = match &self.g {
-                AppGraph::Directed(g) => g.edge_count(),
-                AppGraph::Undirected(g) => g.edge_count(),
-            };
+            None => println!("Warning: Node {:?} not found in fdg simulation or already removed.", payload.id),
+        }
 
-            println!("Node '{}' ({:?}) removed.", label, node_idx_to_remove);
-        } else {
-            println!("Node with label '{}' not found for removal.", label);
+        self.ids.remove_node(payload.id);
+        for id in incident {
+            self.ids.remove_edge(id);
         }
+        self.graph_nodes_count = self.ids.node_count();
+        
+        // Also update edge count as edges connected to this node are removed automatically by petgraph
+         self.graph_edges_count = match &self.g {
+            AppGraph::Directed(g) => g.edge_count(),
+            AppGraph::Undirected(g) => g.edge_count(),
+        };
+
+        self.structure_changed();
+        println!("Node '{}' ({:?}) removed.", payload.label, node_idx_to_remove);
+        true
     }
 
 
     // Updated to accept weight
-    pub fn add_edge_ui(&mut self, from_label: String, to_label: String, weight: f32) {
+    pub fn add_edge_ui(&mut self, from_label: String, to_label: String, weight: f32) -> Result<(), String> {
         if from_label.is_empty() || to_label.is_empty() {
-            println!("Node labels for adding edge cannot be empty.");
-            return;
+            return Err(tr("rename.empty").to_owned());
         }
-        if from_label == to_label {
-            println!("Cannot add self-loop via UI with identical labels.");
-            return;
-        }
-
-        let n1_idx_opt = self.node_label_to_index_map.get(&from_label).copied();
-        let n2_idx_opt = self.node_label_to_index_map.get(&to_label).copied();
 
-        if let (Some(n1_idx), Some(n2_idx)) = (n1_idx_opt, n2_idx_opt) {
-            let edge_label = format!("边: {}->{}", from_label, to_label);
-            let edge_payload = EdgePayload { label: edge_label, weight };
-
-            let _ = match &mut self.g { // Ignore return value
-                AppGraph::Directed(g) => { g.add_edge(n1_idx, n2_idx, edge_payload.clone()); },
-                AppGraph::Undirected(g) => { g.add_edge(n1_idx, n2_idx, edge_payload.clone()); },
-            };
-            self.sim.add_edge(n1_idx, n2_idx, edge_payload);
-            self.graph_edges_count = match &self.g {
-                AppGraph::Directed(g) => g.edge_count(),
-                AppGraph::Undirected(g) => g.edge_count(),
-            };
-            println!("Edge added between '{}' ({:?}) and '{}' ({:?})", from_label, n1_idx, to_label, n2_idx);
-        } else {
-            if n1_idx_opt.is_none() { println!("Node '{}' not found.", from_label); }
-            if n2_idx_opt.is_none() { println!("Node '{}' not found.", to_label); }
+        let n1_idx = self.find_node_by_label(&from_label)?;
+        let n2_idx = self.find_node_by_label(&to_label)?;
+        if n1_idx == n2_idx {
+            return Err(tr("edit.self_loop").to_owned());
         }
+        let edge_label = trf("gen.edge_between", &[&from_label, &to_label]);
+        let edge_payload = EdgePayload { label: edge_label, weight, ..Default::default() };
+        self.insert_edge(n1_idx, n2_idx, edge_payload);
+        println!("Edge added between '{}' ({:?}) and '{}' ({:?})", from_label, n1_idx, to_label, n2_idx);
+        Ok(())
     }
     
     // This function is for the button "Add Edge Between Selected"
@@ -680,19 +1558,10 @@ impl BasicApp {
                 AppGraph::Undirected(g) => g.node(n2_idx).map_or_else(|| "N/A".to_string(), |n| n.payload().label.clone()),
             };
 
-            let edge_label = format!("边: {}->{}", n1_label, n2_label);
+            let edge_label = trf("gen.edge_between", &[&n1_label, &n2_label]);
             // Use default weight or input_edge_weight if we add UI for it here
-            let edge_payload = EdgePayload { label: edge_label, weight: self.input_edge_weight };
-
-            let _ = match &mut self.g {
-                AppGraph::Directed(g) => { g.add_edge(n1_idx, n2_idx, edge_payload.clone()); },
-                AppGraph::Undirected(g) => { g.add_edge(n1_idx, n2_idx, edge_payload.clone()); },
-            };
-            self.sim.add_edge(n1_idx, n2_idx, edge_payload);
-            self.graph_edges_count = match &self.g {
-                AppGraph::Directed(g) => g.edge_count(),
-                AppGraph::Undirected(g) => g.edge_count(),
-            };
+            let edge_payload = EdgePayload { label: edge_label, weight: self.input_edge_weight, ..Default::default() };
+            self.insert_edge(n1_idx, n2_idx, edge_payload);
             println!("Edge added between selected {:?} and {:?}", n1_idx, n2_idx);
         } else {
             println!("Please select exactly two nodes to add an edge.");
@@ -711,25 +1580,15 @@ impl BasicApp {
         }
 
         for edge_idx in selected_edge_indices {
-            match &mut self.g {
-                AppGraph::Directed(g) => { g.remove_edge(edge_idx); },
-                AppGraph::Undirected(g) => { g.remove_edge(edge_idx); },
-            };
-            let _removed_edge_payload_fdg = self.sim.remove_edge(edge_idx);
-            if _removed_edge_payload_fdg.is_none() {
-                println!("Warning: Edge {:?} not found in fdg simulation or already removed.", edge_idx);
-            }
+            self.remove_edge(edge_idx);
             println!("Edge {:?} removed.", edge_idx);
         }
 
-        self.graph_edges_count = match &self.g {
-            AppGraph::Directed(g) => g.edge_count(),
-            AppGraph::Undirected(g) => g.edge_count(),
-        };
         match &mut self.g {
             AppGraph::Directed(g) => { g.set_selected_edges(Default::default()); }
             AppGraph::Undirected(g) => { g.set_selected_edges(Default::default()); }
         };
+        self.wake_simulation();
     }
 } // This closes impl BasicApp block that starts at line 78
 
@@ -793,11 +1652,31 @@ impl BasicApp {
 impl App for BasicApp {
     fn update(&mut self, ctx: &Context, frame: &mut eframe::Frame) {
         // Event handling should happen early
-        self.handle_events();
+        self.handle_events(ctx);
         
         // Simulation update
+        if self.overlap.continuous {
+            self.overlap.measure(ctx, &self.sim, self.style_labels_always, self.theme.active().label_size);
+        }
+        if self.view_3d && self.sim_3d_stale {
+            self.refresh_3d_simulation();
+        }
         self.update_simulation();
-        Self::sync_node_positions_to_egui(&self.sim, &mut self.g, &self.node_label_to_index_map);
+        if !self.view_3d {
+            Self::sync_node_positions_to_egui(&self.sim, &mut self.g, &self.ids);
+        }
+
+        self.visual_mapping.apply(&mut self.g);
+        self.node_shapes.apply(ctx, &mut self.g);
+        self.edge_render.apply(&mut self.g);
+        self.theme.apply(ctx, &mut self.g);
+        crate::shapes::apply_style_overrides(&mut self.g);
+        self.search.apply(&mut self.g);
+        self.focus.track_selection(&self.g);
+        self.focus.apply(&mut self.g, &self.ids);
+        if let Some(nodes) = self.focus.take_layout_request() {
+            self.run_local_layout(&nodes);
+        }
 
         // Draw settings panel (this will be moved to settings_panel.rs)
         crate::settings_panel::draw_settings_panel(self, ctx);
@@ -805,4 +1684,11 @@ impl App for BasicApp {
         // Draw graph view (this will be moved to graph_view.rs)
         crate::graph_view::draw_graph_view(self, ctx, frame);
     }
+
+    fn save(&mut self, storage: &mut dyn eframe::Storage) {
+        eframe::set_value(storage, THEME_STORAGE_KEY, &self.theme);
+        eframe::set_value(storage, FONT_STORAGE_KEY, &self.fonts);
+        eframe::set_value(storage, LANGUAGE_STORAGE_KEY, &self.language);
+        eframe::set_value(storage, KEYMAP_STORAGE_KEY, &self.keymap);
+    }
 }
\ No newline at end of file
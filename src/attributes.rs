@@ -0,0 +1,325 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use egui::{Color32, Ui};
+use serde::{Deserialize, Serialize};
+use crate::app::AppGraph;
+use crate::i18n::{tr, trf};
+
+/// Free-form data attached to a node or edge. Sorted, so the table keeps its row order.
+pub type Attributes = BTreeMap<String, AttrValue>;
+
+/// Attribute names and the type each one has across the graph.
+pub type Schema = BTreeMap<String, AttrType>;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum AttrType {
+    String,
+    Number,
+    Bool,
+    Color,
+    List,
+}
+
+impl AttrType {
+    pub const ALL: [AttrType; 5] = [AttrType::String, AttrType::Number, AttrType::Bool, AttrType::Color, AttrType::List];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            AttrType::String => tr("attr_type.string"),
+            AttrType::Number => tr("attr_type.number"),
+            AttrType::Bool => tr("attr_type.bool"),
+            AttrType::Color => tr("attr_type.color"),
+            AttrType::List => tr("attr_type.list"),
+        }
+    }
+
+    pub fn default_value(&self) -> AttrValue {
+        match self {
+            AttrType::String => AttrValue::String(String::new()),
+            AttrType::Number => AttrValue::Number(0.0),
+            AttrType::Bool => AttrValue::Bool(false),
+            AttrType::Color => AttrValue::Color(Color32::GRAY),
+            AttrType::List => AttrValue::List(Vec::new()),
+        }
+    }
+
+    /// Values of these types can drive size, width, opacity and ramp colours.
+    pub fn is_numeric(&self) -> bool {
+        matches!(self, AttrType::Number | AttrType::Bool)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "value", rename_all = "lowercase")]
+pub enum AttrValue {
+    String(String),
+    Number(f64),
+    Bool(bool),
+    Color(Color32),
+    List(Vec<AttrValue>),
+}
+
+impl AttrValue {
+    pub fn ty(&self) -> AttrType {
+        match self {
+            AttrValue::String(_) => AttrType::String,
+            AttrValue::Number(_) => AttrType::Number,
+            AttrValue::Bool(_) => AttrType::Bool,
+            AttrValue::Color(_) => AttrType::Color,
+            AttrValue::List(_) => AttrType::List,
+        }
+    }
+
+    /// Reads untyped text (a form field, a CSV cell) as the most specific type it fits:
+    /// bool, number, `#rrggbb` colour, comma-separated list, and string otherwise.
+    pub fn infer(text: &str) -> AttrValue {
+        let text = text.trim();
+        if text.contains(',') {
+            return AttrValue::List(split_list(text));
+        }
+        infer_scalar(text)
+    }
+
+    /// Reads text as the given type.
+    pub fn parse(text: &str, ty: AttrType) -> Result<AttrValue, String> {
+        let text = text.trim();
+        let invalid = || trf("attr.invalid", &[&text, &ty.label()]);
+        match ty {
+            AttrType::String => Ok(AttrValue::String(text.to_owned())),
+            AttrType::Number => text.parse::<f64>().ok().filter(|n| n.is_finite()).map(AttrValue::Number).ok_or_else(invalid),
+            AttrType::Bool => parse_bool(text).map(AttrValue::Bool).ok_or_else(invalid),
+            AttrType::Color => parse_color(text).map(AttrValue::Color).ok_or_else(invalid),
+            AttrType::List if text.is_empty() => Ok(AttrValue::List(Vec::new())),
+            AttrType::List => Ok(AttrValue::List(split_list(text))),
+        }
+    }
+
+    /// The value as another type, through its text form. Falls back to the type's default.
+    pub fn convert(&self, ty: AttrType) -> AttrValue {
+        if self.ty() == ty {
+            return self.clone();
+        }
+        AttrValue::parse(&self.to_string(), ty).unwrap_or_else(|_| ty.default_value())
+    }
+
+    /// Numbers as they are, booleans as 0 or 1.
+    pub fn as_number(&self) -> Option<f64> {
+        match self {
+            AttrValue::Number(n) => Some(*n),
+            AttrValue::Bool(b) => Some(if *b { 1.0 } else { 0.0 }),
+            _ => None,
+        }
+    }
+
+    /// Case-insensitive: substring of the text form, or an element of a list.
+    pub fn contains(&self, needle: &str) -> bool {
+        let needle = needle.trim().to_lowercase();
+        match self {
+            AttrValue::List(items) => items.iter().any(|item| item.to_string().to_lowercase() == needle),
+            other => other.to_string().to_lowercase().contains(&needle),
+        }
+    }
+}
+
+impl fmt::Display for AttrValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AttrValue::String(s) => write!(f, "{}", s),
+            AttrValue::Number(n) => write!(f, "{}", n),
+            AttrValue::Bool(b) => write!(f, "{}", b),
+            AttrValue::Color(c) if c.a() == 255 => write!(f, "#{:02x}{:02x}{:02x}", c.r(), c.g(), c.b()),
+            AttrValue::Color(c) => write!(f, "#{:02x}{:02x}{:02x}{:02x}", c.r(), c.g(), c.b(), c.a()),
+            AttrValue::List(items) => {
+                let items: Vec<String> = items.iter().map(|item| item.to_string()).collect();
+                write!(f, "{}", items.join(", "))
+            }
+        }
+    }
+}
+
+fn infer_scalar(text: &str) -> AttrValue {
+    if let Some(b) = parse_bool(text) {
+        AttrValue::Bool(b)
+    } else if let Some(n) = text.parse::<f64>().ok().filter(|n| n.is_finite()) {
+        AttrValue::Number(n)
+    } else if let Some(c) = parse_color(text) {
+        AttrValue::Color(c)
+    } else {
+        AttrValue::String(text.to_owned())
+    }
+}
+
+fn split_list(text: &str) -> Vec<AttrValue> {
+    text.split(',').map(|item| infer_scalar(item.trim())).collect()
+}
+
+fn parse_bool(text: &str) -> Option<bool> {
+    match text.to_lowercase().as_str() {
+        "true" => Some(true),
+        "false" => Some(false),
+        _ => None,
+    }
+}
+
+/// `#rrggbb` or `#rrggbbaa`.
+fn parse_color(text: &str) -> Option<Color32> {
+    let hex = text.strip_prefix('#')?;
+    if !matches!(hex.len(), 6 | 8) || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let byte = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+    let alpha = if hex.len() == 8 { byte(6)? } else { 255 };
+    Some(Color32::from_rgba_unmultiplied(byte(0)?, byte(2)?, byte(4)?, alpha))
+}
+
+/// Type of every attribute name seen in `maps`. A name used with different types is a string.
+pub fn infer_schema<'a>(maps: impl Iterator<Item = &'a Attributes>) -> Schema {
+    let mut schema = Schema::new();
+    for attributes in maps {
+        for (key, value) in attributes {
+            schema
+                .entry(key.clone())
+                .and_modify(|ty| {
+                    if *ty != value.ty() {
+                        *ty = AttrType::String;
+                    }
+                })
+                .or_insert(value.ty());
+        }
+    }
+    schema
+}
+
+pub fn node_schema(g: &AppGraph) -> Schema {
+    let payloads: Vec<_> = g.node_indices().into_iter().filter_map(|idx| g.node_payload(idx)).collect();
+    infer_schema(payloads.into_iter().map(|p| &p.attributes))
+}
+
+pub fn edge_schema(g: &AppGraph) -> Schema {
+    let edges = g.edges();
+    infer_schema(edges.iter().map(|(_, _, p)| &p.attributes))
+}
+
+/// The "new attribute" row of the table. Shared by the node and edge tables.
+#[derive(Default)]
+pub struct AttributeDraft {
+    pub key: String,
+    pub ty: Option<AttrType>, // None: inferred from the value
+    pub value: String,
+    pub error: Option<String>,
+}
+
+fn value_editor(ui: &mut Ui, value: &mut AttrValue) -> bool {
+    // Lists are edited as their comma-separated text
+    if matches!(value, AttrValue::List(_)) {
+        let mut text = value.to_string();
+        let changed = ui.add(egui::TextEdit::singleline(&mut text).desired_width(120.0)).changed();
+        if let (true, Ok(list)) = (changed, AttrValue::parse(&text, AttrType::List)) {
+            *value = list;
+        }
+        return changed;
+    }
+    match value {
+        AttrValue::String(s) => ui.add(egui::TextEdit::singleline(s).desired_width(120.0)).changed(),
+        AttrValue::Number(n) => ui.add(egui::DragValue::new(n).speed(0.1)).changed(),
+        AttrValue::Bool(b) => ui.checkbox(b, "").changed(),
+        AttrValue::Color(c) => ui.color_edit_button_srgba(c).changed(),
+        AttrValue::List(_) => false,
+    }
+}
+
+fn type_combo(ui: &mut Ui, id: impl std::hash::Hash, ty: &mut AttrType) -> bool {
+    let before = *ty;
+    egui::ComboBox::from_id_salt(id).selected_text(ty.label()).width(70.0).show_ui(ui, |ui| {
+        for option in AttrType::ALL {
+            ui.selectable_value(ty, option, option.label());
+        }
+    });
+    *ty != before
+}
+
+/// Editable table of one element's attributes. Returns true if anything changed.
+pub fn draw_attribute_table(ui: &mut Ui, id: &str, attributes: &mut Attributes, draft: &mut AttributeDraft) -> bool {
+    let mut changed = false;
+    let mut removed = None;
+    egui::Grid::new((id, "attributes")).num_columns(4).striped(true).show(ui, |ui| {
+        for (key, value) in attributes.iter_mut() {
+            ui.label(key);
+            let mut ty = value.ty();
+            if type_combo(ui, (id, key), &mut ty) {
+                *value = value.convert(ty);
+                changed = true;
+            }
+            changed |= value_editor(ui, value);
+            if ui.small_button("✖").on_hover_text(tr("common.remove")).clicked() {
+                removed = Some(key.clone());
+            }
+            ui.end_row();
+        }
+    });
+    if let Some(key) = removed {
+        attributes.remove(&key);
+        changed = true;
+    }
+
+    ui.horizontal(|ui| {
+        ui.add(egui::TextEdit::singleline(&mut draft.key).hint_text(tr("attr.new_key")).desired_width(70.0));
+        let selected = draft.ty.map_or(tr("attr_type.auto"), |ty| ty.label());
+        egui::ComboBox::from_id_salt((id, "new_attribute_type")).selected_text(selected).width(70.0).show_ui(ui, |ui| {
+            ui.selectable_value(&mut draft.ty, None, tr("attr_type.auto"));
+            for option in AttrType::ALL {
+                ui.selectable_value(&mut draft.ty, Some(option), option.label());
+            }
+        });
+        ui.add(egui::TextEdit::singleline(&mut draft.value).hint_text(tr("attr.new_value")).desired_width(80.0));
+        if ui.button(tr("common.add")).clicked() {
+            let key = draft.key.trim().to_owned();
+            let value = match draft.ty {
+                Some(ty) => AttrValue::parse(&draft.value, ty),
+                None => Ok(AttrValue::infer(&draft.value)),
+            };
+            draft.error = None;
+            if key.is_empty() {
+                draft.error = Some(tr("attr.empty_key").to_owned());
+            } else if attributes.contains_key(&key) {
+                draft.error = Some(trf("attr.duplicate_key", &[&key]));
+            } else {
+                match value {
+                    Ok(value) => {
+                        attributes.insert(key, value);
+                        draft.key.clear();
+                        draft.value.clear();
+                        changed = true;
+                    }
+                    Err(error) => draft.error = Some(error),
+                }
+            }
+        }
+    });
+    if let Some(error) = &draft.error {
+        ui.colored_label(ui.visuals().error_fg_color, error);
+    }
+    changed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn infer_picks_the_most_specific_type() {
+        assert_eq!(AttrValue::infer(" TRUE "), AttrValue::Bool(true));
+        assert_eq!(AttrValue::infer("2.5"), AttrValue::Number(2.5));
+        assert_eq!(AttrValue::infer("#ff000080"), AttrValue::Color(Color32::from_rgba_unmultiplied(255, 0, 0, 128)));
+        assert_eq!(AttrValue::infer("#ff00"), AttrValue::String("#ff00".to_owned()));
+        assert_eq!(AttrValue::infer("inf"), AttrValue::String("inf".to_owned()));
+        assert_eq!(AttrValue::infer("1, x"), AttrValue::List(vec![AttrValue::Number(1.0), AttrValue::String("x".to_owned())]));
+    }
+
+    #[test]
+    fn parse_refuses_text_of_the_wrong_type() {
+        assert!(AttrValue::parse("abc", AttrType::Number).is_err());
+        assert_eq!(AttrValue::parse("", AttrType::List), Ok(AttrValue::List(Vec::new())));
+        assert_eq!(AttrValue::Number(3.0).convert(AttrType::Bool), AttrValue::Bool(false));
+    }
+}
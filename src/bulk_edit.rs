@@ -0,0 +1,408 @@
+use egui::{Color32, Ui};
+use petgraph::stable_graph::{EdgeIndex, NodeIndex};
+use crate::app::BasicApp;
+use crate::ids::{EdgeId, NodeId};
+use crate::i18n::{tr, trf};
+use crate::shapes::{DashStyle, EdgeOverrides, NodeOverrides, DEFAULT_EDGE_WIDTH, DEFAULT_NODE_RADIUS};
+
+/// How the bulk editor changes the weights of the selected elements.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum WeightOp {
+    #[default]
+    Keep,
+    Set,
+    Scale,
+    Offset,
+}
+
+impl WeightOp {
+    pub const ALL: [WeightOp; 4] = [WeightOp::Keep, WeightOp::Set, WeightOp::Scale, WeightOp::Offset];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            WeightOp::Keep => tr("bulk.keep"),
+            WeightOp::Set => tr("bulk.weight_set"),
+            WeightOp::Scale => tr("bulk.weight_scale"),
+            WeightOp::Offset => tr("bulk.weight_offset"),
+        }
+    }
+
+    // Weights never go negative, like everywhere else they can be entered
+    fn apply(&self, weight: f32, value: f32) -> f32 {
+        let weight = match self {
+            WeightOp::Keep => weight,
+            WeightOp::Set => value,
+            WeightOp::Scale => weight * value,
+            WeightOp::Offset => weight + value,
+        };
+        weight.max(0.0)
+    }
+}
+
+/// What happens to one style override of every selected element.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OverrideChange {
+    #[default]
+    Keep,
+    Set,
+    Clear, // Back to the mapped/theme style
+}
+
+impl OverrideChange {
+    pub const ALL: [OverrideChange; 3] = [OverrideChange::Keep, OverrideChange::Set, OverrideChange::Clear];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            OverrideChange::Keep => tr("bulk.keep"),
+            OverrideChange::Set => tr("bulk.set"),
+            OverrideChange::Clear => tr("bulk.clear"),
+        }
+    }
+
+    fn apply<T>(&self, current: Option<T>, value: T) -> Option<T> {
+        match self {
+            OverrideChange::Keep => current,
+            OverrideChange::Set => Some(value),
+            OverrideChange::Clear => None,
+        }
+    }
+}
+
+// Labels, weights and overrides of a set of elements. Keyed by id: a deleted element's index
+// can be handed to a new one, which must not be touched by an undo.
+struct BulkSnapshot {
+    nodes: Vec<(NodeId, String, f32, NodeOverrides)>,
+    edges: Vec<(EdgeId, String, f32, EdgeOverrides)>,
+}
+
+impl BulkSnapshot {
+    fn capture(app: &BasicApp, nodes: &[NodeIndex], edges: &[EdgeIndex]) -> Self {
+        Self {
+            nodes: nodes.iter().filter_map(|idx| app.g.node_payload(*idx)).map(|p| (p.id, p.label.clone(), p.weight, p.overrides)).collect(),
+            edges: edges.iter().filter_map(|idx| app.g.edge_payload(*idx)).map(|p| (p.id, p.label.clone(), p.weight, p.overrides)).collect(),
+        }
+    }
+
+    // Writes the values onto the elements that still exist
+    fn write(self, app: &mut BasicApp) -> Result<(), String> {
+        for (id, label, weight, overrides) in self.nodes {
+            let Some(idx) = app.ids.egui_node(id) else { continue };
+            app.rename_node(idx, label)?;
+            set_node_values(app, idx, weight, overrides);
+        }
+        for (id, label, weight, overrides) in self.edges {
+            let Some(idx) = app.ids.egui_edge(id) else { continue };
+            app.rename_edge(idx, label);
+            set_edge_values(app, idx, weight, overrides);
+        }
+        Ok(())
+    }
+}
+
+/// Inputs of the multi-selection editor, plus the state needed to undo its last edit.
+pub struct BulkEditor {
+    pub weight_op: WeightOp,
+    pub weight_value: f32,
+    pub rename_pattern: String, // Empty: labels stay
+    pub rename_start: usize,
+    pub node_radius: (OverrideChange, f32),
+    pub node_color: (OverrideChange, Color32),
+    pub node_label_color: (OverrideChange, Color32),
+    pub edge_width: (OverrideChange, f32),
+    pub edge_color: (OverrideChange, Color32),
+    pub edge_dash: (OverrideChange, DashStyle),
+    pub error: Option<String>,
+    undo: Option<BulkSnapshot>,
+}
+
+impl Default for BulkEditor {
+    fn default() -> Self {
+        Self {
+            weight_op: WeightOp::Keep,
+            weight_value: 1.0,
+            rename_pattern: String::new(),
+            rename_start: 1,
+            node_radius: (OverrideChange::Keep, DEFAULT_NODE_RADIUS),
+            node_color: (OverrideChange::Keep, Color32::LIGHT_BLUE),
+            node_label_color: (OverrideChange::Keep, Color32::WHITE),
+            edge_width: (OverrideChange::Keep, DEFAULT_EDGE_WIDTH),
+            edge_color: (OverrideChange::Keep, Color32::GRAY),
+            edge_dash: (OverrideChange::Keep, DashStyle::Dashed),
+            error: None,
+            undo: None,
+        }
+    }
+}
+
+impl BulkEditor {
+    pub fn can_undo(&self) -> bool {
+        self.undo.is_some()
+    }
+
+    /// Forgets the last edit, for when the whole graph is replaced.
+    pub fn clear_undo(&mut self) {
+        self.undo = None;
+        self.error = None;
+    }
+
+    fn node_overrides(&self, current: NodeOverrides) -> NodeOverrides {
+        NodeOverrides {
+            radius: self.node_radius.0.apply(current.radius, self.node_radius.1),
+            color: self.node_color.0.apply(current.color, self.node_color.1),
+            label_color: self.node_label_color.0.apply(current.label_color, self.node_label_color.1),
+        }
+    }
+
+    fn edge_overrides(&self, current: EdgeOverrides) -> EdgeOverrides {
+        EdgeOverrides {
+            width: self.edge_width.0.apply(current.width, self.edge_width.1),
+            color: self.edge_color.0.apply(current.color, self.edge_color.1),
+            dash: self.edge_dash.0.apply(current.dash, self.edge_dash.1),
+        }
+    }
+}
+
+/// Expands a rename pattern: `{n}` is the running number, `{label}` the current label.
+pub fn pattern_label(pattern: &str, n: usize, label: &str) -> String {
+    pattern.replace("{n}", &n.to_string()).replace("{label}", label).trim().to_owned()
+}
+
+/// Applies the editor's inputs to every selected node and edge at once, as one undoable step.
+/// Nothing changes if the rename would leave a node without a label.
+pub fn apply(app: &mut BasicApp, nodes: &[NodeIndex], edges: &[EdgeIndex]) -> Result<(), String> {
+    let before = BulkSnapshot::capture(app, nodes, edges);
+
+    // Everything is computed and checked before the first change
+    let editor = &app.bulk_edit;
+    let (op, value) = (editor.weight_op, editor.weight_value);
+    let pattern = editor.rename_pattern.trim();
+    // Nodes are numbered first, then edges, in selection order
+    let relabel = |n: usize, label: &str| if pattern.is_empty() { label.to_owned() } else { pattern_label(pattern, editor.rename_start + n, label) };
+    let after = BulkSnapshot {
+        nodes: before.nodes.iter().enumerate().map(|(i, (id, label, weight, overrides))| (*id, relabel(i, label), op.apply(*weight, value), editor.node_overrides(*overrides))).collect(),
+        edges: before.edges.iter().enumerate().map(|(i, (id, label, weight, overrides))| (*id, relabel(before.nodes.len() + i, label), op.apply(*weight, value), editor.edge_overrides(*overrides))).collect(),
+    };
+    // Labels may repeat, only an empty pattern result is refused
+    if after.nodes.iter().any(|(_, label, ..)| label.is_empty()) {
+        return Err(tr("rename.empty").to_owned());
+    }
+
+    app.bulk_edit.undo = Some(before);
+    after.write(app)
+}
+
+/// Puts back what the last bulk edit changed. Elements deleted since then are skipped.
+pub fn undo(app: &mut BasicApp) -> Result<(), String> {
+    let Some(snapshot) = app.bulk_edit.undo.take() else { return Ok(()) };
+    snapshot.write(app)
+}
+
+fn set_node_values(app: &mut BasicApp, idx: NodeIndex, weight: f32, overrides: NodeOverrides) {
+    let Some(payload) = app.get_node_payload_mut(idx) else { return };
+    payload.weight = weight;
+    payload.overrides = overrides;
+    let payload = payload.clone();
+    app.update_fdg_node_payload(idx, payload);
+}
+
+fn set_edge_values(app: &mut BasicApp, idx: EdgeIndex, weight: f32, overrides: EdgeOverrides) {
+    let Some(payload) = app.get_edge_payload_mut(idx) else { return };
+    payload.weight = weight;
+    payload.overrides = overrides;
+    let payload = payload.clone();
+    app.update_fdg_edge_payload(idx, payload);
+}
+
+// The common value, the default marker when none is set, or "mixed"
+fn shared_text<T: PartialEq + Copy>(values: &[Option<T>], show: impl Fn(T) -> String) -> String {
+    match values.first() {
+        Some(first) if values.iter().all(|v| v == first) => first.map_or_else(|| tr("bulk.default").to_owned(), show),
+        Some(_) => tr("bulk.mixed").to_owned(),
+        None => String::new(),
+    }
+}
+
+fn weight_text(weights: &[f32]) -> String {
+    let min = weights.iter().copied().fold(f32::INFINITY, f32::min);
+    let max = weights.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+    if weights.is_empty() {
+        String::new()
+    } else if min == max {
+        format!("{:.2}", min)
+    } else {
+        trf("bulk.weight_range", &[&format!("{:.2}", min), &format!("{:.2}", max)])
+    }
+}
+
+fn color_text(color: Color32) -> String {
+    format!("#{:02x}{:02x}{:02x}", color.r(), color.g(), color.b())
+}
+
+fn change_combo(ui: &mut Ui, id: &str, change: &mut OverrideChange) {
+    egui::ComboBox::from_id_salt(id).selected_text(change.label()).show_ui(ui, |ui| {
+        for option in OverrideChange::ALL {
+            ui.selectable_value(change, option, option.label());
+        }
+    });
+}
+
+/// One grid row: property name, shared value, what to do with it and the new value.
+fn override_row<T>(ui: &mut Ui, id: &str, name: &str, shared: String, change: &mut (OverrideChange, T), editor: impl FnOnce(&mut Ui, &mut T)) {
+    ui.label(name);
+    ui.label(shared);
+    change_combo(ui, id, &mut change.0);
+    ui.add_enabled_ui(change.0 == OverrideChange::Set, |ui| editor(ui, &mut change.1));
+    ui.end_row();
+}
+
+/// The property editor for a selection of several nodes and/or edges.
+pub fn draw_bulk_editor(app: &mut BasicApp, ui: &mut Ui, nodes: &[NodeIndex], edges: &[EdgeIndex]) {
+    ui.label(trf("bulk.summary", &[&nodes.len(), &edges.len()]));
+    let node_payloads: Vec<_> = nodes.iter().filter_map(|idx| app.g.node_payload(*idx).cloned()).collect();
+    let edge_payloads: Vec<_> = edges.iter().filter_map(|idx| app.g.edge_payload(*idx).cloned()).collect();
+    let editor = &mut app.bulk_edit;
+
+    egui::Grid::new("bulk_edit_grid").num_columns(4).striped(true).show(ui, |ui| {
+        ui.label("");
+        ui.label(tr("bulk.current"));
+        ui.label(tr("bulk.change"));
+        ui.label(tr("bulk.new_value"));
+        ui.end_row();
+
+        let weights: Vec<f32> = node_payloads.iter().map(|p| p.weight).chain(edge_payloads.iter().map(|p| p.weight)).collect();
+        ui.label(tr("bulk.weight"));
+        ui.label(weight_text(&weights));
+        egui::ComboBox::from_id_salt("bulk_weight_op").selected_text(editor.weight_op.label()).show_ui(ui, |ui| {
+            for op in WeightOp::ALL {
+                ui.selectable_value(&mut editor.weight_op, op, op.label());
+            }
+        });
+        ui.add_enabled(editor.weight_op != WeightOp::Keep, egui::DragValue::new(&mut editor.weight_value).speed(0.1));
+        ui.end_row();
+
+        if !node_payloads.is_empty() {
+            let overrides: Vec<NodeOverrides> = node_payloads.iter().map(|p| p.overrides).collect();
+            let radii: Vec<_> = overrides.iter().map(|o| o.radius).collect();
+            override_row(ui, "bulk_node_radius", tr("bulk.node_radius"), shared_text(&radii, |r| format!("{:.1}", r)), &mut editor.node_radius, |ui, value| {
+                ui.add(egui::DragValue::new(value).speed(0.1).range(1.0..=100.0));
+            });
+            let colors: Vec<_> = overrides.iter().map(|o| o.color).collect();
+            override_row(ui, "bulk_node_color", tr("bulk.node_color"), shared_text(&colors, color_text), &mut editor.node_color, |ui, value| {
+                ui.color_edit_button_srgba(value);
+            });
+            let label_colors: Vec<_> = overrides.iter().map(|o| o.label_color).collect();
+            override_row(ui, "bulk_node_label_color", tr("bulk.node_label_color"), shared_text(&label_colors, color_text), &mut editor.node_label_color, |ui, value| {
+                ui.color_edit_button_srgba(value);
+            });
+        }
+        if !edge_payloads.is_empty() {
+            let overrides: Vec<EdgeOverrides> = edge_payloads.iter().map(|p| p.overrides).collect();
+            let widths: Vec<_> = overrides.iter().map(|o| o.width).collect();
+            override_row(ui, "bulk_edge_width", tr("bulk.edge_width"), shared_text(&widths, |w| format!("{:.1}", w)), &mut editor.edge_width, |ui, value| {
+                ui.add(egui::DragValue::new(value).speed(0.1).range(0.5..=20.0));
+            });
+            let colors: Vec<_> = overrides.iter().map(|o| o.color).collect();
+            override_row(ui, "bulk_edge_color", tr("bulk.edge_color"), shared_text(&colors, color_text), &mut editor.edge_color, |ui, value| {
+                ui.color_edit_button_srgba(value);
+            });
+            let dashes: Vec<_> = overrides.iter().map(|o| o.dash).collect();
+            override_row(ui, "bulk_edge_dash", tr("bulk.edge_dash"), shared_text(&dashes, |d| d.label().to_owned()), &mut editor.edge_dash, |ui, value| {
+                egui::ComboBox::from_id_salt("bulk_edge_dash_value").selected_text(value.label()).show_ui(ui, |ui| {
+                    for dash in DashStyle::ALL {
+                        ui.selectable_value(value, dash, dash.label());
+                    }
+                });
+            });
+        }
+    });
+
+    ui.horizontal(|ui| {
+        ui.label(tr("bulk.rename"));
+        ui.add(egui::TextEdit::singleline(&mut editor.rename_pattern).hint_text(tr("bulk.rename_hint")).desired_width(110.0))
+            .on_hover_text(tr("bulk.rename_help"));
+        ui.add(egui::DragValue::new(&mut editor.rename_start).prefix(tr("bulk.rename_start")));
+    });
+    if !editor.rename_pattern.trim().is_empty() {
+        if let Some(first) = node_payloads.first().map(|p| &p.label).or(edge_payloads.first().map(|p| &p.label)) {
+            ui.weak(trf("bulk.rename_preview", &[&pattern_label(&editor.rename_pattern, editor.rename_start, first)]));
+        }
+    }
+
+    let mut apply_clicked = false;
+    let mut undo_clicked = false;
+    ui.horizontal(|ui| {
+        apply_clicked = ui.button(tr("bulk.apply")).clicked();
+        undo_clicked = ui.add_enabled(editor.can_undo(), egui::Button::new(tr("bulk.undo"))).clicked();
+    });
+    if let Some(error) = &editor.error {
+        ui.colored_label(ui.visuals().error_fg_color, error);
+    }
+    let result = if apply_clicked {
+        apply(app, nodes, edges)
+    } else if undo_clicked {
+        undo(app)
+    } else {
+        return;
+    };
+    app.bulk_edit.error = result.err();
+}
+
+#[cfg(test)]
+mod tests {
+    use egui::Pos2;
+    use crate::app::{EdgePayload, NodePayload};
+    use super::*;
+
+    fn add_node(app: &mut BasicApp, label: &str, weight: f32) -> NodeIndex {
+        app.insert_node(NodePayload { label: label.to_owned(), weight, ..Default::default() }, Pos2::ZERO)
+    }
+
+    #[test]
+    fn pattern_label_fills_in_number_and_label() {
+        assert_eq!(pattern_label("n{n}-{label}", 3, "x"), "n3-x");
+        assert_eq!(pattern_label(" {label} ", 1, "x"), "x");
+    }
+
+    #[test]
+    fn undo_restores_the_edit_and_undo_is_single_use() {
+        let mut app = BasicApp::empty_for_tests();
+        let a = add_node(&mut app, "a", 1.0);
+        let b = add_node(&mut app, "b", 2.0);
+        app.bulk_edit.weight_op = WeightOp::Scale;
+        app.bulk_edit.weight_value = 10.0;
+        app.bulk_edit.rename_pattern = "n{n}".to_owned();
+        apply(&mut app, &[a, b], &[]).unwrap();
+        assert_eq!(app.node_label(b).as_deref(), Some("n2"));
+        assert_eq!(app.g.node_payload(b).unwrap().weight, 20.0);
+
+        undo(&mut app).unwrap();
+        assert_eq!(app.node_label(a).as_deref(), Some("a"));
+        assert_eq!(app.g.node_payload(b).unwrap().weight, 2.0);
+        assert_eq!(app.sim[app.sim_node(b).unwrap()].0.weight, 2.0);
+        assert!(!app.bulk_edit.can_undo());
+    }
+
+    #[test]
+    fn undo_skips_deleted_elements_whose_index_was_reused() {
+        let mut app = BasicApp::empty_for_tests();
+        let a = add_node(&mut app, "a", 1.0);
+        let b = add_node(&mut app, "b", 2.0);
+        let e = app.insert_edge(a, b, EdgePayload { label: "e".to_owned(), weight: 1.0, ..Default::default() }).unwrap();
+        app.bulk_edit.weight_op = WeightOp::Set;
+        app.bulk_edit.weight_value = 5.0;
+        apply(&mut app, &[a, b], &[e]).unwrap();
+
+        // Deleting `a` takes `e` with it; the new node and edge get the freed indices
+        app.remove_node_by_index(a);
+        let c = add_node(&mut app, "c", 3.0);
+        let f = app.insert_edge(b, c, EdgePayload { label: "f".to_owned(), weight: 4.0, ..Default::default() }).unwrap();
+        assert_eq!((c, f), (a, e));
+
+        undo(&mut app).unwrap();
+        assert_eq!(app.node_label(c).as_deref(), Some("c"));
+        assert_eq!(app.g.node_payload(c).unwrap().weight, 3.0);
+        assert_eq!(app.g.edge_payload(f).unwrap().label, "f");
+        assert_eq!(app.g.edge_payload(f).unwrap().weight, 4.0);
+        assert_eq!(app.g.node_payload(b).unwrap().weight, 2.0);
+    }
+}
@@ -0,0 +1,171 @@
+use std::collections::HashMap;
+use egui::{Pos2, Ui, Vec2};
+use serde::{Deserialize, Serialize};
+use crate::app::{BasicApp, EdgePayload, NodePayload};
+use crate::attributes::Attributes;
+use crate::i18n::trf;
+use crate::shapes::{EdgeOverrides, NodeOverrides, NodeShapeKind};
+
+// Tags clipboard text as ours, so pasting arbitrary text is rejected instead of misread
+const FORMAT_TAG: &str = "graph-subgraph/1";
+
+#[derive(Serialize, Deserialize)]
+struct ClipboardNode {
+    label: String,
+    weight: f32,
+    pinned: bool,
+    shape: Option<NodeShapeKind>,
+    icon_path: Option<String>,
+    offset: [f32; 2], // Relative to the centre of the copied nodes
+    #[serde(default)]
+    overrides: NodeOverrides,
+    #[serde(default, skip_serializing_if = "Attributes::is_empty")]
+    attributes: Attributes,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ClipboardEdge {
+    source: usize, // Positions in `nodes`
+    target: usize,
+    label: String,
+    weight: f32,
+    #[serde(default)]
+    overrides: EdgeOverrides,
+    #[serde(default, skip_serializing_if = "Attributes::is_empty")]
+    attributes: Attributes,
+}
+
+/// The selected nodes, the edges among them and their layout, as put on the clipboard.
+#[derive(Serialize, Deserialize)]
+struct Subgraph {
+    format: String,
+    nodes: Vec<ClipboardNode>,
+    edges: Vec<ClipboardEdge>,
+}
+
+/// JSON of the selected nodes and their induced edges, or `None` if nothing is selected.
+pub fn copy_selection(app: &BasicApp) -> Option<String> {
+    let selected = app.g.selected_nodes();
+    if selected.is_empty() {
+        return None;
+    }
+    let locations: Vec<Pos2> = selected.iter().map(|idx| app.g.node_location(*idx).unwrap_or_default()).collect();
+    let centre = locations.iter().fold(Vec2::ZERO, |sum, p| sum + p.to_vec2()) / locations.len() as f32;
+
+    let mut position = HashMap::new();
+    let mut nodes = Vec::new();
+    for (idx, location) in selected.iter().zip(&locations) {
+        let Some(payload) = app.g.node_payload(*idx) else { continue };
+        position.insert(*idx, nodes.len());
+        let offset = location.to_vec2() - centre;
+        nodes.push(ClipboardNode {
+            label: payload.label.clone(),
+            weight: payload.weight,
+            pinned: payload.pinned,
+            shape: payload.shape,
+            icon_path: payload.icon_path.clone(),
+            offset: [offset.x, offset.y],
+            overrides: payload.overrides,
+            attributes: payload.attributes.clone(),
+        });
+    }
+    let edges = app
+        .g
+        .edges()
+        .into_iter()
+        .filter_map(|(source, target, payload)| {
+            Some(ClipboardEdge {
+                source: *position.get(&source)?,
+                target: *position.get(&target)?,
+                label: payload.label,
+                weight: payload.weight,
+                overrides: payload.overrides,
+                attributes: payload.attributes,
+            })
+        })
+        .collect();
+    serde_json::to_string_pretty(&Subgraph { format: FORMAT_TAG.to_owned(), nodes, edges }).ok()
+}
+
+/// Inserts a copied subgraph centred on `at` (canvas coordinates) and selects it.
+/// Pasted elements get new ids; labels that already exist get a ` (2)`-style suffix.
+/// Returns the number of pasted nodes.
+pub fn paste(app: &mut BasicApp, text: &str, at: Pos2) -> Result<usize, String> {
+    let subgraph: Subgraph = serde_json::from_str(text).map_err(|e| trf("clipboard.invalid", &[&e]))?;
+    if subgraph.format != FORMAT_TAG {
+        return Err(trf("clipboard.invalid", &[&subgraph.format]));
+    }
+
+    let mut pasted = Vec::with_capacity(subgraph.nodes.len());
+    for node in subgraph.nodes {
+        let payload = NodePayload {
+            label: app.unique_node_label(&node.label),
+            weight: node.weight,
+            pinned: node.pinned,
+            shape: node.shape,
+            icon_path: node.icon_path,
+            overrides: node.overrides,
+            attributes: node.attributes,
+            ..Default::default()
+        };
+        pasted.push(app.insert_node(payload, at + Vec2::new(node.offset[0], node.offset[1])));
+    }
+    for edge in subgraph.edges {
+        if let (Some(&source), Some(&target)) = (pasted.get(edge.source), pasted.get(edge.target)) {
+            let payload = EdgePayload { label: edge.label, weight: edge.weight, overrides: edge.overrides, attributes: edge.attributes, ..Default::default() };
+            app.insert_edge(source, target, payload);
+        }
+    }
+    let count = pasted.len();
+    app.select_nodes(pasted);
+    Ok(count)
+}
+
+/// Removes what `copy_selection` put on the clipboard: the selected nodes, and with them the
+/// edges among them. Selected edges that were not copied (those to unselected nodes) stay.
+fn cut_copied(app: &mut BasicApp) {
+    for node_idx in app.g.selected_nodes() {
+        app.remove_node_by_index(node_idx);
+    }
+    app.clear_selection();
+}
+
+/// Paste target: the pointer if it is over the canvas, the middle of the view otherwise.
+pub fn paste_position(ui: &Ui) -> Pos2 {
+    let meta = egui_graphs::Metadata::load(ui);
+    let pointer = ui.ctx().pointer_hover_pos().filter(|p| ui.max_rect().contains(*p));
+    meta.screen_to_canvas_pos(pointer.unwrap_or(ui.max_rect().center()))
+}
+
+pub fn paste_text(app: &mut BasicApp, text: String, at: Pos2) {
+    match paste(app, &text, at) {
+        Ok(count) => {
+            println!("Pasted {} nodes.", count);
+            app.clipboard_text = Some(text);
+        }
+        Err(e) => println!("{}", e),
+    }
+}
+
+/// Handles the copy/cut/paste events egui turns Ctrl+C/X/V into. Text fields keep them while focused.
+/// Pasting reads the system clipboard, so a subgraph copied in another window or instance works too.
+pub fn handle_clipboard_events(app: &mut BasicApp, ui: &Ui) {
+    if ui.ctx().wants_keyboard_input() {
+        return;
+    }
+    let events = ui.input(|i| i.events.clone());
+    for event in events {
+        match event {
+            egui::Event::Copy | egui::Event::Cut => {
+                let Some(text) = copy_selection(app) else { continue };
+                ui.ctx().copy_text(text.clone());
+                app.clipboard_text = Some(text);
+                if event == egui::Event::Cut {
+                    cut_copied(app);
+                }
+            }
+            egui::Event::Paste(text) if !app.view_3d => paste_text(app, text, paste_position(ui)),
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,231 @@
+use egui::{Pos2, Rect};
+use fdg::ForceGraph;
+use petgraph::stable_graph::{DefaultIx, NodeIndex};
+use crate::app::{NodePayload, EdgePayload};
+use crate::i18n::trf;
+use crate::ids::{GraphIds, NodeId};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Axis {
+    Horizontal,
+    Vertical,
+}
+
+#[derive(Clone, Debug)]
+pub enum ConstraintKind {
+    /// All nodes share one coordinate: same y for `Horizontal`, same x for `Vertical`.
+    Align(Axis),
+    /// No two nodes of the set get closer than `distance`.
+    MinSeparation { distance: f32 },
+    /// Every node of `nodes` stays left of (or above) every node of `after`, with a gap.
+    Order { axis: Axis, after: Vec<NodeId>, gap: f32 },
+    /// Nodes are kept inside a fixed region of the canvas.
+    BoundingBox(Rect),
+}
+
+/// Nodes are held by id, so constraints survive index changes and skip nodes that are
+/// temporarily out of the simulation (inside a collapsed group).
+#[derive(Clone, Debug)]
+pub struct Constraint {
+    pub kind: ConstraintKind,
+    pub nodes: Vec<NodeId>,
+    pub enabled: bool,
+}
+
+impl Constraint {
+    pub fn new(kind: ConstraintKind, nodes: Vec<NodeId>) -> Self {
+        Self { kind, nodes, enabled: true }
+    }
+
+    // Nothing left to constrain: no nodes, or an order with nothing to come after them
+    fn is_void(&self) -> bool {
+        match &self.kind {
+            ConstraintKind::Order { after, .. } => self.nodes.is_empty() || after.is_empty(),
+            _ => self.nodes.is_empty(),
+        }
+    }
+
+    pub fn description(&self) -> String {
+        match &self.kind {
+            ConstraintKind::Align(Axis::Horizontal) => trf("constraint.align_horizontal", &[&self.nodes.len()]),
+            ConstraintKind::Align(Axis::Vertical) => trf("constraint.align_vertical", &[&self.nodes.len()]),
+            ConstraintKind::MinSeparation { distance } => trf("constraint.min_separation", &[&format!("{:.0}", distance), &self.nodes.len()]),
+            ConstraintKind::Order { axis: Axis::Horizontal, after, .. } => trf("constraint.left_of", &[&self.nodes.len(), &after.len()]),
+            ConstraintKind::Order { axis: Axis::Vertical, after, .. } => trf("constraint.above", &[&self.nodes.len(), &after.len()]),
+            ConstraintKind::BoundingBox(_) => trf("constraint.bounding_box", &[&self.nodes.len()]),
+        }
+    }
+}
+
+/// Constraints are enforced by projection: after every force step the positions are
+/// nudged until the constraints hold, a few Gauss-Seidel sweeps at a time.
+pub struct ConstraintSet {
+    pub constraints: Vec<Constraint>,
+    pub iterations: usize,
+    pub group_a: Vec<NodeId>, // Staging groups for order constraints
+    pub group_b: Vec<NodeId>,
+}
+
+impl Default for ConstraintSet {
+    fn default() -> Self {
+        Self { constraints: Vec::new(), iterations: 5, group_a: Vec::new(), group_b: Vec::new() }
+    }
+}
+
+impl ConstraintSet {
+    pub fn add(&mut self, constraint: Constraint) {
+        self.constraints.push(constraint);
+    }
+
+    pub fn clear(&mut self) {
+        self.constraints.clear();
+        self.group_a.clear();
+        self.group_b.clear();
+    }
+
+    pub fn remove_node(&mut self, id: NodeId) {
+        for constraint in &mut self.constraints {
+            constraint.nodes.retain(|n| *n != id);
+            if let ConstraintKind::Order { after, .. } = &mut constraint.kind {
+                after.retain(|n| *n != id);
+            }
+        }
+        self.constraints.retain(|c| !c.is_void());
+        self.group_a.retain(|n| *n != id);
+        self.group_b.retain(|n| *n != id);
+    }
+
+    pub fn project(&self, sim: &mut ForceGraph<f32, 2, NodePayload, EdgePayload>, ids: &GraphIds) {
+        if self.constraints.iter().all(|c| !c.enabled) {
+            return;
+        }
+        for _ in 0..self.iterations {
+            for constraint in self.constraints.iter().filter(|c| c.enabled) {
+                project_constraint(constraint, sim, ids);
+            }
+        }
+    }
+}
+
+fn position(sim: &ForceGraph<f32, 2, NodePayload, EdgePayload>, idx: NodeIndex<DefaultIx>) -> Option<Pos2> {
+    sim.node_weight(idx).map(|(_, p)| Pos2::new(p.coords.x, p.coords.y))
+}
+
+fn is_pinned(sim: &ForceGraph<f32, 2, NodePayload, EdgePayload>, idx: NodeIndex<DefaultIx>) -> bool {
+    sim.node_weight(idx).is_some_and(|(payload, _)| payload.pinned)
+}
+
+// Pinned nodes are never moved by a projection
+fn move_node(sim: &mut ForceGraph<f32, 2, NodePayload, EdgePayload>, idx: NodeIndex<DefaultIx>, dx: f32, dy: f32) {
+    if let Some((payload, point)) = sim.node_weight_mut(idx) {
+        if !payload.pinned {
+            point.coords.x += dx;
+            point.coords.y += dy;
+        }
+    }
+}
+
+fn axis_value(pos: Pos2, axis: Axis) -> f32 {
+    match axis {
+        Axis::Horizontal => pos.x,
+        Axis::Vertical => pos.y,
+    }
+}
+
+fn axis_delta(delta: f32, axis: Axis) -> (f32, f32) {
+    match axis {
+        Axis::Horizontal => (delta, 0.0),
+        Axis::Vertical => (0.0, delta),
+    }
+}
+
+fn project_constraint(constraint: &Constraint, sim: &mut ForceGraph<f32, 2, NodePayload, EdgePayload>, ids: &GraphIds) {
+    let nodes: Vec<(NodeIndex<DefaultIx>, Pos2)> =
+        constraint.nodes.iter().filter_map(|&id| ids.sim_node(id)).filter_map(|idx| position(sim, idx).map(|p| (idx, p))).collect();
+
+    match &constraint.kind {
+        ConstraintKind::Align(axis) => {
+            // Align on the pinned nodes if there are any, otherwise on the mean
+            let pinned: Vec<_> = nodes.iter().filter(|(idx, _)| is_pinned(sim, *idx)).collect();
+            let source: Vec<&(NodeIndex<DefaultIx>, Pos2)> = if pinned.is_empty() { nodes.iter().collect() } else { pinned };
+            if source.is_empty() {
+                return;
+            }
+            // Horizontal alignment means a shared y, so the projected axis is the other one
+            let shared_axis = match axis {
+                Axis::Horizontal => Axis::Vertical,
+                Axis::Vertical => Axis::Horizontal,
+            };
+            let target = source.iter().map(|(_, p)| axis_value(*p, shared_axis)).sum::<f32>() / source.len() as f32;
+            for (idx, pos) in &nodes {
+                let (dx, dy) = axis_delta(target - axis_value(*pos, shared_axis), shared_axis);
+                move_node(sim, *idx, dx, dy);
+            }
+        }
+        ConstraintKind::MinSeparation { distance } => {
+            for i in 0..nodes.len() {
+                for j in (i + 1)..nodes.len() {
+                    let (Some(pi), Some(pj)) = (position(sim, nodes[i].0), position(sim, nodes[j].0)) else { continue };
+                    let delta = pj - pi;
+                    let dist = delta.length();
+                    if dist >= *distance {
+                        continue;
+                    }
+                    let dir = if dist > f32::EPSILON { delta / dist } else { egui::Vec2::X };
+                    let push = dir * (*distance - dist) * 0.5;
+                    move_node(sim, nodes[i].0, -push.x, -push.y);
+                    move_node(sim, nodes[j].0, push.x, push.y);
+                }
+            }
+        }
+        ConstraintKind::Order { axis, after, gap } => {
+            let after: Vec<NodeIndex<DefaultIx>> = after.iter().filter_map(|&id| ids.sim_node(id)).collect();
+            for &(a, _) in &nodes {
+                for &b in &after {
+                    let (Some(pa), Some(pb)) = (position(sim, a), position(sim, b)) else { continue };
+                    let violation = axis_value(pa, *axis) + gap - axis_value(pb, *axis);
+                    if violation <= 0.0 {
+                        continue;
+                    }
+                    let (dx, dy) = axis_delta(violation * 0.5, *axis);
+                    move_node(sim, a, -dx, -dy);
+                    move_node(sim, b, dx, dy);
+                }
+            }
+        }
+        ConstraintKind::BoundingBox(rect) => {
+            for (idx, pos) in &nodes {
+                let clamped = rect.clamp(*pos);
+                move_node(sim, *idx, clamped.x - pos.x, clamped.y - pos.y);
+            }
+        }
+    }
+}
+
+/// Bounding box of the given nodes in simulation coordinates.
+pub fn nodes_bounds(sim: &ForceGraph<f32, 2, NodePayload, EdgePayload>, nodes: &[NodeIndex<DefaultIx>]) -> Option<Rect> {
+    let mut points = nodes.iter().filter_map(|&idx| position(sim, idx));
+    let first = points.next()?;
+    Some(points.fold(Rect::from_min_max(first, first), |rect, p| rect.union(Rect::from_min_max(p, p))))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn removing_nodes_drops_constraints_left_without_a_side() {
+        let mut set = ConstraintSet::default();
+        let order = ConstraintKind::Order { axis: Axis::Horizontal, after: vec![NodeId(3)], gap: 10.0 };
+        set.add(Constraint::new(order, vec![NodeId(1), NodeId(2)]));
+        set.add(Constraint::new(ConstraintKind::Align(Axis::Vertical), vec![NodeId(1), NodeId(3)]));
+
+        set.remove_node(NodeId(3));
+        assert_eq!(set.constraints.len(), 1);
+        assert!(matches!(set.constraints[0].kind, ConstraintKind::Align(_)));
+        assert_eq!(set.constraints[0].nodes, vec![NodeId(1)]);
+
+        set.remove_node(NodeId(1));
+        assert!(set.constraints.is_empty());
+    }
+}
@@ -0,0 +1,79 @@
+use std::collections::VecDeque;
+use egui::{Color32, Pos2, Sense, Shape, Stroke, Ui, Vec2};
+
+// Number of steps kept for the energy plot
+const HISTORY_LEN: usize = 300;
+
+/// Tracks the simulation energy over time and decides when the layout has settled.
+pub struct ConvergenceMonitor {
+    pub energy_history: VecDeque<f32>,
+    pub auto_stop: bool,
+    pub threshold: f32, // Mean displacement per step below which a step counts as "calm"
+    pub calm_steps_required: usize,
+    pub calm_steps: usize,
+    pub converged: bool,
+}
+
+impl Default for ConvergenceMonitor {
+    fn default() -> Self {
+        Self {
+            energy_history: VecDeque::with_capacity(HISTORY_LEN),
+            auto_stop: true,
+            threshold: 0.05,
+            calm_steps_required: 30,
+            calm_steps: 0,
+            converged: false,
+        }
+    }
+}
+
+impl ConvergenceMonitor {
+    pub fn record(&mut self, energy: f32, mean_displacement: f32) {
+        if self.energy_history.len() == HISTORY_LEN {
+            self.energy_history.pop_front();
+        }
+        self.energy_history.push_back(energy);
+
+        if mean_displacement < self.threshold {
+            self.calm_steps += 1;
+        } else {
+            self.calm_steps = 0;
+        }
+        if self.auto_stop && self.calm_steps >= self.calm_steps_required {
+            self.converged = true;
+        }
+    }
+
+    // Called after structural edits and drags so the layout starts moving again
+    pub fn wake(&mut self) {
+        self.converged = false;
+        self.calm_steps = 0;
+    }
+
+    pub fn clear(&mut self) {
+        self.wake();
+        self.energy_history.clear();
+    }
+}
+
+/// Draws the recorded energy as a small line plot, scaled to the current maximum.
+pub fn draw_energy_plot(ui: &mut Ui, monitor: &ConvergenceMonitor) {
+    let size = Vec2::new(ui.available_width(), 60.0);
+    let (rect, _response) = ui.allocate_exact_size(size, Sense::hover());
+    let painter = ui.painter_at(rect);
+    painter.rect_stroke(rect, 2.0, ui.visuals().widgets.noninteractive.bg_stroke, egui::StrokeKind::Inside);
+
+    if monitor.energy_history.len() < 2 {
+        return;
+    }
+    let max_energy = monitor.energy_history.iter().copied().fold(f32::EPSILON, f32::max);
+    let step_x = rect.width() / (HISTORY_LEN - 1) as f32;
+    let points: Vec<Pos2> = monitor
+        .energy_history
+        .iter()
+        .enumerate()
+        .map(|(i, e)| Pos2::new(rect.left() + i as f32 * step_x, rect.bottom() - e / max_energy * rect.height()))
+        .collect();
+    let color = if monitor.converged { Color32::from_rgb(80, 180, 80) } else { Color32::from_rgb(90, 140, 230) };
+    painter.add(Shape::line(points, Stroke::new(1.5, color)));
+}
@@ -0,0 +1,154 @@
+use std::collections::{HashSet, VecDeque};
+use petgraph::{Direction, EdgeType};
+use petgraph::stable_graph::{DefaultIx, EdgeIndex, NodeIndex};
+use crate::app::{AppGraph, AppGraphOf};
+use crate::i18n::tr;
+use crate::ids::{GraphIds, NodeId};
+
+/// What happens to the nodes outside the focused neighbourhood.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FocusFade {
+    #[default]
+    Dim,
+    Hide,
+}
+
+impl FocusFade {
+    pub const ALL: [FocusFade; 2] = [FocusFade::Dim, FocusFade::Hide];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            FocusFade::Dim => tr("filter_mode.dim"),
+            FocusFade::Hide => tr("filter_mode.hide"),
+        }
+    }
+}
+
+/// Nodes within `hops` steps of `start`, start included. `None` follows edges both ways;
+/// `Some(Outgoing)` gives descendants and `Some(Incoming)` ancestors on directed graphs.
+pub fn neighbourhood(g: &AppGraph, start: &[NodeIndex<DefaultIx>], hops: usize, direction: Option<Direction>) -> HashSet<NodeIndex<DefaultIx>> {
+    fn bfs<Ty: EdgeType>(g: &AppGraphOf<Ty>, start: &[NodeIndex<DefaultIx>], hops: usize, direction: Option<Direction>) -> HashSet<NodeIndex<DefaultIx>> {
+        let mut seen: HashSet<NodeIndex<DefaultIx>> = start.iter().copied().filter(|idx| g.g.contains_node(*idx)).collect();
+        let mut queue: VecDeque<(NodeIndex<DefaultIx>, usize)> = seen.iter().map(|idx| (*idx, 0)).collect();
+        while let Some((idx, depth)) = queue.pop_front() {
+            if depth == hops {
+                continue;
+            }
+            let next: Vec<NodeIndex<DefaultIx>> = match direction {
+                Some(dir) => g.g.neighbors_directed(idx, dir).collect(),
+                None => g.g.neighbors_undirected(idx).collect(),
+            };
+            for n in next {
+                if seen.insert(n) {
+                    queue.push_back((n, depth + 1));
+                }
+            }
+        }
+        seen
+    }
+    match g {
+        AppGraph::Directed(g) => bfs(g, start, hops, direction),
+        AppGraph::Undirected(g) => bfs(g, start, hops, direction),
+    }
+}
+
+/// Ego-network focus: only the k-hop neighbourhood of the focused node stays fully visible.
+/// Focusing another node pushes it onto the breadcrumb trail. The trail holds node ids,
+/// so a deleted centre drops out instead of its index leading to whichever node reuses it.
+pub struct EgoFocus {
+    pub enabled: bool,
+    pub k: usize,
+    pub fade: FocusFade,
+    pub trail: Vec<NodeId>, // Breadcrumbs, the current centre last
+    visible: HashSet<NodeIndex<DefaultIx>>,
+    applied: Option<(NodeId, usize)>, // Centre and k of the last local layout
+    layout_requested: bool,
+}
+
+impl Default for EgoFocus {
+    fn default() -> Self {
+        Self { enabled: false, k: 1, fade: FocusFade::Dim, trail: Vec::new(), visible: HashSet::new(), applied: None, layout_requested: false }
+    }
+}
+
+impl EgoFocus {
+    pub fn center(&self) -> Option<NodeId> {
+        self.trail.last().copied()
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.enabled && self.center().is_some()
+    }
+
+    pub fn focus_on(&mut self, id: NodeId) {
+        if self.center() != Some(id) {
+            self.trail.push(id);
+        }
+    }
+
+    /// Goes back to the `i`-th breadcrumb, dropping the ones after it.
+    pub fn step_back_to(&mut self, i: usize) {
+        self.trail.truncate(i + 1);
+    }
+
+    pub fn exit(&mut self) {
+        self.trail.clear();
+        self.applied = None;
+    }
+
+    /// A single selected node becomes the new centre.
+    pub fn track_selection(&mut self, g: &AppGraph) {
+        if !self.enabled {
+            return;
+        }
+        if let [idx] = g.selected_nodes()[..] {
+            if let Some(payload) = g.node_payload(idx) {
+                self.focus_on(payload.id);
+            }
+        }
+    }
+
+    /// Fades the nodes outside the neighbourhood, on top of what the search filter did.
+    pub fn apply(&mut self, g: &mut AppGraph, ids: &GraphIds) {
+        self.trail.retain(|id| ids.egui_node(*id).is_some());
+        let Some(center) = self.center().filter(|_| self.enabled) else {
+            self.visible.clear();
+            return;
+        };
+        let Some(center_idx) = ids.egui_node(center) else { return };
+        if self.applied != Some((center, self.k)) {
+            self.applied = Some((center, self.k));
+            self.layout_requested = true;
+        }
+        self.visible = neighbourhood(g, &[center_idx], self.k, None);
+        match g {
+            AppGraph::Directed(g) => self.apply_specific(g),
+            AppGraph::Undirected(g) => self.apply_specific(g),
+        }
+    }
+
+    fn apply_specific<Ty: EdgeType>(&self, g: &mut AppGraphOf<Ty>) {
+        let is_out = |idx: &NodeIndex<DefaultIx>| !self.visible.contains(idx);
+        let nodes: Vec<NodeIndex<DefaultIx>> = g.g.node_indices().collect();
+        for idx in &nodes {
+            let out = is_out(idx);
+            let Some(node) = g.node_mut(*idx) else { continue };
+            let style = &mut node.payload_mut().style;
+            style.dimmed |= out && self.fade == FocusFade::Dim;
+            style.hidden |= out && self.fade == FocusFade::Hide;
+        }
+        let edges: Vec<EdgeIndex<DefaultIx>> = g.g.edge_indices().collect();
+        for idx in edges {
+            let out = g.g.edge_endpoints(idx).is_some_and(|(a, b)| is_out(&a) || is_out(&b));
+            let Some(edge) = g.edge_mut(idx) else { continue };
+            let style = &mut edge.payload_mut().style;
+            style.dimmed |= out && self.fade == FocusFade::Dim;
+            style.hidden |= out && self.fade == FocusFade::Hide;
+        }
+    }
+
+    /// The neighbourhood to lay out again, once after each change of centre or k.
+    pub fn take_layout_request(&mut self) -> Option<HashSet<NodeIndex<DefaultIx>>> {
+        std::mem::take(&mut self.layout_requested).then(|| self.visible.clone())
+    }
+}
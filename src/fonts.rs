@@ -0,0 +1,247 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use egui::{Context, FontData, FontDefinitions, FontFamily};
+use serde::{Deserialize, Serialize};
+use crate::i18n::tr;
+
+/// Key of the font settings in eframe storage.
+pub const FONT_STORAGE_KEY: &str = "font_settings";
+
+// Well-known CJK-capable font files, best first. Matched case-insensitively.
+const KNOWN_CJK_FONTS: &[&str] = &[
+    "notosanscjk-regular.ttc",
+    "notosanscjksc-regular.otf",
+    "notosanssc-regular.otf",
+    "notosanssc-regular.ttf",
+    "sourcehansanssc-regular.otf",
+    "sourcehansans-regular.ttc",
+    "wqy-microhei.ttc",
+    "wqy-zenhei.ttc",
+    "droidsansfallbackfull.ttf",
+    "droidsansfallback.ttf",
+    "pingfang.ttc",
+    "hiragino sans gb.ttc",
+    "stheiti light.ttc",
+    "songti.ttc",
+    "arial unicode.ttf",
+    "msyh.ttc",
+    "simsun.ttc",
+    "simhei.ttf",
+];
+
+// Name fragments that usually mean CJK coverage, for fonts not in the list above
+const CJK_NAME_HINTS: &[&str] = &["cjk", "wqy", "sourcehan", "notosanssc", "notoserifsc", "droidsansfallback", "uming", "ukai"];
+
+// Font directories nest (e.g. /usr/share/fonts/opentype/noto), but not deeply
+const MAX_SCAN_DEPTH: usize = 4;
+
+// Matches the scale the original hard-coded SimSun font was loaded with
+const CJK_FONT_SCALE: f32 = 0.8;
+
+// No font ships with the repo, the build names one (absolute path) through the environment
+#[cfg(feature = "embedded-font")]
+const EMBEDDED_FONT: &[u8] = include_bytes!(env!(
+    "EMBEDDED_CJK_FONT",
+    "the embedded-font feature needs EMBEDDED_CJK_FONT set to the absolute path of a redistributable CJK font, see assets/fonts/README.md"
+));
+
+/// Which fonts are installed into egui: the user's font, then the fallback chain,
+/// then the best discovered system font, then the embedded font if compiled in.
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FontSettings {
+    pub user_font_path: String,
+    pub fallback_chain: Vec<String>,
+    #[serde(skip)]
+    pub discovered: Vec<PathBuf>,
+    #[serde(skip)]
+    pub loaded: Vec<String>, // Fonts actually in use, in priority order
+    #[serde(skip)]
+    pub input_fallback_path: String,
+}
+
+impl FontSettings {
+    /// Rescans the system and replaces the egui font definitions.
+    pub fn install(&mut self, ctx: &Context) {
+        self.discovered = discover_cjk_fonts();
+        self.loaded.clear();
+
+        let mut candidates: Vec<PathBuf> = Vec::new();
+        if !self.user_font_path.trim().is_empty() {
+            candidates.push(PathBuf::from(self.user_font_path.trim()));
+        }
+        candidates.extend(self.fallback_chain.iter().filter(|p| !p.trim().is_empty()).map(|p| PathBuf::from(p.trim())));
+        // One system font is enough; CJK fonts are large
+        candidates.extend(self.discovered.first().cloned());
+
+        let mut fonts = FontDefinitions::default();
+        let mut names = Vec::new();
+        for path in candidates {
+            if names.iter().any(|(_, p): &(String, PathBuf)| *p == path) {
+                continue;
+            }
+            match fs::read(&path) {
+                Ok(bytes) => {
+                    let name = format!("cjk_font_{}", names.len());
+                    let mut data = FontData::from_owned(bytes);
+                    data.tweak.scale = CJK_FONT_SCALE;
+                    fonts.font_data.insert(name.clone(), Arc::new(data));
+                    names.push((name, path));
+                }
+                Err(e) => eprintln!("Error loading font file at '{}': {}", path.display(), e),
+            }
+        }
+        #[cfg(feature = "embedded-font")]
+        {
+            let mut data = FontData::from_static(EMBEDDED_FONT);
+            data.tweak.scale = CJK_FONT_SCALE;
+            fonts.font_data.insert("cjk_font_embedded".to_owned(), Arc::new(data));
+            names.push(("cjk_font_embedded".to_owned(), PathBuf::from(tr("fonts.embedded"))));
+        }
+        if names.is_empty() {
+            eprintln!("No CJK-capable font found. Chinese characters might not display correctly.");
+        }
+
+        // Inserted in front of egui's own fonts, keeping the chain order
+        for family in [FontFamily::Proportional, FontFamily::Monospace] {
+            let list = fonts.families.entry(family).or_default();
+            for (i, (name, _)) in names.iter().enumerate() {
+                list.insert(i, name.clone());
+            }
+        }
+        self.loaded = names.into_iter().map(|(_, path)| path.display().to_string()).collect();
+        ctx.set_fonts(fonts);
+    }
+}
+
+/// CJK-capable font files found in the standard font directories, best first.
+pub fn discover_cjk_fonts() -> Vec<PathBuf> {
+    let mut dirs = system_font_dirs();
+    for dir in fontconfig_dirs() {
+        if !dirs.contains(&dir) {
+            dirs.push(dir);
+        }
+    }
+    let mut found: Vec<(usize, PathBuf)> = Vec::new();
+    for dir in dirs {
+        scan_dir(&dir, 0, &mut found);
+    }
+    found.sort_by_key(|(rank, _)| *rank);
+    // Fontconfig usually lists the standard directories again
+    let mut seen = HashSet::new();
+    found.retain(|(_, path)| seen.insert(path.clone()));
+    found.into_iter().map(|(_, path)| path).collect()
+}
+
+fn home_dir() -> Option<PathBuf> {
+    std::env::var_os("HOME").or_else(|| std::env::var_os("USERPROFILE")).map(PathBuf::from)
+}
+
+fn system_font_dirs() -> Vec<PathBuf> {
+    let mut dirs: Vec<PathBuf> = Vec::new();
+    if cfg!(target_os = "windows") {
+        let windir = std::env::var_os("WINDIR").map(PathBuf::from).unwrap_or_else(|| PathBuf::from("C:\\Windows"));
+        dirs.push(windir.join("Fonts"));
+        if let Some(local) = std::env::var_os("LOCALAPPDATA") {
+            dirs.push(PathBuf::from(local).join("Microsoft").join("Windows").join("Fonts"));
+        }
+    } else if cfg!(target_os = "macos") {
+        dirs.push(PathBuf::from("/System/Library/Fonts"));
+        dirs.push(PathBuf::from("/Library/Fonts"));
+        if let Some(home) = home_dir() {
+            dirs.push(home.join("Library/Fonts"));
+        }
+    } else {
+        dirs.push(PathBuf::from("/usr/share/fonts"));
+        dirs.push(PathBuf::from("/usr/local/share/fonts"));
+        if let Some(home) = home_dir() {
+            dirs.push(home.join(".local/share/fonts"));
+            dirs.push(home.join(".fonts"));
+        }
+    }
+    dirs
+}
+
+// <dir> entries of the fontconfig configuration; distributions add font locations there
+fn fontconfig_dirs() -> Vec<PathBuf> {
+    let mut conf_files = vec![PathBuf::from("/etc/fonts/fonts.conf"), PathBuf::from("/etc/fonts/local.conf")];
+    if let Ok(entries) = fs::read_dir("/etc/fonts/conf.d") {
+        conf_files.extend(entries.flatten().map(|e| e.path()).filter(|p| p.extension().is_some_and(|e| e == "conf")));
+    }
+    if let Some(home) = home_dir() {
+        conf_files.push(home.join(".config/fontconfig/fonts.conf"));
+    }
+
+    let mut dirs = Vec::new();
+    for conf in conf_files {
+        let Ok(text) = fs::read_to_string(&conf) else { continue };
+        let mut rest = text.as_str();
+        while let Some(start) = rest.find("<dir") {
+            rest = &rest[start + 4..];
+            let Some(tag_end) = rest.find('>') else { break };
+            let attributes = &rest[..tag_end];
+            rest = &rest[tag_end + 1..];
+            // Skips <directory> and similar tags
+            if !attributes.is_empty() && !attributes.starts_with(char::is_whitespace) {
+                continue;
+            }
+            let Some(close) = rest.find("</dir>") else { break };
+            let value = rest[..close].trim();
+            rest = &rest[close..];
+            if let Some(dir) = resolve_fontconfig_dir(value, attributes, &conf) {
+                dirs.push(dir);
+            }
+        }
+    }
+    dirs
+}
+
+fn resolve_fontconfig_dir(value: &str, attributes: &str, conf: &Path) -> Option<PathBuf> {
+    if value.is_empty() {
+        return None;
+    }
+    if attributes.contains("prefix=\"xdg\"") {
+        let data_home = std::env::var_os("XDG_DATA_HOME").map(PathBuf::from).or_else(|| home_dir().map(|h| h.join(".local/share")))?;
+        return Some(data_home.join(value));
+    }
+    if let Some(stripped) = value.strip_prefix("~/") {
+        return home_dir().map(|h| h.join(stripped));
+    }
+    let path = PathBuf::from(value);
+    if path.is_relative() {
+        return conf.parent().map(|p| p.join(path));
+    }
+    Some(path)
+}
+
+fn scan_dir(dir: &Path, depth: usize, found: &mut Vec<(usize, PathBuf)>) {
+    let Ok(entries) = fs::read_dir(dir) else { return };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            if depth < MAX_SCAN_DEPTH {
+                scan_dir(&path, depth + 1, found);
+            }
+            continue;
+        }
+        if let Some(rank) = cjk_rank(&path) {
+            found.push((rank, path));
+        }
+    }
+}
+
+// Lower is better; known fonts keep their list order, name hints come after them
+fn cjk_rank(path: &Path) -> Option<usize> {
+    let name = path.file_name()?.to_str()?.to_lowercase();
+    if let Some(pos) = KNOWN_CJK_FONTS.iter().position(|known| *known == name) {
+        return Some(pos);
+    }
+    let is_font = [".ttf", ".otf", ".ttc"].iter().any(|ext| name.ends_with(ext));
+    if !is_font || !CJK_NAME_HINTS.iter().any(|hint| name.contains(hint)) {
+        return None;
+    }
+    // Bold/light cuts of the same family are a worse default than the regular one
+    Some(KNOWN_CJK_FONTS.len() + usize::from(!name.contains("regular")))
+}
@@ -1,22 +1,58 @@
 use eframe::Frame;
-use egui::{Context, CentralPanel};
-use egui_graphs::{GraphView, SettingsStyle, SettingsNavigation, SettingsInteraction, DefaultNodeShape, DefaultEdgeShape, LayoutStateRandom, LayoutRandom}; // Corrected layout imports
-use crate::app::{BasicApp, AppGraph, NodePayload, EdgePayload};
-use petgraph::stable_graph::DefaultIx;
+use egui::{Context, CentralPanel, Color32, Pos2, Stroke, Ui, Vec2};
+use egui_graphs::{GraphView, Metadata, SettingsStyle, SettingsNavigation, SettingsInteraction, LayoutStateRandom, LayoutRandom}; // Corrected layout imports
+use crate::app::{BasicApp, AppGraph, ContextTarget, InlineEdit, NodePayload, EdgePayload};
+use crate::constraints::{Axis, ConstraintKind};
+use crate::ids::NodeId;
+use crate::shapes::{AppNodeShape, AppEdgeShape, DEFAULT_NODE_RADIUS};
+use crate::i18n::{tr, trf};
+use crate::keymap::SEARCH_FIELD_ID;
+use crate::search::FilterMode;
+use crate::selection::{SelectionOp, SelectionTool};
+use petgraph::stable_graph::{DefaultIx, EdgeIndex, NodeIndex};
 use petgraph::Directed;
 
+// Canvas margin kept around the matches when zooming to them
+const ZOOM_MARGIN: f32 = 60.0;
+const MAX_ZOOM_TO_MATCH: f32 = 4.0;
+
+const INLINE_EDIT_ID: &str = "inline_label_edit";
+
 pub fn draw_graph_view(app: &mut BasicApp, ctx: &Context, _frame: &mut Frame) {
-    CentralPanel::default().show(ctx, |ui| {
+    egui::TopBottomPanel::top("search_bar").show(ctx, |ui| {
+        draw_search_bar(app, ui);
+        if app.focus.is_active() {
+            draw_focus_breadcrumbs(app, ui);
+        }
+    });
+
+    let canvas_frame = egui::Frame::central_panel(&ctx.style()).fill(app.theme.active().background);
+    CentralPanel::default().frame(canvas_frame).show(ctx, |ui| {
+        crate::keymap::handle_shortcuts(app, ui);
+        crate::clipboard::handle_clipboard_events(app, ui);
+        if app.view_3d {
+            crate::view3d::draw_3d_view(app, ui);
+            return;
+        }
+
+        if let Some(bounds) = app.pending_zoom.take() {
+            // Fit-to-screen would undo the zoom on the next frame
+            app.nav_fit_to_screen = false;
+            zoom_to_canvas_rect(ui, ui.max_rect(), bounds);
+        }
+
         let settings_style = SettingsStyle::new()
             .with_labels_always(app.style_labels_always);
 
+        // Rectangle/lasso drags must not also pan the view or move nodes
+        let selecting = app.selection.captures_drag();
         let settings_navigation = SettingsNavigation::new()
             .with_fit_to_screen_enabled(app.nav_fit_to_screen)
-            .with_zoom_and_pan_enabled(app.nav_zoom_and_pan)
+            .with_zoom_and_pan_enabled(app.nav_zoom_and_pan && !selecting)
             .with_zoom_speed(app.nav_zoom_speed);
 
         let settings_interaction = SettingsInteraction::new()
-            .with_dragging_enabled(app.ia_dragging_enabled)
+            .with_dragging_enabled(app.ia_dragging_enabled && !selecting)
             .with_node_clicking_enabled(app.ia_node_clicking_enabled)
             .with_node_selection_enabled(app.ia_node_selection_enabled)
             .with_node_selection_multi_enabled(app.ia_node_selection_multi_enabled)
@@ -24,25 +60,579 @@ pub fn draw_graph_view(app: &mut BasicApp, ctx: &Context, _frame: &mut Frame) {
             .with_edge_selection_enabled(app.ia_edge_selection_enabled)
             .with_edge_selection_multi_enabled(app.ia_edge_selection_multi_enabled);
         
-        match &mut app.g {
+        let response = match &mut app.g {
             AppGraph::Directed(g_directed) => {
                 ui.add(
-                    &mut GraphView::<NodePayload, EdgePayload, Directed, DefaultIx, DefaultNodeShape, DefaultEdgeShape, LayoutStateRandom, LayoutRandom>::new(g_directed)
+                    &mut GraphView::<NodePayload, EdgePayload, Directed, DefaultIx, AppNodeShape, AppEdgeShape, LayoutStateRandom, LayoutRandom>::new(g_directed)
                         .with_interactions(&settings_interaction)
                         .with_navigations(&settings_navigation)
                         .with_styles(&settings_style)
                         .with_events(&app.event_publisher)
-                );
+                )
             }
             AppGraph::Undirected(g_undirected) => {
                 ui.add(
-                    &mut GraphView::<NodePayload, EdgePayload, petgraph::Undirected, DefaultIx, DefaultNodeShape, DefaultEdgeShape, LayoutStateRandom, LayoutRandom>::new(g_undirected)
+                    &mut GraphView::<NodePayload, EdgePayload, petgraph::Undirected, DefaultIx, AppNodeShape, AppEdgeShape, LayoutStateRandom, LayoutRandom>::new(g_undirected)
                         .with_interactions(&settings_interaction)
                         .with_navigations(&settings_navigation)
                         .with_styles(&settings_style)
                         .with_events(&app.event_publisher)
-                );
+                )
+            }
+        };
+
+        app.overlap.zoom = Metadata::load(ui).zoom;
+
+        if app.selection.tool == SelectionTool::Edge {
+            handle_edge_tool(app, ui, &response);
+        } else if selecting {
+            handle_canvas_selection(app, ui, &response);
+        }
+        if app.edge_draft_source.is_some() {
+            handle_edge_draft(app, ui, &response);
+        }
+
+        if app.show_constraint_guides {
+            draw_constraint_guides(app, ui);
+        }
+        draw_group_outlines(app, ui);
+        draw_pin_markers(app, ui);
+        app.visual_mapping.draw_legend(ui, response.rect);
+        // Moving the view by hand would be undone by fit-to-screen on the next frame
+        if app.minimap.enabled && crate::minimap::draw_minimap(app, ui, response.rect) {
+            app.nav_fit_to_screen = false;
+        }
+
+        // Double-click edits a label in place; the edge tool uses clicks for itself
+        if response.double_clicked() && app.selection.tool == SelectionTool::Pointer {
+            if let Some(pos) = response.interact_pointer_pos() {
+                start_inline_edit(app, ui, pos);
+            }
+        }
+        draw_inline_edit(app, ui);
+
+        if response.secondary_clicked() {
+            if let Some(pos) = response.interact_pointer_pos() {
+                open_context_menu(app, ui, pos);
+            }
+        }
+        response.context_menu(|ui| draw_context_menu(app, ui));
+    });
+
+    if app.keymap.show_editor {
+        crate::keymap::draw_keymap_editor(&mut app.keymap, ctx);
+    }
+    if app.keymap.show_cheat_sheet {
+        crate::keymap::draw_cheat_sheet(&mut app.keymap, ctx);
+    }
+}
+
+fn handle_canvas_selection(app: &mut BasicApp, ui: &Ui, response: &egui::Response) {
+    if response.drag_started_by(egui::PointerButton::Primary) {
+        if let Some(pos) = response.interact_pointer_pos() {
+            app.selection.begin(pos);
+        }
+    }
+    if !app.selection.is_dragging() {
+        return;
+    }
+    if let Some(pos) = response.interact_pointer_pos() {
+        app.selection.extend(pos);
+    }
+    if response.drag_stopped() {
+        let op = SelectionOp::from_modifiers(ui.input(|i| i.modifiers));
+        let meta = Metadata::load(ui);
+        app.selection.finish(&mut app.g, &meta, op);
+    } else {
+        app.selection.draw(ui.painter(), ui.visuals().selection.stroke.color);
+    }
+}
+
+// Edge tool: pressing on a node starts a draft edge, releasing over another node completes it
+fn handle_edge_tool(app: &mut BasicApp, ui: &Ui, response: &egui::Response) {
+    if response.drag_started_by(egui::PointerButton::Primary) {
+        let origin = ui.input(|i| i.pointer.press_origin());
+        app.edge_draft_source = origin.and_then(|pos| node_at_screen_pos(app, ui, pos));
+    }
+    if response.drag_stopped() && app.edge_draft_source.is_some() {
+        complete_edge_draft(app, ui, response.interact_pointer_pos());
+    }
+}
+
+// "Start edge from here" and the edge tool: a rubber line to the pointer until a node is clicked
+fn handle_edge_draft(app: &mut BasicApp, ui: &Ui, response: &egui::Response) {
+    let Some(source) = app.edge_draft_source else { return };
+    if ui.input(|i| i.key_pressed(egui::Key::Escape)) || app.g.node_payload(source).is_none() {
+        app.edge_draft_source = None;
+        return;
+    }
+    if response.clicked() {
+        complete_edge_draft(app, ui, response.interact_pointer_pos());
+        return;
+    }
+    let (Some(location), Some(pointer)) = (app.g.node_location(source), response.hover_pos()) else { return };
+    let meta = Metadata::load(ui);
+    let stroke = ui.visuals().selection.stroke;
+    ui.painter().extend(egui::Shape::dashed_line(&[meta.canvas_to_screen_pos(location), pointer], stroke, 6.0, 4.0));
+    // Ring around the node the edge would connect to
+    if let Some(target) = node_at_screen_pos(app, ui, pointer).filter(|t| *t != source) {
+        if let Some(target_location) = app.g.node_location(target) {
+            let radius = app.g.node_payload(target).and_then(|p| p.style.radius).unwrap_or(DEFAULT_NODE_RADIUS);
+            ui.painter().circle_stroke(meta.canvas_to_screen_pos(target_location), meta.canvas_to_screen_size(radius) + 4.0, stroke);
+        }
+    }
+}
+
+// Connects the draft source to the node at `pos`. With Shift held the target becomes the next
+// source, so a path can be drawn node by node; otherwise, or over the background, the draft ends.
+fn complete_edge_draft(app: &mut BasicApp, ui: &Ui, pos: Option<Pos2>) {
+    let Some(source) = app.edge_draft_source.take() else { return };
+    let Some(target) = pos.and_then(|pos| node_at_screen_pos(app, ui, pos)).filter(|t| *t != source) else { return };
+    // The indices are known already, so duplicate labels don't matter here
+    if let (Some(from), Some(to)) = (app.node_label(source), app.node_label(target)) {
+        let label = trf("gen.edge_between", &[&from, &to]);
+        app.insert_edge(source, target, EdgePayload { label, weight: app.input_edge_weight, ..Default::default() });
+    }
+    if ui.input(|i| i.modifiers.shift) {
+        app.edge_draft_source = Some(target);
+    }
+}
+
+fn draw_search_bar(app: &mut BasicApp, ui: &mut Ui) {
+    ui.horizontal(|ui| {
+        ui.label(tr("selection.tool"));
+        for tool in SelectionTool::ALL {
+            ui.selectable_value(&mut app.selection.tool, tool, tool.label()).on_hover_text(tool.hint());
+        }
+        ui.add_enabled(app.selection.tool.is_area(), egui::Checkbox::new(&mut app.selection.include_edges, tr("selection.include_edges")));
+        ui.separator();
+
+        let search_id = egui::Id::new(SEARCH_FIELD_ID);
+        let response = ui.add(egui::TextEdit::singleline(&mut app.search.query).id(search_id).hint_text(tr("search.hint")).desired_width(240.0));
+        // Enter jumps to the results, like the button
+        if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+            app.pending_zoom = app.search.matches_bounds(&app.g);
+        }
+        if app.search.is_active() {
+            ui.label(trf("search.matches", &[&app.search.matches.len()]));
+        }
+        if ui.add_enabled(!app.search.matches.is_empty() && !app.view_3d, egui::Button::new(tr("search.zoom"))).clicked() {
+            app.pending_zoom = app.search.matches_bounds(&app.g);
+        }
+        if ui.add_enabled(app.search.is_active(), egui::Button::new(tr("search.clear"))).clicked() {
+            app.search.clear();
+        }
+        ui.separator();
+        ui.label(tr("search.mode"));
+        egui::ComboBox::from_id_salt("filter_mode").selected_text(app.search.mode.label()).show_ui(ui, |ui| {
+            for mode in FilterMode::ALL {
+                ui.selectable_value(&mut app.search.mode, mode, mode.label());
+            }
+        });
+    });
+}
+
+fn draw_focus_breadcrumbs(app: &mut BasicApp, ui: &mut Ui) {
+    ui.horizontal(|ui| {
+        ui.label(tr("focus.breadcrumbs"));
+        let mut step_back = None;
+        let last = app.focus.trail.len().saturating_sub(1);
+        for (i, id) in app.focus.trail.iter().enumerate() {
+            if i > 0 {
+                ui.label("›");
             }
+            let Some(idx) = app.ids.egui_node(*id) else { continue };
+            let label = app.node_label(idx).unwrap_or_default();
+            if ui.add_enabled(i != last, egui::Button::new(label).small()).clicked() {
+                step_back = Some((i, idx));
+            }
+        }
+        if let Some((i, idx)) = step_back {
+            app.focus.step_back_to(i);
+            // Selecting the crumb keeps the selection tracker from pushing the old centre again
+            app.select_nodes(vec![idx]);
+        }
+        ui.separator();
+        ui.add(egui::Slider::new(&mut app.focus.k, 1..=6).text(tr("focus.k")));
+        if ui.button(tr("focus.exit")).clicked() {
+            app.focus.exit();
         }
     });
-}
\ No newline at end of file
+}
+
+// Sets zoom and pan so `target` (canvas coordinates) fills `viewport` (screen coordinates)
+fn zoom_to_canvas_rect(ui: &Ui, viewport: egui::Rect, target: egui::Rect) {
+    let target = target.expand(ZOOM_MARGIN);
+    let zoom = (viewport.width() / target.width()).min(viewport.height() / target.height()).min(MAX_ZOOM_TO_MATCH);
+    let mut meta = Metadata::load(ui);
+    meta.zoom = zoom;
+    meta.pan = viewport.center().to_vec2() - target.center().to_vec2() * zoom;
+    meta.save(ui);
+}
+
+fn node_at_screen_pos(app: &BasicApp, ui: &Ui, screen_pos: Pos2) -> Option<NodeIndex<DefaultIx>> {
+    let meta = Metadata::load(ui);
+    match &app.g {
+        AppGraph::Directed(g) => g.node_by_screen_pos(&meta, screen_pos),
+        AppGraph::Undirected(g) => g.node_by_screen_pos(&meta, screen_pos),
+    }
+}
+
+fn edge_at_screen_pos(app: &BasicApp, ui: &Ui, screen_pos: Pos2) -> Option<EdgeIndex<DefaultIx>> {
+    let meta = Metadata::load(ui);
+    match &app.g {
+        AppGraph::Directed(g) => g.edge_by_screen_pos(&meta, screen_pos),
+        AppGraph::Undirected(g) => g.edge_by_screen_pos(&meta, screen_pos),
+    }
+}
+
+fn start_inline_edit(app: &mut BasicApp, ui: &Ui, screen_pos: Pos2) {
+    let (target, text) = if let Some(node_idx) = node_at_screen_pos(app, ui, screen_pos) {
+        (ContextTarget::Node(node_idx), app.node_label(node_idx))
+    } else if let Some(edge_idx) = edge_at_screen_pos(app, ui, screen_pos) {
+        (ContextTarget::Edge(edge_idx), app.get_edge_payload_mut(edge_idx).map(|p| p.label.clone()))
+    } else {
+        return;
+    };
+    if let Some(text) = text {
+        app.inline_edit = Some(InlineEdit { target, text, error: None });
+        ui.memory_mut(|m| m.request_focus(egui::Id::new(INLINE_EDIT_ID)));
+    }
+}
+
+// Canvas anchor of the label being edited: below a node, at the middle of an edge
+fn inline_edit_anchor(app: &BasicApp, target: ContextTarget) -> Option<Pos2> {
+    match target {
+        ContextTarget::Node(node_idx) => {
+            let radius = app.g.node_payload(node_idx)?.style.radius.unwrap_or(DEFAULT_NODE_RADIUS);
+            Some(app.g.node_location(node_idx)? + Vec2::new(0.0, radius))
+        }
+        ContextTarget::Edge(edge_idx) => {
+            let (source, target) = app.g.edge_endpoints(edge_idx)?;
+            Some(app.g.node_location(source)?.lerp(app.g.node_location(target)?, 0.5))
+        }
+        ContextTarget::Background(pos) => Some(pos),
+    }
+}
+
+fn draw_inline_edit(app: &mut BasicApp, ui: &Ui) {
+    let Some(target) = app.inline_edit.as_ref().map(|e| e.target) else { return };
+    let Some(anchor) = inline_edit_anchor(app, target) else {
+        app.inline_edit = None; // The element was removed meanwhile
+        return;
+    };
+    let screen_pos = Metadata::load(ui).canvas_to_screen_pos(anchor) + Vec2::new(0.0, 4.0);
+    let Some(edit) = app.inline_edit.as_mut() else { return };
+    let mut commit = false;
+    let mut cancel = false;
+    egui::Area::new(egui::Id::new("inline_edit_area"))
+        .fixed_pos(screen_pos)
+        .pivot(egui::Align2::CENTER_TOP)
+        .order(egui::Order::Foreground)
+        .show(ui.ctx(), |ui| {
+            egui::Frame::popup(ui.style()).show(ui, |ui| {
+                let response = ui.add(egui::TextEdit::singleline(&mut edit.text).id(egui::Id::new(INLINE_EDIT_ID)).desired_width(140.0));
+                if response.changed() {
+                    edit.error = None;
+                }
+                if response.lost_focus() {
+                    // Escape cancels; Enter or clicking elsewhere keeps the new label
+                    if ui.input(|i| i.key_pressed(egui::Key::Escape)) {
+                        cancel = true;
+                    } else {
+                        commit = true;
+                    }
+                }
+                if let Some(error) = &edit.error {
+                    ui.colored_label(ui.visuals().error_fg_color, error);
+                }
+            });
+        });
+    if cancel {
+        app.inline_edit = None;
+    } else if commit {
+        app.commit_inline_edit();
+        if app.inline_edit.is_some() {
+            ui.memory_mut(|m| m.request_focus(egui::Id::new(INLINE_EDIT_ID)));
+        }
+    }
+}
+
+// Nodes win over edges, so a node sitting on an edge still gets the node menu
+fn open_context_menu(app: &mut BasicApp, ui: &Ui, screen_pos: Pos2) {
+    let target = if let Some(node_idx) = node_at_screen_pos(app, ui, screen_pos) {
+        ContextTarget::Node(node_idx)
+    } else if let Some(edge_idx) = edge_at_screen_pos(app, ui, screen_pos) {
+        ContextTarget::Edge(edge_idx)
+    } else {
+        ContextTarget::Background(Metadata::load(ui).screen_to_canvas_pos(screen_pos))
+    };
+    // The editable fields start from the element's current values
+    match target {
+        ContextTarget::Node(node_idx) => {
+            if let Some(payload) = app.g.node_payload(node_idx) {
+                app.context_text = payload.label.clone();
+                app.context_weight = payload.weight;
+            }
+        }
+        ContextTarget::Edge(edge_idx) => {
+            if let Some((label, weight)) = app.get_edge_payload_mut(edge_idx).map(|p| (p.label.clone(), p.weight)) {
+                app.context_text = label;
+                app.context_weight = weight;
+            }
+        }
+        ContextTarget::Background(_) => {}
+    }
+    app.context_target = Some(target);
+}
+
+// Expanded groups are outlined with their name, so it is clear what collapsing would merge
+fn draw_group_outlines(app: &BasicApp, ui: &Ui) {
+    let meta = Metadata::load(ui);
+    let painter = ui.painter();
+    let color = app.theme.active().edge_color.gamma_multiply(0.6);
+    for group in app.groups.groups.iter().filter(|g| g.collapsed.is_none()) {
+        let points: Vec<Pos2> = group
+            .members
+            .iter()
+            .filter_map(|id| app.ids.egui_node(*id))
+            .filter_map(|idx| app.g.node_location(idx))
+            .map(|p| meta.canvas_to_screen_pos(p))
+            .collect();
+        if points.is_empty() {
+            continue;
+        }
+        let rect = points.iter().fold(egui::Rect::NOTHING, |r, p| r.union(egui::Rect::from_min_max(*p, *p))).expand(meta.canvas_to_screen_size(DEFAULT_NODE_RADIUS) + 10.0);
+        painter.rect_stroke(rect, 8.0, Stroke::new(1.0, color), egui::StrokeKind::Outside);
+        painter.text(rect.left_top() + Vec2::new(6.0, -2.0), egui::Align2::LEFT_BOTTOM, &group.name, egui::FontId::proportional(12.0), color);
+    }
+}
+
+// Pinned nodes get a small pushpin drawn above them
+fn draw_pin_markers(app: &BasicApp, ui: &Ui) {
+    let meta = Metadata::load(ui);
+    let painter = ui.painter();
+    let color = Color32::from_rgb(220, 60, 60);
+    for node_idx in app.g.node_indices() {
+        if !app.g.node_payload(node_idx).is_some_and(|p| p.pinned) {
+            continue;
+        }
+        let Some(location) = app.g.node_location(node_idx) else { continue };
+        let anchor = meta.canvas_to_screen_pos(location);
+        let head = anchor - Vec2::new(0.0, meta.canvas_to_screen_size(5.0) + 8.0);
+        painter.line_segment([head, head + Vec2::new(0.0, 6.0)], Stroke::new(1.5, color));
+        painter.circle_filled(head, 3.0, color);
+    }
+}
+
+// Guides are drawn from the egui node locations, which mirror the simulation coordinates
+fn draw_constraint_guides(app: &BasicApp, ui: &Ui) {
+    let meta = Metadata::load(ui);
+    let painter = ui.painter();
+    let stroke = Stroke::new(1.0, Color32::from_rgba_unmultiplied(120, 120, 220, 160));
+    let screen_points = |nodes: &[NodeId]| -> Vec<Pos2> {
+        nodes.iter().filter_map(|id| app.ids.egui_node(*id)).filter_map(|idx| app.g.node_location(idx)).map(|p| meta.canvas_to_screen_pos(p)).collect()
+    };
+    let bounds = |points: &[Pos2]| points.iter().fold(egui::Rect::NOTHING, |r, p| r.union(egui::Rect::from_min_max(*p, *p)));
+
+    for constraint in app.constraints.constraints.iter().filter(|c| c.enabled) {
+        let points = screen_points(&constraint.nodes);
+        if points.is_empty() {
+            continue;
+        }
+        let rect = bounds(&points);
+        match &constraint.kind {
+            ConstraintKind::Align(Axis::Horizontal) => {
+                let y = rect.center().y;
+                painter.extend(egui::Shape::dashed_line(&[Pos2::new(rect.left() - 20.0, y), Pos2::new(rect.right() + 20.0, y)], stroke, 6.0, 4.0));
+            }
+            ConstraintKind::Align(Axis::Vertical) => {
+                let x = rect.center().x;
+                painter.extend(egui::Shape::dashed_line(&[Pos2::new(x, rect.top() - 20.0), Pos2::new(x, rect.bottom() + 20.0)], stroke, 6.0, 4.0));
+            }
+            ConstraintKind::MinSeparation { distance } => {
+                let radius = meta.canvas_to_screen_size(*distance) * 0.5;
+                for p in &points {
+                    painter.circle_stroke(*p, radius, stroke);
+                }
+            }
+            ConstraintKind::Order { axis, after, .. } => {
+                let after_points = screen_points(after);
+                if after_points.is_empty() {
+                    continue;
+                }
+                let after_rect = bounds(&after_points);
+                let all = rect.union(after_rect);
+                let line = match axis {
+                    Axis::Horizontal => {
+                        let x = (rect.right() + after_rect.left()) * 0.5;
+                        [Pos2::new(x, all.top() - 20.0), Pos2::new(x, all.bottom() + 20.0)]
+                    }
+                    Axis::Vertical => {
+                        let y = (rect.bottom() + after_rect.top()) * 0.5;
+                        [Pos2::new(all.left() - 20.0, y), Pos2::new(all.right() + 20.0, y)]
+                    }
+                };
+                painter.extend(egui::Shape::dashed_line(&line, stroke, 6.0, 4.0));
+            }
+            ConstraintKind::BoundingBox(region) => {
+                let screen_rect = egui::Rect::from_min_max(meta.canvas_to_screen_pos(region.min), meta.canvas_to_screen_pos(region.max));
+                painter.rect_stroke(screen_rect, 4.0, stroke, egui::StrokeKind::Inside);
+            }
+        }
+    }
+}
+
+fn draw_context_menu(app: &mut BasicApp, ui: &mut Ui) {
+    match app.context_target {
+        Some(ContextTarget::Node(node_idx)) => draw_node_context_menu(app, ui, node_idx),
+        Some(ContextTarget::Edge(edge_idx)) => draw_edge_context_menu(app, ui, edge_idx),
+        Some(ContextTarget::Background(pos)) => draw_background_context_menu(app, ui, pos),
+        None => {
+            ui.label(tr("menu.no_target"));
+        }
+    }
+}
+
+fn draw_node_context_menu(app: &mut BasicApp, ui: &mut Ui, node_idx: NodeIndex<DefaultIx>) {
+    let Some(payload) = app.g.node_payload(node_idx) else {
+        ui.label(tr("menu.no_target"));
+        return;
+    };
+    let pinned = payload.pinned;
+    ui.horizontal(|ui| {
+        let response = ui.add(egui::TextEdit::singleline(&mut app.context_text).desired_width(120.0));
+        let submitted = response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+        if ui.button(tr("menu.rename")).clicked() || submitted {
+            match app.rename_node(node_idx, app.context_text.clone()) {
+                Ok(()) => ui.close_menu(),
+                Err(e) => println!("{}", e),
+            }
+        }
+    });
+    ui.horizontal(|ui| {
+        ui.label(tr("menu.weight"));
+        ui.add(egui::DragValue::new(&mut app.context_weight).speed(0.1).range(0.0..=100.0));
+        if ui.button(tr("menu.apply")).clicked() {
+            app.set_node_weight(node_idx, app.context_weight);
+            ui.close_menu();
+        }
+    });
+    ui.separator();
+    if ui.button(if pinned { tr("pin.unpin") } else { tr("pin.pin") }).clicked() {
+        app.toggle_node_pinned(node_idx);
+        ui.close_menu();
+    }
+    if ui.add_enabled(!app.g.selected_nodes().is_empty(), egui::Button::new(tr("menu.copy_selection"))).clicked() {
+        if let Some(text) = crate::clipboard::copy_selection(app) {
+            ui.ctx().copy_text(text.clone());
+            app.clipboard_text = Some(text);
+        }
+        ui.close_menu();
+    }
+    ui.menu_button(tr("menu.select"), |ui| draw_neighbourhood_commands(app, ui, &[node_idx]));
+    if ui.button(tr("focus.focus_here")).clicked() {
+        app.focus.enabled = true;
+        if let Some(id) = app.node_id(node_idx) {
+            app.focus.focus_on(id);
+        }
+        ui.close_menu();
+    }
+    if let Some(id) = app.node_id(node_idx) {
+        if let Some(i) = app.groups.group_of_meta(id) {
+            if ui.button(tr("group.expand")).clicked() {
+                app.expand_group(i);
+                ui.close_menu();
+            }
+        } else if let Some(i) = app.groups.expanded_group_of(id) {
+            if ui.button(trf("group.collapse_named", &[&app.groups.groups[i].name])).clicked() {
+                app.collapse_group(i);
+                ui.close_menu();
+            }
+        }
+    }
+    if ui.button(tr("menu.start_edge")).on_hover_text(tr("menu.start_edge_hint")).clicked() {
+        app.edge_draft_source = Some(node_idx);
+        ui.close_menu();
+    }
+    ui.separator();
+    if ui.button(tr("menu.delete_node")).clicked() {
+        app.remove_node_by_index(node_idx);
+        app.context_target = None;
+        ui.close_menu();
+    }
+}
+
+/// "Select 1-hop / k-hop / ancestors / descendants" around `start`, shared by the node menu and the focus settings.
+pub fn draw_neighbourhood_commands(app: &mut BasicApp, ui: &mut Ui, start: &[NodeIndex<DefaultIx>]) {
+    if ui.button(tr("menu.select_neighbours")).clicked() {
+        app.select_neighbourhood(start, 1, None);
+        ui.close_menu();
+    }
+    if ui.button(trf("focus.select_k_hop", &[&app.focus.k])).clicked() {
+        app.select_neighbourhood(start, app.focus.k, None);
+        ui.close_menu();
+    }
+    if app.is_directed {
+        if ui.button(tr("focus.select_ancestors")).clicked() {
+            app.select_neighbourhood(start, usize::MAX, Some(petgraph::Direction::Incoming));
+            ui.close_menu();
+        }
+        if ui.button(tr("focus.select_descendants")).clicked() {
+            app.select_neighbourhood(start, usize::MAX, Some(petgraph::Direction::Outgoing));
+            ui.close_menu();
+        }
+    }
+}
+
+fn draw_edge_context_menu(app: &mut BasicApp, ui: &mut Ui, edge_idx: EdgeIndex<DefaultIx>) {
+    if app.get_edge_payload_mut(edge_idx).is_none() {
+        ui.label(tr("menu.no_target"));
+        return;
+    }
+    egui::Grid::new("edge_context_fields").num_columns(2).show(ui, |ui| {
+        ui.label(tr("menu.label"));
+        ui.add(egui::TextEdit::singleline(&mut app.context_text).desired_width(120.0));
+        ui.end_row();
+        ui.label(tr("menu.weight"));
+        ui.add(egui::DragValue::new(&mut app.context_weight).speed(0.1).range(0.0..=100.0));
+        ui.end_row();
+    });
+    if ui.button(tr("menu.apply")).clicked() {
+        app.set_edge_weight_and_label(edge_idx, app.context_weight, app.context_text.clone());
+        ui.close_menu();
+    }
+    ui.separator();
+    if app.is_directed && ui.button(tr("menu.reverse_edge")).clicked() {
+        app.reverse_edge(edge_idx);
+        ui.close_menu();
+    }
+    if ui.button(tr("menu.delete_edge")).clicked() {
+        app.remove_edge(edge_idx);
+        app.context_target = None;
+        ui.close_menu();
+    }
+}
+
+fn draw_background_context_menu(app: &mut BasicApp, ui: &mut Ui, pos: Pos2) {
+    if ui.button(tr("menu.add_node_here")).clicked() {
+        app.add_node_at(pos);
+        ui.close_menu();
+    }
+    let paste = ui.add_enabled(app.clipboard_text.is_some(), egui::Button::new(tr("menu.paste"))).on_disabled_hover_text(tr("menu.paste_empty"));
+    if paste.clicked() {
+        if let Some(text) = app.clipboard_text.clone() {
+            crate::clipboard::paste_text(app, text, pos);
+        }
+        ui.close_menu();
+    }
+    ui.separator();
+    if ui.button(tr("nav.fit")).clicked() {
+        app.zoom_to_fit();
+        ui.close_menu();
+    }
+    if ui.button(tr("menu.run_layout")).clicked() {
+        app.run_layout();
+        ui.close_menu();
+    }
+}
@@ -0,0 +1,110 @@
+use egui::Vec2;
+use crate::app::{EdgePayload, NodePayload};
+use crate::i18n::trf;
+use crate::ids::NodeId;
+
+/// A member removed from the graph while its group is collapsed.
+#[derive(Clone, Debug)]
+pub struct HiddenNode {
+    pub payload: NodePayload,
+    pub offset: Vec2, // From the meta-node, so expanding puts the members back around it
+}
+
+/// An edge removed with a collapsed group, keyed by endpoint ids like the rest of the app.
+#[derive(Clone, Debug)]
+pub struct HiddenEdge {
+    pub source: NodeId,
+    pub target: NodeId,
+    pub payload: EdgePayload,
+}
+
+#[derive(Clone, Debug)]
+pub struct Collapsed {
+    pub meta: NodeId, // The node standing in for the group
+    pub nodes: Vec<HiddenNode>,
+    pub edges: Vec<HiddenEdge>,
+}
+
+/// Named cluster of nodes. Members are node ids, so a group survives anything that
+/// rebuilds node indices (direction conversion, collapsing other groups) and renaming.
+/// A member may be the meta-node of another collapsed group, which nests the groups.
+#[derive(Clone, Debug)]
+pub struct NodeGroup {
+    pub name: String,
+    pub members: Vec<NodeId>,
+    pub collapsed: Option<Collapsed>,
+}
+
+impl NodeGroup {
+    pub fn description(&self) -> String {
+        trf("group.description", &[&self.name, &self.members.len()])
+    }
+
+    fn hides(&self, id: NodeId) -> bool {
+        self.collapsed.as_ref().is_some_and(|c| c.nodes.iter().any(|n| n.payload.id == id))
+    }
+}
+
+#[derive(Default)]
+pub struct GroupSet {
+    pub groups: Vec<NodeGroup>,
+    pub input_name: String,
+}
+
+impl GroupSet {
+    pub fn clear(&mut self) {
+        self.groups.clear();
+    }
+
+    /// Labels of the nodes currently collapsed away.
+    pub fn hidden_labels(&self) -> impl Iterator<Item = &str> {
+        self.groups.iter().filter_map(|g| g.collapsed.as_ref()).flat_map(|c| c.nodes.iter().map(|n| n.payload.label.as_str()))
+    }
+
+    /// Index of the collapsed group whose meta-node is `id`.
+    pub fn group_of_meta(&self, id: NodeId) -> Option<usize> {
+        self.groups.iter().position(|g| g.collapsed.as_ref().is_some_and(|c| c.meta == id))
+    }
+
+    /// Innermost expanded group that has `id` as a member.
+    pub fn expanded_group_of(&self, id: NodeId) -> Option<usize> {
+        self.groups
+            .iter()
+            .enumerate()
+            .filter(|(_, g)| g.collapsed.is_none() && g.members.contains(&id))
+            .min_by_key(|(_, g)| g.members.len())
+            .map(|(i, _)| i)
+    }
+
+    /// The visible node that stands for `id`: itself, or the meta-node of the collapsed group hiding it.
+    pub fn representative(&self, id: NodeId, is_visible: impl Fn(NodeId) -> bool) -> Option<NodeId> {
+        let mut current = id;
+        // Each step moves one nesting level out; the bound only guards against cycles
+        for _ in 0..=self.groups.len() {
+            if is_visible(current) {
+                return Some(current);
+            }
+            current = self.groups.iter().find(|g| g.hides(current))?.collapsed.as_ref().map(|c| c.meta)?;
+        }
+        None
+    }
+
+    /// A deleted node leaves its groups and the hidden edges that led to it are dropped.
+    /// Deleting a meta-node deletes its group, hidden members and nested groups included.
+    pub fn node_removed(&mut self, id: NodeId) {
+        let mut removed = vec![id];
+        while let Some(id) = removed.pop() {
+            if let Some(i) = self.group_of_meta(id) {
+                let group = self.groups.remove(i);
+                removed.extend(group.collapsed.into_iter().flat_map(|c| c.nodes).map(|n| n.payload.id));
+            }
+            for group in &mut self.groups {
+                group.members.retain(|m| *m != id);
+                if let Some(collapsed) = group.collapsed.as_mut() {
+                    collapsed.edges.retain(|e| e.source != id && e.target != id);
+                }
+            }
+        }
+        self.groups.retain(|g| !g.members.is_empty());
+    }
+}
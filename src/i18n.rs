@@ -0,0 +1,788 @@
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::OnceLock;
+use serde::{Deserialize, Serialize};
+
+/// Key of the chosen language in eframe storage.
+pub const LANGUAGE_STORAGE_KEY: &str = "language";
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Language {
+    #[default]
+    ZhCn,
+    EnUs,
+}
+
+impl Language {
+    pub const ALL: [Language; 2] = [Language::ZhCn, Language::EnUs];
+
+    // Always shown in the language itself so users can find their way back
+    pub fn native_name(&self) -> &'static str {
+        match self {
+            Language::ZhCn => "简体中文",
+            Language::EnUs => "English",
+        }
+    }
+
+    fn catalogue(&self) -> &'static HashMap<&'static str, &'static str> {
+        static ZH: OnceLock<HashMap<&'static str, &'static str>> = OnceLock::new();
+        static EN: OnceLock<HashMap<&'static str, &'static str>> = OnceLock::new();
+        match self {
+            Language::ZhCn => ZH.get_or_init(|| ZH_CN.iter().copied().collect()),
+            Language::EnUs => EN.get_or_init(|| EN_US.iter().copied().collect()),
+        }
+    }
+}
+
+// The UI language is process-wide, like the egui style, so enum labels can stay `&'static str`
+static CURRENT: AtomicU8 = AtomicU8::new(0);
+
+pub fn set_language(language: Language) {
+    CURRENT.store(language as u8, Ordering::Relaxed);
+}
+
+pub fn language() -> Language {
+    match CURRENT.load(Ordering::Relaxed) {
+        1 => Language::EnUs,
+        _ => Language::ZhCn,
+    }
+}
+
+/// Message for `key` in the current language. Missing entries fall back to English,
+/// then to the key itself so a gap is visible instead of blank.
+pub fn tr(key: &'static str) -> &'static str {
+    language()
+        .catalogue()
+        .get(key)
+        .or_else(|| Language::EnUs.catalogue().get(key))
+        .copied()
+        .unwrap_or(key)
+}
+
+/// `tr` with positional `{0}`, `{1}`, ... placeholders; word order differs between languages.
+pub fn trf(key: &'static str, args: &[&dyn Display]) -> String {
+    let mut text = tr(key).to_owned();
+    for (i, arg) in args.iter().enumerate() {
+        text = text.replace(&format!("{{{}}}", i), &arg.to_string());
+    }
+    text
+}
+
+const EN_US: &[(&str, &str)] = &[
+    ("app.title", "egui_basic_standalone - Interactive graph"),
+    ("panel.title", "Settings"),
+    ("panel.language", "Language:"),
+    ("common.add", "Add"),
+    ("common.delete", "Delete"),
+    ("common.remove", "Remove"),
+    ("common.none", "None"),
+    ("graph.section", "Graph properties"),
+    ("graph.directed", "Directed graph"),
+    ("graph.node_count", "Nodes:"),
+    ("graph.edge_count", "Edges:"),
+    ("graph.reset", "Reset graph"),
+    ("graph.reset_hint", "Regenerate the graph with the current node and edge counts"),
+    ("gen.node_label", "Node{0}"),
+    ("gen.edge_label", "Edge {0}-{1}"),
+    ("gen.edge_between", "Edge: {0}->{1}"),
+    ("style.section", "Style"),
+    ("style.labels_always", "Always show labels"),
+    ("style.node_shape", "Node shape:"),
+    ("style.icon_file", "Icon file:"),
+    ("style.reload", "Reload"),
+    ("view3d.toggle", "3D view"),
+    ("view3d.hint", "Run the force simulation in 3D; drag with the left button to orbit, scroll to zoom"),
+    ("view3d.depth_cueing", "Depth cueing"),
+    ("view3d.distance", "Perspective distance"),
+    ("view3d.flatten", "Flatten to 2D (keep current view)"),
+    ("fonts.section", "Fonts"),
+    ("fonts.loaded", "Fonts in use (by priority):"),
+    ("fonts.none_found", "No CJK font found, Chinese text may render as boxes"),
+    ("fonts.discovered", "{0} CJK fonts found on this system"),
+    ("fonts.user_font", "Preferred font file:"),
+    ("fonts.fallback_chain", "Fallback chain:"),
+    ("fonts.rescan", "Rescan and apply"),
+    ("fonts.embedded", "(embedded font)"),
+    ("theme.preset", "Theme:"),
+    ("theme.light", "Light"),
+    ("theme.dark", "Dark"),
+    ("theme.high_contrast", "High contrast"),
+    ("theme.custom", "Custom"),
+    ("theme.dark_mode", "Dark UI:"),
+    ("theme.background", "Canvas background:"),
+    ("theme.node_color", "Node colour:"),
+    ("theme.edge_color", "Edge colour:"),
+    ("theme.selection_fill", "Selection fill:"),
+    ("theme.selection_stroke", "Selection stroke:"),
+    ("theme.label_color", "Label colour:"),
+    ("theme.label_font", "Label font:"),
+    ("theme.label_size", "Label size:"),
+    ("theme.style_sheet", "Style sheet file:"),
+    ("theme.save", "Save style sheet"),
+    ("theme.load", "Load and apply"),
+    ("theme.saved", "Style sheet saved to {0}"),
+    ("theme.save_failed", "Save failed: {0}"),
+    ("theme.loaded", "Applied style sheet {0}"),
+    ("theme.load_failed", "Load failed: {0}"),
+    ("font.monospace", "Monospace"),
+    ("font.proportional", "Proportional"),
+    ("shape.circle", "Circle"),
+    ("shape.square", "Square"),
+    ("shape.diamond", "Diamond"),
+    ("shape.hexagon", "Hexagon"),
+    ("shape.rounded_rect", "Rounded rectangle (fits label)"),
+    ("shape.icon", "Icon"),
+    ("edge_style.section", "Edge style:"),
+    ("edge_style.routing", "Routing:"),
+    ("edge_style.arrow", "Arrow:"),
+    ("edge_style.dash", "Stroke:"),
+    ("edge_style.label", "Edge labels:"),
+    ("edge_style.bundle_strength", "Bundling strength"),
+    ("edge_style.bundle_hint", "0 draws straight lines, 1 follows the hierarchy exactly"),
+    ("routing.straight", "Straight"),
+    ("routing.curved", "Curved"),
+    ("routing.orthogonal", "Orthogonal"),
+    ("routing.bundled", "Bundled"),
+    ("arrow.filled", "Filled triangle"),
+    ("arrow.open", "Open arrow"),
+    ("arrow.circle", "Dot"),
+    ("dash.solid", "Solid"),
+    ("dash.dashed", "Dashed"),
+    ("dash.dotted", "Dotted"),
+    ("edge_label.none", "Hidden"),
+    ("edge_label.label", "Label"),
+    ("edge_label.weight", "Weight"),
+    ("mapping.section", "Visual mapping"),
+    ("mapping.node_size", "Node size"),
+    ("mapping.radius_range", "  Radius range"),
+    ("mapping.node_color", "Node colour"),
+    ("mapping.ramp", "  Colour ramp"),
+    ("mapping.edge_width", "Edge width"),
+    ("mapping.width_range", "  Width range"),
+    ("mapping.edge_opacity", "Edge opacity"),
+    ("mapping.opacity_range", "  Opacity range"),
+    ("mapping.edge_color", "Edge colour"),
+    ("mapping.show_legend", "Show legend on canvas"),
+    ("attr.weight", "Weight"),
+    ("attr.degree", "Degree"),
+    ("ramp.blue_red", "Blue-red"),
+    ("ramp.green_yellow", "Green-yellow"),
+    ("ramp.grayscale", "Grayscale"),
+    ("nav.section", "Navigation"),
+    ("nav.fit", "Fit to screen"),
+    ("nav.fit_hint", "When enabled, the graph is always scaled to fit the screen."),
+    ("nav.zoom_pan", "Zoom and pan"),
+    ("nav.zoom_pan_hint", "When enabled, Ctrl+scroll zooms and middle-button drag pans."),
+    ("nav.zoom_speed", "Zoom speed"),
+    ("interaction.section", "Interaction"),
+    ("interaction.node_drag", "Draggable nodes"),
+    ("interaction.node_drag_hint", "When enabled, nodes can be dragged with the left mouse button."),
+    ("interaction.node_click", "Clickable nodes"),
+    ("interaction.node_click_hint", "When enabled, node clicks are reported as events."),
+    ("interaction.node_click_forced", "Enabled automatically while node dragging or selection is on"),
+    ("interaction.node_select", "Selectable nodes"),
+    ("interaction.node_select_hint", "When enabled, clicking a node selects or deselects it."),
+    ("interaction.node_select_forced", "Enabled automatically while node multi-selection is on"),
+    ("interaction.node_multi", "Multi-select nodes"),
+    ("interaction.node_multi_hint", "When enabled, Ctrl+click selects several nodes."),
+    ("interaction.edge_click", "Clickable edges"),
+    ("interaction.edge_click_hint", "When enabled, edge clicks are reported as events."),
+    ("interaction.edge_click_forced", "Enabled automatically while edge selection is on"),
+    ("interaction.edge_select", "Selectable edges"),
+    ("interaction.edge_select_hint", "When enabled, clicking an edge selects or deselects it."),
+    ("interaction.edge_select_forced", "Enabled automatically while edge multi-selection is on"),
+    ("interaction.edge_multi", "Multi-select edges"),
+    ("interaction.edge_multi_hint", "When enabled, Ctrl+click selects several edges."),
+    ("sim.section", "Simulation"),
+    ("sim.stop", "Stop simulation"),
+    ("sim.dt", "Time step (dt)"),
+    ("sim.cooloff", "Cool-off factor"),
+    ("sim.scale", "Scale"),
+    ("sim.energy", "Energy: {0}"),
+    ("sim.mean_displacement", "Mean displacement: {0}"),
+    ("sim.converged", "Layout converged, simulation paused"),
+    ("sim.resume", "Resume"),
+    ("sim.auto_stop", "Stop automatically when converged"),
+    ("sim.threshold", "Displacement threshold"),
+    ("overlap.remove", "Remove overlaps"),
+    ("overlap.remove_hint", "Push overlapping nodes apart using their radius and label size"),
+    ("overlap.continuous", "Remove continuously while simulating"),
+    ("overlap.padding", "Overlap padding"),
+    ("weights.section", "Weight effects:"),
+    ("weights.edge_effect", "Edge weight"),
+    ("weights.edge_mapping", "Edge mapping"),
+    ("weights.node_effect", "Node weight"),
+    ("weights.node_mapping", "Node mapping"),
+    ("weight_mapping.linear", "Linear"),
+    ("weight_mapping.log", "Logarithmic"),
+    ("weight_mapping.inverse", "Inverse"),
+    ("edge_effect.spring_strength", "Spring strength"),
+    ("edge_effect.ideal_length", "Ideal length"),
+    ("node_effect.mass", "Mass"),
+    ("node_effect.charge", "Repulsive charge"),
+    ("constraints.section", "Layout constraints"),
+    ("constraints.from_selection", "Create a constraint from the selected nodes:"),
+    ("constraints.align_horizontal", "Align horizontally"),
+    ("constraints.align_vertical", "Align vertically"),
+    ("constraints.distance", "Distance:"),
+    ("constraints.min_separation", "Minimum separation"),
+    ("constraints.bounding_box", "Keep in current region"),
+    ("constraints.bounding_box_hint", "Selected nodes can only move inside their current bounding box"),
+    ("constraints.set_group_a", "Set as group A ({0})"),
+    ("constraints.set_group_b", "Set as group B ({0})"),
+    ("constraints.a_left_of_b", "A left of B"),
+    ("constraints.a_above_b", "A above B"),
+    ("constraints.iterations", "Projection iterations"),
+    ("constraints.empty", "No constraints"),
+    ("constraints.show_guides", "Show constraint guides"),
+    ("constraint.align_horizontal", "Horizontal alignment ({0} nodes)"),
+    ("constraint.align_vertical", "Vertical alignment ({0} nodes)"),
+    ("constraint.min_separation", "Minimum separation {0} ({1} nodes)"),
+    ("constraint.left_of", "{0} nodes left of {1} nodes"),
+    ("constraint.above", "{0} nodes above {1} nodes"),
+    ("constraint.bounding_box", "Fixed region ({0} nodes)"),
+    ("edit.section", "Nodes and edges"),
+    ("edit.disabled_in_3d", "The graph can't be edited in the 3D view, switch back to 2D first."),
+    ("edit.add_node_heading", "Add node:"),
+    ("edit.label", "Label:"),
+    ("edit.weight", "Weight:"),
+    ("edit.add_node", "Add node"),
+    ("edit.remove_node_heading", "Remove node:"),
+    ("edit.add_edge_heading", "Add edge (by label):"),
+    ("edit.from", "From:"),
+    ("edit.to", "To:"),
+    ("edit.add_edge", "Add edge"),
+    ("edit.add_edge_selected", "Add edge between selected nodes"),
+    ("edit.add_edge_selected_hint", "Select two nodes, then click to connect them"),
+    ("edit.remove_selected_edges", "Delete selected edges"),
+    ("edit.remove_selected_edges_hint", "Select one or more edges, then click to delete them"),
+    ("pin.pin", "Pin node"),
+    ("pin.unpin", "Unpin"),
+    ("pin.pin_selected", "Pin selected nodes"),
+    ("pin.pin_selected_hint", "Pinned nodes ignore the force simulation; Shift+drag also pins a node"),
+    ("props.heading", "Selected element:"),
+    ("props.node", "Node: {0}"),
+    ("props.edge", "Edge: {0}"),
+    ("props.weight_prefix", "Weight: "),
+    ("props.shape", "Shape:"),
+    ("props.follow_global", "Use global"),
+    ("props.none", "Nothing selected."),
+    ("debug.section", "Debug"),
+    ("debug.egui_graph", "Egui graph: {0} nodes, {1} edges"),
+    ("debug.fdg_graph", "Fdg graph: {0} nodes, {1} edges"),
+    ("debug.id_map", "Id map: {0} nodes, {1} edges"),
+    ("debug.selected_nodes", "Selected nodes:"),
+    ("debug.selected_edges", "Selected edges:"),
+    ("search.hint", "Search node labels… (Ctrl+F)"),
+    ("search.matches", "{0} matches"),
+    ("search.zoom", "Zoom to matches"),
+    ("search.clear", "Clear"),
+    ("search.mode", "Non-matching:"),
+    ("filter.section", "Search and filter"),
+    ("filter.add", "Add condition"),
+    ("filter.empty", "No filter conditions"),
+    ("filter.results", "Results:"),
+    ("filter.more", "… and {0} more"),
+    ("filter_mode.dim", "Dim"),
+    ("filter_mode.hide", "Hide"),
+    ("filter_mode.select", "Select matches"),
+    ("predicate.weight", "Weight range"),
+    ("predicate.degree", "Degree range"),
+    ("predicate.pinned", "Pinned"),
+    ("predicate.shape", "Shape"),
+    ("selection.tool", "Tool:"),
+    ("selection.pointer", "Pointer"),
+    ("selection.rectangle", "Rectangle"),
+    ("selection.lasso", "Lasso"),
+    ("selection.include_edges", "Include edges"),
+    ("selection.hint", "Drag to select; Shift adds, Ctrl subtracts, Shift+Ctrl intersects"),
+    ("menu.no_target", "Nothing under the cursor"),
+    ("menu.rename", "Rename"),
+    ("menu.weight", "Weight"),
+    ("menu.label", "Label"),
+    ("menu.apply", "Apply"),
+    ("menu.select_neighbours", "Select 1-hop neighbours"),
+    ("menu.start_edge", "Start edge from here"),
+    ("menu.start_edge_hint", "Then click the target node, Shift+click to keep drawing a path; Esc or a click on the background cancels"),
+    ("menu.delete_node", "Delete node"),
+    ("menu.reverse_edge", "Reverse direction"),
+    ("menu.delete_edge", "Delete edge"),
+    ("menu.add_node_here", "Add node here"),
+    ("menu.paste", "Paste (Ctrl+V reads the system clipboard)"),
+    ("menu.paste_empty", "Nothing copied yet; Ctrl+V pastes a selection copied in another window"),
+    ("menu.run_layout", "Run layout"),
+    ("selection.edge", "Edge"),
+    ("selection.edge_hint", "Drag from one node to another to connect them; hold Shift on release to keep drawing from the target, Esc stops"),
+    ("action.delete_selection", "Delete selection"),
+    ("action.select_all", "Select all"),
+    ("action.clear_selection", "Clear selection / stop drawing an edge"),
+    ("action.add_node", "Add node at the cursor"),
+    ("action.edge_tool", "Toggle the edge tool"),
+    ("action.pointer_tool", "Pointer tool"),
+    ("action.fit_to_screen", "Fit to screen"),
+    ("action.toggle_simulation", "Pause / resume the simulation"),
+    ("action.run_layout", "Run layout"),
+    ("action.focus_search", "Focus the search field"),
+    ("action.cheat_sheet", "Show the shortcut cheat sheet"),
+    ("keymap.title", "Keyboard shortcuts"),
+    ("keymap.hint", "Click a shortcut, then press the new key combination; Esc cancels."),
+    ("keymap.press_key", "Press a key…"),
+    ("keymap.unbound", "Unbound"),
+    ("keymap.clear", "Clear"),
+    ("keymap.reset", "Restore defaults"),
+    ("keymap.cheat_sheet", "Shortcuts"),
+    ("keymap.edit", "Edit shortcuts…"),
+    ("keymap.close", "Close"),
+    ("keymap.open", "Keyboard shortcuts…"),
+    ("keymap.open_hint", "Press ? on the canvas to see every shortcut"),
+    ("clipboard.invalid", "The clipboard does not hold a graph selection: {0}"),
+    ("menu.copy_selection", "Copy selection (Ctrl+C)"),
+    ("rename.missing", "The node no longer exists"),
+    ("rename.empty", "The label cannot be empty"),
+    ("props.rename_hint", "Double-click a node or edge on the canvas to rename it"),
+    ("nav.minimap", "Show minimap"),
+    ("nav.minimap_hint", "Click the minimap to jump there, drag to move the viewport"),
+    ("group.section", "Groups"),
+    ("group.name_hint", "Group name"),
+    ("group.create", "Group selected nodes"),
+    ("group.nesting_hint", "A group containing a collapsed group nests it; expand the outer group first."),
+    ("group.default_name", "Group {0}"),
+    ("group.description", "{0} ({1} members)"),
+    ("group.collapse", "Collapse"),
+    ("group.collapse_named", "Collapse group {0}"),
+    ("group.expand", "Expand group"),
+    ("group.select", "Select members"),
+    ("group.dissolve", "Dissolve the group (keeps the nodes)"),
+    ("group.edge_label", "{0} edges"),
+    ("group.dissolve_button", "Dissolve"),
+    ("menu.select", "Select"),
+    ("focus.section", "Focus mode"),
+    ("focus.enabled", "Focus on neighbourhoods"),
+    ("focus.enabled_hint", "Selecting a node shows only its k-hop neighbourhood and lays it out again"),
+    ("focus.k", "Hops k"),
+    ("focus.fade", "Other nodes:"),
+    ("focus.expand_selection", "Expand the selection:"),
+    ("focus.select_k_hop", "Select {0}-hop neighbours"),
+    ("focus.select_ancestors", "Select ancestors"),
+    ("focus.select_descendants", "Select descendants"),
+    ("focus.focus_here", "Focus on this node"),
+    ("focus.breadcrumbs", "Focus:"),
+    ("focus.exit", "Leave focus"),
+    ("bulk.summary", "{0} nodes and {1} edges selected"),
+    ("bulk.current", "Current"),
+    ("bulk.change", "Change"),
+    ("bulk.new_value", "New value"),
+    ("bulk.keep", "Keep"),
+    ("bulk.set", "Set"),
+    ("bulk.clear", "Reset"),
+    ("bulk.weight", "Weight"),
+    ("bulk.weight_set", "Set to"),
+    ("bulk.weight_scale", "Multiply by"),
+    ("bulk.weight_offset", "Add"),
+    ("bulk.weight_range", "mixed ({0}–{1})"),
+    ("bulk.default", "default"),
+    ("bulk.mixed", "mixed"),
+    ("bulk.node_radius", "Node radius"),
+    ("bulk.node_color", "Node colour"),
+    ("bulk.node_label_color", "Node label colour"),
+    ("bulk.edge_width", "Edge width"),
+    ("bulk.edge_color", "Edge colour"),
+    ("bulk.edge_dash", "Edge dash"),
+    ("bulk.rename", "Rename:"),
+    ("bulk.rename_hint", "e.g. prefix_{n}"),
+    ("bulk.rename_help", "{n} is a running number (nodes first, then edges), {label} the current label; leave empty to keep labels"),
+    ("bulk.rename_start", "from: "),
+    ("bulk.rename_preview", "First becomes: {0}"),
+    ("bulk.apply", "Apply to selection"),
+    ("bulk.undo", "Undo last bulk edit"),
+    ("attr.section", "Attributes:"),
+    ("attr.new_key", "name"),
+    ("attr.new_value", "value"),
+    ("attr.empty_key", "Attribute name cannot be empty"),
+    ("attr.duplicate_key", "Attribute \"{0}\" already exists"),
+    ("attr.invalid", "\"{0}\" is not a valid {1}"),
+    ("attr_type.auto", "Auto"),
+    ("attr_type.string", "text"),
+    ("attr_type.number", "number"),
+    ("attr_type.bool", "boolean"),
+    ("attr_type.color", "colour"),
+    ("attr_type.list", "list"),
+    ("attr_test.present", "exists"),
+    ("attr_test.equals", "equals"),
+    ("attr_test.contains", "contains"),
+    ("attr_test.range", "in range"),
+    ("predicate.attribute", "Attribute"),
+    ("edit.label_not_found", "No node is labelled '{0}'"),
+    ("edit.label_ambiguous", "{0} nodes are labelled '{1}'; pick the node on the canvas instead"),
+    ("edit.self_loop", "A self-loop can't be added by label"),
+    ("project.file", "Project file:"),
+    ("project.save", "Save project"),
+    ("project.load", "Open project"),
+    ("project.load_hint", "Replaces the current graph with the one in the file (nodes, edges, positions, pins, styling and groups)"),
+    ("project.saved", "Project saved to {0}"),
+    ("project.save_failed", "Save failed: {0}"),
+    ("project.loaded", "Opened project {0}"),
+    ("project.load_failed", "Load failed: {0}"),
+    ("project.invalid", "Not a project file: {0}"),
+    ("project.bad_ids", "The project file has missing or repeated ids"),
+];
+
+const ZH_CN: &[(&str, &str)] = &[
+    ("app.title", "egui_basic_standalone - 可交互图应用"),
+    ("panel.title", "配置面板"),
+    ("panel.language", "语言:"),
+    ("common.add", "添加"),
+    ("common.delete", "删除"),
+    ("common.remove", "移除"),
+    ("common.none", "无"),
+    ("graph.section", "图属性"),
+    ("graph.directed", "有向图"),
+    ("graph.node_count", "节点数:"),
+    ("graph.edge_count", "边数:"),
+    ("graph.reset", "重置图"),
+    ("graph.reset_hint", "使用当前节点和边数重新生成图"),
+    ("gen.node_label", "节点{0}"),
+    ("gen.edge_label", "边 {0}-{1}"),
+    ("gen.edge_between", "边: {0}->{1}"),
+    ("style.section", "样式设置"),
+    ("style.labels_always", "总是显示标签"),
+    ("style.node_shape", "节点形状:"),
+    ("style.icon_file", "图标文件:"),
+    ("style.reload", "重新加载"),
+    ("view3d.toggle", "3D 视图"),
+    ("view3d.hint", "在三维空间中运行力导向模拟；左键拖拽旋转，滚轮缩放"),
+    ("view3d.depth_cueing", "深度提示"),
+    ("view3d.distance", "透视距离"),
+    ("view3d.flatten", "压平为2D (保留当前视角)"),
+    ("fonts.section", "字体设置"),
+    ("fonts.loaded", "当前使用的字体 (按优先级):"),
+    ("fonts.none_found", "未找到中文字体，中文可能显示为方块"),
+    ("fonts.discovered", "系统中发现 {0} 个中文字体"),
+    ("fonts.user_font", "首选字体文件:"),
+    ("fonts.fallback_chain", "回退字体链:"),
+    ("fonts.rescan", "重新扫描并应用"),
+    ("fonts.embedded", "(内置字体)"),
+    ("theme.preset", "主题:"),
+    ("theme.light", "浅色"),
+    ("theme.dark", "深色"),
+    ("theme.high_contrast", "高对比度"),
+    ("theme.custom", "自定义"),
+    ("theme.dark_mode", "深色界面:"),
+    ("theme.background", "画布背景:"),
+    ("theme.node_color", "节点颜色:"),
+    ("theme.edge_color", "边颜色:"),
+    ("theme.selection_fill", "选中填充:"),
+    ("theme.selection_stroke", "选中描边:"),
+    ("theme.label_color", "标签颜色:"),
+    ("theme.label_font", "标签字体:"),
+    ("theme.label_size", "标签字号:"),
+    ("theme.style_sheet", "样式表文件:"),
+    ("theme.save", "保存样式表"),
+    ("theme.load", "加载并应用"),
+    ("theme.saved", "样式表已保存到 {0}"),
+    ("theme.save_failed", "保存失败: {0}"),
+    ("theme.loaded", "已应用样式表 {0}"),
+    ("theme.load_failed", "加载失败: {0}"),
+    ("font.monospace", "等宽"),
+    ("font.proportional", "比例"),
+    ("shape.circle", "圆形"),
+    ("shape.square", "方形"),
+    ("shape.diamond", "菱形"),
+    ("shape.hexagon", "六边形"),
+    ("shape.rounded_rect", "圆角矩形 (适应标签)"),
+    ("shape.icon", "图标"),
+    ("edge_style.section", "边样式:"),
+    ("edge_style.routing", "走线:"),
+    ("edge_style.arrow", "箭头:"),
+    ("edge_style.dash", "线型:"),
+    ("edge_style.label", "边标签:"),
+    ("edge_style.bundle_strength", "绑定强度"),
+    ("edge_style.bundle_hint", "0 为直线，1 为完全沿层次结构走线"),
+    ("routing.straight", "直线"),
+    ("routing.curved", "曲线"),
+    ("routing.orthogonal", "正交折线"),
+    ("routing.bundled", "边绑定"),
+    ("arrow.filled", "实心三角"),
+    ("arrow.open", "开放箭头"),
+    ("arrow.circle", "圆点"),
+    ("dash.solid", "实线"),
+    ("dash.dashed", "虚线"),
+    ("dash.dotted", "点线"),
+    ("edge_label.none", "不显示"),
+    ("edge_label.label", "标签"),
+    ("edge_label.weight", "权重"),
+    ("mapping.section", "视觉映射"),
+    ("mapping.node_size", "节点大小"),
+    ("mapping.radius_range", "  半径范围"),
+    ("mapping.node_color", "节点颜色"),
+    ("mapping.ramp", "  色带"),
+    ("mapping.edge_width", "边宽度"),
+    ("mapping.width_range", "  宽度范围"),
+    ("mapping.edge_opacity", "边透明度"),
+    ("mapping.opacity_range", "  透明度范围"),
+    ("mapping.edge_color", "边颜色"),
+    ("mapping.show_legend", "在画布上显示图例"),
+    ("attr.weight", "权重"),
+    ("attr.degree", "度数"),
+    ("ramp.blue_red", "蓝-红"),
+    ("ramp.green_yellow", "绿-黄"),
+    ("ramp.grayscale", "灰度"),
+    ("nav.section", "导航设置"),
+    ("nav.fit", "适应屏幕"),
+    ("nav.fit_hint", "启用后，图表将始终缩放以适应屏幕。"),
+    ("nav.zoom_pan", "缩放与平移"),
+    ("nav.zoom_pan_hint", "启用后，可用Ctrl+滚轮缩放，鼠标中键拖拽平移。"),
+    ("nav.zoom_speed", "缩放速度"),
+    ("interaction.section", "交互设置"),
+    ("interaction.node_drag", "节点可拖拽"),
+    ("interaction.node_drag_hint", "启用后，可按住鼠标左键拖拽节点。"),
+    ("interaction.node_click", "节点可点击"),
+    ("interaction.node_click_hint", "启用后，可捕获节点点击事件。"),
+    ("interaction.node_click_forced", "节点拖拽或选择已启用时，点击自动启用"),
+    ("interaction.node_select", "节点可选择"),
+    ("interaction.node_select_hint", "启用后，可单击选择/取消选择节点。"),
+    ("interaction.node_select_forced", "节点多选已启用时，单选自动启用"),
+    ("interaction.node_multi", "节点可多选"),
+    ("interaction.node_multi_hint", "启用后，可按住Ctrl点击多选节点。"),
+    ("interaction.edge_click", "边可点击"),
+    ("interaction.edge_click_hint", "启用后，可捕获边点击事件。"),
+    ("interaction.edge_click_forced", "边选择已启用时，点击自动启用"),
+    ("interaction.edge_select", "边可选择"),
+    ("interaction.edge_select_hint", "启用后，可单击选择/取消选择边。"),
+    ("interaction.edge_select_forced", "边多选已启用时，单选自动启用"),
+    ("interaction.edge_multi", "边可多选"),
+    ("interaction.edge_multi_hint", "启用后，可按住Ctrl点击多选边。"),
+    ("sim.section", "模拟设置"),
+    ("sim.stop", "停止模拟"),
+    ("sim.dt", "时间步长 (dt)"),
+    ("sim.cooloff", "冷却因子"),
+    ("sim.scale", "缩放尺度"),
+    ("sim.energy", "能量: {0}"),
+    ("sim.mean_displacement", "平均位移: {0}"),
+    ("sim.converged", "布局已收敛，模拟自动暂停"),
+    ("sim.resume", "继续"),
+    ("sim.auto_stop", "收敛后自动停止"),
+    ("sim.threshold", "位移阈值"),
+    ("overlap.remove", "消除重叠"),
+    ("overlap.remove_hint", "按节点半径和标签尺寸将重叠的节点推开"),
+    ("overlap.continuous", "模拟时持续消除"),
+    ("overlap.padding", "重叠间隙"),
+    ("weights.section", "权重影响:"),
+    ("weights.edge_effect", "边权重"),
+    ("weights.edge_mapping", "边映射"),
+    ("weights.node_effect", "节点权重"),
+    ("weights.node_mapping", "节点映射"),
+    ("weight_mapping.linear", "线性"),
+    ("weight_mapping.log", "对数"),
+    ("weight_mapping.inverse", "反比"),
+    ("edge_effect.spring_strength", "弹簧强度"),
+    ("edge_effect.ideal_length", "理想长度"),
+    ("node_effect.mass", "质量"),
+    ("node_effect.charge", "斥力电荷"),
+    ("constraints.section", "布局约束"),
+    ("constraints.from_selection", "根据当前选中的节点创建约束:"),
+    ("constraints.align_horizontal", "水平对齐"),
+    ("constraints.align_vertical", "垂直对齐"),
+    ("constraints.distance", "间距:"),
+    ("constraints.min_separation", "最小间距"),
+    ("constraints.bounding_box", "固定在当前区域"),
+    ("constraints.bounding_box_hint", "选中节点只能在其当前包围盒内移动"),
+    ("constraints.set_group_a", "设为组A ({0})"),
+    ("constraints.set_group_b", "设为组B ({0})"),
+    ("constraints.a_left_of_b", "A 在 B 左侧"),
+    ("constraints.a_above_b", "A 在 B 上方"),
+    ("constraints.iterations", "投影迭代次数"),
+    ("constraints.empty", "暂无约束"),
+    ("constraints.show_guides", "显示约束辅助线"),
+    ("constraint.align_horizontal", "水平对齐 ({0} 个节点)"),
+    ("constraint.align_vertical", "垂直对齐 ({0} 个节点)"),
+    ("constraint.min_separation", "最小间距 {0} ({1} 个节点)"),
+    ("constraint.left_of", "{0} 个节点在 {1} 个节点左侧"),
+    ("constraint.above", "{0} 个节点在 {1} 个节点上方"),
+    ("constraint.bounding_box", "固定区域 ({0} 个节点)"),
+    ("edit.section", "节点/边管理"),
+    ("edit.disabled_in_3d", "3D 视图下不能编辑图结构，请先切换回2D。"),
+    ("edit.add_node_heading", "添加节点:"),
+    ("edit.label", "标签:"),
+    ("edit.weight", "权重:"),
+    ("edit.add_node", "添加节点"),
+    ("edit.remove_node_heading", "删除节点:"),
+    ("edit.add_edge_heading", "添加边 (通过标签):"),
+    ("edit.from", "从:"),
+    ("edit.to", "到:"),
+    ("edit.add_edge", "添加边"),
+    ("edit.add_edge_selected", "在选中节点间添加边"),
+    ("edit.add_edge_selected_hint", "选择两个节点后点击此按钮添加边"),
+    ("edit.remove_selected_edges", "删除选中的边"),
+    ("edit.remove_selected_edges_hint", "选择一条或多条边后点击此按钮删除"),
+    ("pin.pin", "固定节点"),
+    ("pin.unpin", "取消固定"),
+    ("pin.pin_selected", "固定选中节点"),
+    ("pin.pin_selected_hint", "固定的节点不受力导向模拟影响，也可按住Shift拖拽节点来固定"),
+    ("props.heading", "选中元素属性:"),
+    ("props.node", "节点: {0}"),
+    ("props.edge", "边: {0}"),
+    ("props.weight_prefix", "权重: "),
+    ("props.shape", "形状:"),
+    ("props.follow_global", "跟随全局"),
+    ("props.none", "未选择任何元素或选择不明确。"),
+    ("debug.section", "调试信息"),
+    ("debug.egui_graph", "Egui图: {0} 节点, {1} 边"),
+    ("debug.fdg_graph", "Fdg图: {0} 节点, {1} 边"),
+    ("debug.id_map", "ID 映射: {0} 个节点, {1} 条边"),
+    ("debug.selected_nodes", "选中的节点:"),
+    ("debug.selected_edges", "选中的边:"),
+    ("search.hint", "搜索节点标签… (Ctrl+F)"),
+    ("search.matches", "{0} 个匹配"),
+    ("search.zoom", "定位匹配"),
+    ("search.clear", "清除"),
+    ("search.mode", "未匹配元素:"),
+    ("filter.section", "搜索与筛选"),
+    ("filter.add", "添加条件"),
+    ("filter.empty", "暂无筛选条件"),
+    ("filter.results", "匹配结果:"),
+    ("filter.more", "… 另有 {0} 个"),
+    ("filter_mode.dim", "淡化"),
+    ("filter_mode.hide", "隐藏"),
+    ("filter_mode.select", "选中匹配项"),
+    ("predicate.weight", "权重范围"),
+    ("predicate.degree", "度数范围"),
+    ("predicate.pinned", "已固定"),
+    ("predicate.shape", "形状"),
+    ("selection.tool", "工具:"),
+    ("selection.pointer", "指针"),
+    ("selection.rectangle", "框选"),
+    ("selection.lasso", "套索"),
+    ("selection.include_edges", "同时选中边"),
+    ("selection.hint", "拖拽选择；Shift 添加，Ctrl 移除，Shift+Ctrl 取交集"),
+    ("menu.no_target", "光标下没有可操作的元素"),
+    ("menu.rename", "重命名"),
+    ("menu.weight", "权重"),
+    ("menu.label", "标签"),
+    ("menu.apply", "应用"),
+    ("menu.select_neighbours", "选择 1 跳邻居"),
+    ("menu.start_edge", "从此节点连边"),
+    ("menu.start_edge_hint", "然后点击目标节点，按住 Shift 点击可连续连线；按Esc或点击空白处取消"),
+    ("menu.delete_node", "删除节点"),
+    ("menu.reverse_edge", "反转方向"),
+    ("menu.delete_edge", "删除边"),
+    ("menu.add_node_here", "在此处添加节点"),
+    ("menu.paste", "粘贴 (Ctrl+V 读取系统剪贴板)"),
+    ("menu.paste_empty", "尚未复制任何元素；用 Ctrl+V 可粘贴其他窗口复制的子图"),
+    ("menu.run_layout", "运行布局"),
+    ("selection.edge", "连线"),
+    ("selection.edge_hint", "从一个节点拖到另一个节点以连线；松开时按住 Shift 可继续从目标节点连线，Esc 结束"),
+    ("action.delete_selection", "删除所选"),
+    ("action.select_all", "全选"),
+    ("action.clear_selection", "取消选择 / 结束连线"),
+    ("action.add_node", "在光标处添加节点"),
+    ("action.edge_tool", "切换连线工具"),
+    ("action.pointer_tool", "指针工具"),
+    ("action.fit_to_screen", "适应屏幕"),
+    ("action.toggle_simulation", "暂停 / 继续模拟"),
+    ("action.run_layout", "运行布局"),
+    ("action.focus_search", "聚焦搜索框"),
+    ("action.cheat_sheet", "显示快捷键一览"),
+    ("keymap.title", "快捷键设置"),
+    ("keymap.hint", "点击快捷键后按下新的按键组合，Esc 取消。"),
+    ("keymap.press_key", "请按键…"),
+    ("keymap.unbound", "未绑定"),
+    ("keymap.clear", "清除"),
+    ("keymap.reset", "恢复默认"),
+    ("keymap.cheat_sheet", "快捷键一览"),
+    ("keymap.edit", "编辑快捷键…"),
+    ("keymap.close", "关闭"),
+    ("keymap.open", "快捷键…"),
+    ("keymap.open_hint", "在画布上按 ? 查看所有快捷键"),
+    ("clipboard.invalid", "剪贴板内容不是可粘贴的子图: {0}"),
+    ("menu.copy_selection", "复制所选 (Ctrl+C)"),
+    ("rename.missing", "该节点已不存在"),
+    ("rename.empty", "标签不能为空"),
+    ("props.rename_hint", "在画布上双击节点或边即可重命名"),
+    ("nav.minimap", "显示小地图"),
+    ("nav.minimap_hint", "点击小地图跳转，拖拽移动视口"),
+    ("group.section", "分组"),
+    ("group.name_hint", "分组名称"),
+    ("group.create", "将所选节点编组"),
+    ("group.nesting_hint", "包含已折叠分组的分组会嵌套它；先展开外层分组。"),
+    ("group.default_name", "分组 {0}"),
+    ("group.description", "{0} ({1} 个成员)"),
+    ("group.collapse", "折叠"),
+    ("group.collapse_named", "折叠分组 {0}"),
+    ("group.expand", "展开分组"),
+    ("group.select", "选中成员"),
+    ("group.dissolve", "解散分组 (保留节点)"),
+    ("group.edge_label", "{0} 条边"),
+    ("group.dissolve_button", "解散"),
+    ("menu.select", "选择"),
+    ("focus.section", "焦点模式"),
+    ("focus.enabled", "启用邻域焦点"),
+    ("focus.enabled_hint", "选中一个节点后只突出显示其 k 跳邻域，并对其重新局部布局"),
+    ("focus.k", "跳数 k"),
+    ("focus.fade", "其余节点:"),
+    ("focus.expand_selection", "扩展当前选择:"),
+    ("focus.select_k_hop", "选择 {0} 跳邻居"),
+    ("focus.select_ancestors", "选择所有祖先"),
+    ("focus.select_descendants", "选择所有后代"),
+    ("focus.focus_here", "聚焦此节点"),
+    ("focus.breadcrumbs", "焦点:"),
+    ("focus.exit", "退出焦点"),
+    ("bulk.summary", "已选择 {0} 个节点、{1} 条边"),
+    ("bulk.current", "当前值"),
+    ("bulk.change", "操作"),
+    ("bulk.new_value", "新值"),
+    ("bulk.keep", "保持不变"),
+    ("bulk.set", "设为"),
+    ("bulk.clear", "恢复默认"),
+    ("bulk.weight", "权重"),
+    ("bulk.weight_set", "设为"),
+    ("bulk.weight_scale", "乘以"),
+    ("bulk.weight_offset", "加上"),
+    ("bulk.weight_range", "不同 ({0}–{1})"),
+    ("bulk.default", "默认"),
+    ("bulk.mixed", "不同"),
+    ("bulk.node_radius", "节点半径"),
+    ("bulk.node_color", "节点颜色"),
+    ("bulk.node_label_color", "节点标签颜色"),
+    ("bulk.edge_width", "边宽度"),
+    ("bulk.edge_color", "边颜色"),
+    ("bulk.edge_dash", "边线型"),
+    ("bulk.rename", "重命名:"),
+    ("bulk.rename_hint", "例如 prefix_{n}"),
+    ("bulk.rename_help", "{n} 为序号 (先节点后边)，{label} 为原标签；留空则不重命名"),
+    ("bulk.rename_start", "起始: "),
+    ("bulk.rename_preview", "第一个将命名为: {0}"),
+    ("bulk.apply", "应用到所选"),
+    ("bulk.undo", "撤销上次批量编辑"),
+    ("attr.section", "属性:"),
+    ("attr.new_key", "名称"),
+    ("attr.new_value", "值"),
+    ("attr.empty_key", "属性名称不能为空"),
+    ("attr.duplicate_key", "属性 \"{0}\" 已存在"),
+    ("attr.invalid", "\"{0}\" 不是有效的{1}"),
+    ("attr_type.auto", "自动"),
+    ("attr_type.string", "文本"),
+    ("attr_type.number", "数值"),
+    ("attr_type.bool", "布尔"),
+    ("attr_type.color", "颜色"),
+    ("attr_type.list", "列表"),
+    ("attr_test.present", "存在"),
+    ("attr_test.equals", "等于"),
+    ("attr_test.contains", "包含"),
+    ("attr_test.range", "范围"),
+    ("predicate.attribute", "属性"),
+    ("edit.label_not_found", "没有标签为 '{0}' 的节点"),
+    ("edit.label_ambiguous", "有 {0} 个节点的标签为 '{1}'，请在画布上选择节点"),
+    ("edit.self_loop", "不能通过标签添加自环"),
+    ("project.file", "项目文件:"),
+    ("project.save", "保存项目"),
+    ("project.load", "打开项目"),
+    ("project.load_hint", "用文件中的图替换当前图 (节点、边、位置、固定状态、样式与分组)"),
+    ("project.saved", "项目已保存到 {0}"),
+    ("project.save_failed", "保存失败: {0}"),
+    ("project.loaded", "已打开项目 {0}"),
+    ("project.load_failed", "加载失败: {0}"),
+    ("project.invalid", "不是项目文件: {0}"),
+    ("project.bad_ids", "项目文件中存在缺失或重复的 ID"),
+];
@@ -0,0 +1,192 @@
+use std::collections::HashMap;
+use fdg::ForceGraph;
+use petgraph::EdgeType;
+use petgraph::stable_graph::{DefaultIx, EdgeIndex, NodeIndex};
+use serde::{Deserialize, Serialize};
+use crate::app::{AppGraph, AppGraphOf, EdgePayload, NodePayload};
+
+/// Identity of a node for its whole life. Unlike labels it is never edited, and unlike
+/// petgraph indices it means the same node in the egui graph and in the simulation.
+/// The default, 0, is never handed out and marks a payload that has no id yet.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct NodeId(pub u64);
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct EdgeId(pub u64);
+
+/// Hands out ids and tracks where each element sits in the egui graph and in the 2D simulation.
+/// The 3D simulation is a `map` of the 2D one and shares its indices only until the next
+/// structural edit; code crossing between the two goes through payload ids.
+#[derive(Default)]
+pub struct GraphIds {
+    next_node: u64,
+    next_edge: u64,
+    nodes: HashMap<NodeId, (NodeIndex<DefaultIx>, NodeIndex<DefaultIx>)>, // (egui, simulation)
+    edges: HashMap<EdgeId, (EdgeIndex<DefaultIx>, EdgeIndex<DefaultIx>)>,
+}
+
+impl GraphIds {
+    pub fn new_node_id(&mut self) -> NodeId {
+        self.next_node += 1;
+        NodeId(self.next_node)
+    }
+
+    pub fn new_edge_id(&mut self) -> EdgeId {
+        self.next_edge += 1;
+        EdgeId(self.next_edge)
+    }
+
+    /// Keeps an id that lives outside both graphs (a collapsed group's member) from being handed out.
+    pub fn reserve_node(&mut self, id: NodeId) {
+        self.next_node = self.next_node.max(id.0);
+    }
+
+    pub fn reserve_edge(&mut self, id: EdgeId) {
+        self.next_edge = self.next_edge.max(id.0);
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn edge_count(&self) -> usize {
+        self.edges.len()
+    }
+
+    pub fn insert_node(&mut self, id: NodeId, egui: NodeIndex<DefaultIx>, sim: NodeIndex<DefaultIx>) {
+        self.nodes.insert(id, (egui, sim));
+    }
+
+    pub fn insert_edge(&mut self, id: EdgeId, egui: EdgeIndex<DefaultIx>, sim: EdgeIndex<DefaultIx>) {
+        self.edges.insert(id, (egui, sim));
+    }
+
+    pub fn remove_node(&mut self, id: NodeId) {
+        self.nodes.remove(&id);
+    }
+
+    pub fn remove_edge(&mut self, id: EdgeId) {
+        self.edges.remove(&id);
+    }
+
+    pub fn egui_node(&self, id: NodeId) -> Option<NodeIndex<DefaultIx>> {
+        self.nodes.get(&id).map(|(egui, _)| *egui)
+    }
+
+    pub fn egui_edge(&self, id: EdgeId) -> Option<EdgeIndex<DefaultIx>> {
+        self.edges.get(&id).map(|(egui, _)| *egui)
+    }
+
+    pub fn sim_node(&self, id: NodeId) -> Option<NodeIndex<DefaultIx>> {
+        self.nodes.get(&id).map(|(_, sim)| *sim)
+    }
+
+    pub fn sim_edge(&self, id: EdgeId) -> Option<EdgeIndex<DefaultIx>> {
+        self.edges.get(&id).map(|(_, sim)| *sim)
+    }
+
+    /// Re-reads both graphs after they were rebuilt (reset, direction conversion, project load).
+    /// Ids travel inside the payloads, so only the indices change.
+    pub fn rebuild(&mut self, g: &AppGraph, sim: &ForceGraph<f32, 2, NodePayload, EdgePayload>) {
+        fn egui_indices<Ty: EdgeType>(g: &AppGraphOf<Ty>) -> (HashMap<NodeId, NodeIndex<DefaultIx>>, HashMap<EdgeId, EdgeIndex<DefaultIx>>) {
+            let nodes = g.g.node_indices().filter_map(|idx| Some((g.node(idx)?.payload().id, idx))).collect();
+            let edges = g.g.edge_indices().filter_map(|idx| Some((g.edge(idx)?.payload().id, idx))).collect();
+            (nodes, edges)
+        }
+        let (egui_nodes, egui_edges) = match g {
+            AppGraph::Directed(g) => egui_indices(g),
+            AppGraph::Undirected(g) => egui_indices(g),
+        };
+        self.nodes = sim.node_indices().filter_map(|sim_idx| {
+            let id = sim[sim_idx].0.id;
+            Some((id, (*egui_nodes.get(&id)?, sim_idx)))
+        }).collect();
+        self.edges = sim.edge_indices().filter_map(|sim_idx| {
+            let id = sim[sim_idx].id;
+            Some((id, (*egui_edges.get(&id)?, sim_idx)))
+        }).collect();
+        // Ids of imported or converted payloads must not be handed out again
+        self.next_node = self.next_node.max(self.nodes.keys().map(|id| id.0).max().unwrap_or(0));
+        self.next_edge = self.next_edge.max(self.edges.keys().map(|id| id.0).max().unwrap_or(0));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use petgraph::stable_graph::StableGraph;
+    use petgraph::Directed;
+    use super::*;
+
+    fn node(ids: &mut GraphIds, label: &str) -> NodePayload {
+        NodePayload { id: ids.new_node_id(), label: label.to_owned(), ..Default::default() }
+    }
+
+    #[test]
+    fn ids_start_after_the_unassigned_default() {
+        let mut ids = GraphIds::default();
+        assert_eq!(ids.new_node_id(), NodeId(1));
+        assert_eq!(ids.new_edge_id(), EdgeId(1));
+        assert_ne!(ids.new_node_id(), NodeId::default());
+    }
+
+    #[test]
+    fn rebuild_maps_each_id_to_its_own_index_in_both_graphs() {
+        let mut ids = GraphIds::default();
+        let payloads: Vec<NodePayload> = ["a", "b", "c"].iter().map(|label| node(&mut ids, label)).collect();
+        let edge = EdgePayload { id: ids.new_edge_id(), ..Default::default() };
+
+        // The egui graph has a hole where a node was removed, the simulation holds the nodes in reverse
+        let mut egui_pet = StableGraph::<NodePayload, EdgePayload, Directed>::new();
+        let removed = egui_pet.add_node(NodePayload::default());
+        let egui_idx: Vec<_> = payloads.iter().map(|p| egui_pet.add_node(p.clone())).collect();
+        egui_pet.remove_node(removed);
+        let egui_edge = egui_pet.add_edge(egui_idx[0], egui_idx[2], edge.clone());
+        let mut sim_pet = StableGraph::<NodePayload, EdgePayload, Directed>::new();
+        let sim_idx: Vec<_> = payloads.iter().rev().map(|p| sim_pet.add_node(p.clone())).collect();
+        let sim_edge = sim_pet.add_edge(sim_idx[2], sim_idx[0], edge.clone());
+
+        let g = AppGraph::Directed(AppGraphOf::<Directed>::from(&egui_pet));
+        let sim = fdg::init_force_graph_uniform(sim_pet, 100.0);
+        ids.rebuild(&g, &sim);
+
+        assert_eq!(ids.node_count(), 3);
+        assert_eq!(ids.edge_count(), 1);
+        for payload in &payloads {
+            let egui = ids.egui_node(payload.id).unwrap();
+            let sim_node = ids.sim_node(payload.id).unwrap();
+            assert_eq!(g.node_payload(egui).unwrap().label, payload.label);
+            assert_eq!(sim[sim_node].0.label, payload.label);
+        }
+        assert_ne!(ids.egui_node(payloads[0].id), ids.sim_node(payloads[0].id));
+        assert_eq!(ids.egui_edge(edge.id), Some(egui_edge));
+        assert_eq!(ids.sim_edge(edge.id), Some(sim_edge));
+    }
+
+    #[test]
+    fn rebuild_keeps_imported_ids_from_being_handed_out_again() {
+        let mut ids = GraphIds::default();
+        let mut pet = StableGraph::<NodePayload, EdgePayload, Directed>::new();
+        let a = pet.add_node(NodePayload { id: NodeId(40), ..Default::default() });
+        let b = pet.add_node(NodePayload { id: NodeId(7), ..Default::default() });
+        pet.add_edge(a, b, EdgePayload { id: EdgeId(12), ..Default::default() });
+
+        let g = AppGraph::Directed(AppGraphOf::<Directed>::from(&pet));
+        let sim = fdg::init_force_graph_uniform(pet, 100.0);
+        ids.rebuild(&g, &sim);
+
+        assert_eq!(ids.new_node_id(), NodeId(41));
+        assert_eq!(ids.new_edge_id(), EdgeId(13));
+    }
+
+    #[test]
+    fn removed_ids_no_longer_resolve() {
+        let mut ids = GraphIds::default();
+        let id = ids.new_node_id();
+        ids.insert_node(id, NodeIndex::new(3), NodeIndex::new(5));
+        assert_eq!(ids.egui_node(id), Some(NodeIndex::new(3)));
+        assert_eq!(ids.sim_node(id), Some(NodeIndex::new(5)));
+        ids.remove_node(id);
+        assert_eq!(ids.egui_node(id), None);
+        assert_eq!(ids.sim_node(id), None);
+    }
+}
@@ -0,0 +1,270 @@
+use egui::{Context, Key, KeyboardShortcut, Modifiers, Ui};
+use serde::{Deserialize, Serialize};
+use crate::app::BasicApp;
+use crate::i18n::tr;
+use crate::selection::SelectionTool;
+
+/// Key of the keymap in eframe storage.
+pub const KEYMAP_STORAGE_KEY: &str = "keymap";
+
+/// Id of the search bar's text field, so the focus shortcut can reach it.
+pub const SEARCH_FIELD_ID: &str = "search_query";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Action {
+    DeleteSelection,
+    SelectAll,
+    ClearSelection,
+    AddNode,
+    EdgeTool,
+    PointerTool,
+    FitToScreen,
+    ToggleSimulation,
+    RunLayout,
+    FocusSearch,
+    CheatSheet,
+}
+
+impl Action {
+    pub const ALL: [Action; 11] = [
+        Action::DeleteSelection,
+        Action::SelectAll,
+        Action::ClearSelection,
+        Action::AddNode,
+        Action::EdgeTool,
+        Action::PointerTool,
+        Action::FitToScreen,
+        Action::ToggleSimulation,
+        Action::RunLayout,
+        Action::FocusSearch,
+        Action::CheatSheet,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Action::DeleteSelection => tr("action.delete_selection"),
+            Action::SelectAll => tr("action.select_all"),
+            Action::ClearSelection => tr("action.clear_selection"),
+            Action::AddNode => tr("action.add_node"),
+            Action::EdgeTool => tr("action.edge_tool"),
+            Action::PointerTool => tr("action.pointer_tool"),
+            Action::FitToScreen => tr("action.fit_to_screen"),
+            Action::ToggleSimulation => tr("action.toggle_simulation"),
+            Action::RunLayout => tr("action.run_layout"),
+            Action::FocusSearch => tr("action.focus_search"),
+            Action::CheatSheet => tr("action.cheat_sheet"),
+        }
+    }
+
+    fn default_shortcut(&self) -> KeyboardShortcut {
+        let (modifiers, key) = match self {
+            Action::DeleteSelection => (Modifiers::NONE, Key::Delete),
+            Action::SelectAll => (Modifiers::COMMAND, Key::A),
+            Action::ClearSelection => (Modifiers::NONE, Key::Escape),
+            Action::AddNode => (Modifiers::NONE, Key::N),
+            Action::EdgeTool => (Modifiers::NONE, Key::E),
+            Action::PointerTool => (Modifiers::NONE, Key::V),
+            Action::FitToScreen => (Modifiers::NONE, Key::F),
+            Action::ToggleSimulation => (Modifiers::NONE, Key::Space),
+            Action::RunLayout => (Modifiers::NONE, Key::L),
+            Action::FocusSearch => (Modifiers::COMMAND, Key::F),
+            Action::CheatSheet => (Modifiers::NONE, Key::Questionmark),
+        };
+        KeyboardShortcut::new(modifiers, key)
+    }
+
+    // Shortcuts that still fire while a text field has keyboard focus
+    fn works_while_typing(&self) -> bool {
+        matches!(self, Action::FocusSearch)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Binding {
+    pub action: Action,
+    pub shortcut: Option<KeyboardShortcut>, // None: unbound
+}
+
+/// Remappable shortcuts plus the state of the keymap dialog and cheat sheet. Persisted through eframe storage.
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct Keymap {
+    bindings: Vec<Binding>,
+    #[serde(skip)]
+    pub show_editor: bool,
+    #[serde(skip)]
+    pub show_cheat_sheet: bool,
+    #[serde(skip)]
+    listening: Option<Action>, // Action waiting for its new key in the dialog
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Self {
+            bindings: Action::ALL.iter().map(|action| Binding { action: *action, shortcut: Some(action.default_shortcut()) }).collect(),
+            show_editor: false,
+            show_cheat_sheet: false,
+            listening: None,
+        }
+    }
+}
+
+impl Keymap {
+    /// Adds actions introduced after the keymap was saved, with their default keys.
+    pub fn with_missing_defaults(mut self) -> Self {
+        for action in Action::ALL {
+            if !self.bindings.iter().any(|b| b.action == action) {
+                self.bindings.push(Binding { action, shortcut: Some(action.default_shortcut()) });
+            }
+        }
+        self
+    }
+
+    pub fn shortcut(&self, action: Action) -> Option<KeyboardShortcut> {
+        self.bindings.iter().find(|b| b.action == action).and_then(|b| b.shortcut)
+    }
+
+    pub fn shortcut_text(&self, ctx: &Context, action: Action) -> String {
+        self.shortcut(action).map(|s| ctx.format_shortcut(&s)).unwrap_or_else(|| tr("keymap.unbound").to_owned())
+    }
+
+    // A key can only do one thing, so binding it takes it away from any other action
+    fn bind(&mut self, action: Action, shortcut: Option<KeyboardShortcut>) {
+        for binding in &mut self.bindings {
+            if binding.action == action {
+                binding.shortcut = shortcut;
+            } else if shortcut.is_some() && binding.shortcut == shortcut {
+                binding.shortcut = None;
+            }
+        }
+    }
+
+    /// Actions whose shortcut was pressed this frame. The keys are consumed.
+    fn pressed(&self, ctx: &Context) -> Vec<Action> {
+        if self.listening.is_some() {
+            return Vec::new();
+        }
+        let typing = ctx.wants_keyboard_input();
+        // Shortcuts with more modifiers go first, so Ctrl+F isn't taken by F
+        let mut bindings: Vec<(Action, KeyboardShortcut)> = self.bindings.iter().filter_map(|b| b.shortcut.map(|s| (b.action, s))).collect();
+        bindings.sort_by_key(|(_, s)| std::cmp::Reverse(modifier_count(s.modifiers)));
+        bindings
+            .into_iter()
+            .filter(|(action, _)| !typing || action.works_while_typing())
+            .filter(|(_, shortcut)| ctx.input_mut(|i| i.consume_shortcut(shortcut)))
+            .map(|(action, _)| action)
+            .collect()
+    }
+
+    fn capture(&mut self, ctx: &Context) {
+        let Some(action) = self.listening else { return };
+        let pressed = ctx.input(|i| {
+            i.events.iter().find_map(|event| match event {
+                egui::Event::Key { key, pressed: true, modifiers, .. } => Some(KeyboardShortcut::new(*modifiers, *key)),
+                _ => None,
+            })
+        });
+        match pressed {
+            // Escape cancels instead of being bound
+            Some(shortcut) if shortcut.logical_key == Key::Escape && shortcut.modifiers.is_none() => self.listening = None,
+            Some(shortcut) => {
+                self.bind(action, Some(shortcut));
+                self.listening = None;
+            }
+            None => {}
+        }
+    }
+}
+
+fn modifier_count(modifiers: Modifiers) -> usize {
+    [modifiers.alt, modifiers.shift, modifiers.command || modifiers.ctrl].iter().filter(|m| **m).count()
+}
+
+/// Runs the actions of this frame's shortcuts. Called from the canvas, which knows where the pointer is.
+pub fn handle_shortcuts(app: &mut BasicApp, ui: &Ui) {
+    for action in app.keymap.pressed(ui.ctx()) {
+        match action {
+            Action::DeleteSelection => app.delete_selection(),
+            Action::SelectAll => app.select_all(),
+            Action::ClearSelection => {
+                app.edge_draft_source = None;
+                app.clear_selection();
+            }
+            Action::AddNode => {
+                let pointer = ui.ctx().pointer_hover_pos().filter(|p| ui.max_rect().contains(*p));
+                if let (false, Some(pointer)) = (app.view_3d, pointer) {
+                    app.add_node_at(egui_graphs::Metadata::load(ui).screen_to_canvas_pos(pointer));
+                }
+            }
+            Action::EdgeTool => {
+                app.selection.tool = if app.selection.tool == SelectionTool::Edge { SelectionTool::Pointer } else { SelectionTool::Edge };
+            }
+            Action::PointerTool => app.selection.tool = SelectionTool::Pointer,
+            Action::FitToScreen => app.zoom_to_fit(),
+            Action::ToggleSimulation => {
+                app.simulation_stopped = !app.simulation_stopped;
+                app.wake_simulation();
+            }
+            Action::RunLayout => app.run_layout(),
+            Action::FocusSearch => ui.ctx().memory_mut(|m| m.request_focus(egui::Id::new(SEARCH_FIELD_ID))),
+            Action::CheatSheet => app.keymap.show_cheat_sheet = !app.keymap.show_cheat_sheet,
+        }
+    }
+}
+
+/// The remapping dialog.
+pub fn draw_keymap_editor(keymap: &mut Keymap, ctx: &Context) {
+    keymap.capture(ctx);
+    let mut open = keymap.show_editor;
+    egui::Window::new(tr("keymap.title")).open(&mut open).resizable(false).show(ctx, |ui| {
+        ui.label(tr("keymap.hint"));
+        egui::Grid::new("keymap_grid").num_columns(3).striped(true).show(ui, |ui| {
+            for action in Action::ALL {
+                ui.label(action.label());
+                let text = if keymap.listening == Some(action) { tr("keymap.press_key").to_owned() } else { keymap.shortcut_text(ctx, action) };
+                if ui.button(text).clicked() {
+                    keymap.listening = Some(action);
+                }
+                if ui.button(tr("keymap.clear")).clicked() {
+                    keymap.bind(action, None);
+                }
+                ui.end_row();
+            }
+        });
+        if ui.button(tr("keymap.reset")).clicked() {
+            keymap.bindings = Keymap::default().bindings;
+        }
+    });
+    keymap.show_editor = open;
+    if !open {
+        keymap.listening = None;
+    }
+}
+
+/// Overlay listing every shortcut, toggled with `?`.
+pub fn draw_cheat_sheet(keymap: &mut Keymap, ctx: &Context) {
+    egui::Area::new(egui::Id::new("shortcut_cheat_sheet"))
+        .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+        .order(egui::Order::Foreground)
+        .show(ctx, |ui| {
+            egui::Frame::popup(ui.style()).show(ui, |ui| {
+                ui.heading(tr("keymap.cheat_sheet"));
+                egui::Grid::new("cheat_sheet_grid").num_columns(2).spacing([24.0, 4.0]).show(ui, |ui| {
+                    for action in Action::ALL {
+                        ui.monospace(keymap.shortcut_text(ctx, action));
+                        ui.label(action.label());
+                        ui.end_row();
+                    }
+                });
+                ui.horizontal(|ui| {
+                    if ui.button(tr("keymap.edit")).clicked() {
+                        keymap.show_editor = true;
+                        keymap.show_cheat_sheet = false;
+                    }
+                    if ui.button(tr("keymap.close")).clicked() {
+                        keymap.show_cheat_sheet = false;
+                    }
+                });
+            });
+        });
+}
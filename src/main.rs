@@ -2,6 +2,27 @@
 mod app;
 mod settings_panel;
 mod graph_view;
+mod physics;
+mod convergence;
+mod constraints;
+mod overlap;
+mod view3d;
+mod shapes;
+mod visual_mapping;
+mod theme;
+mod fonts;
+mod i18n;
+mod search;
+mod selection;
+mod keymap;
+mod clipboard;
+mod minimap;
+mod groups;
+mod focus;
+mod bulk_edit;
+mod attributes;
+mod ids;
+mod project;
 
 // Use the app structure from the app module
 use app::BasicApp;
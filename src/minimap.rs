@@ -0,0 +1,97 @@
+use egui::{Color32, Pos2, Rect, Sense, Stroke, Ui, Vec2};
+use egui_graphs::Metadata;
+use crate::app::BasicApp;
+
+const MINIMAP_SIZE: Vec2 = Vec2::new(200.0, 140.0);
+const MINIMAP_MARGIN: f32 = 10.0; // Distance from the canvas corner
+const MINIMAP_PADDING: f32 = 6.0; // Inside the frame, so nodes on the border stay visible
+
+/// Overview of the whole graph in the bottom-right corner of the canvas.
+pub struct Minimap {
+    pub enabled: bool,
+}
+
+impl Default for Minimap {
+    fn default() -> Self {
+        Self { enabled: true }
+    }
+}
+
+// Maps between canvas coordinates and the minimap's screen rectangle
+struct MinimapTransform {
+    origin: Pos2, // Canvas point drawn at `inner.min`
+    scale: f32,
+    inner: Rect,
+}
+
+impl MinimapTransform {
+    fn new(world: Rect, inner: Rect) -> Self {
+        let scale = (inner.width() / world.width().max(1.0)).min(inner.height() / world.height().max(1.0));
+        // Centred on the minimap when the aspect ratios differ
+        let used = world.size() * scale;
+        let offset = (inner.size() - used) * 0.5;
+        Self { origin: world.min - offset / scale, scale, inner }
+    }
+
+    fn to_minimap(&self, canvas: Pos2) -> Pos2 {
+        self.inner.min + (canvas - self.origin) * self.scale
+    }
+
+    fn to_canvas(&self, minimap: Pos2) -> Pos2 {
+        self.origin + (minimap - self.inner.min) / self.scale
+    }
+}
+
+/// Draws the minimap over the canvas `viewport` and handles click-to-jump and viewport dragging.
+/// Returns true when it moved the view, so fit-to-screen can be turned off.
+pub fn draw_minimap(app: &BasicApp, ui: &Ui, viewport: Rect) -> bool {
+    let mut meta = Metadata::load(ui);
+    let visible = Rect::from_two_pos(meta.screen_to_canvas_pos(viewport.min), meta.screen_to_canvas_pos(viewport.max));
+
+    let nodes: Vec<_> = app
+        .g
+        .node_indices()
+        .into_iter()
+        .filter_map(|idx| {
+            let payload = app.g.node_payload(idx)?;
+            (!payload.style.hidden).then_some((idx, app.g.node_location(idx)?))
+        })
+        .collect();
+    let world = nodes.iter().fold(visible, |rect, (_, p)| rect.union(Rect::from_min_max(*p, *p)));
+
+    let frame = Rect::from_min_size(viewport.max - MINIMAP_SIZE - Vec2::splat(MINIMAP_MARGIN), MINIMAP_SIZE);
+    let transform = MinimapTransform::new(world, frame.shrink(MINIMAP_PADDING));
+    let theme = app.theme.active();
+    let painter = ui.painter_at(frame);
+    painter.rect_filled(frame, 4.0, theme.background.gamma_multiply(0.9));
+    painter.rect_stroke(frame, 4.0, Stroke::new(1.0, theme.edge_color), egui::StrokeKind::Inside);
+
+    let selected = app.g.selected_nodes();
+    let match_color = ui.visuals().warn_fg_color;
+    for (idx, location) in &nodes {
+        let (color, radius) = if selected.contains(idx) {
+            (theme.selection_stroke, 2.5)
+        } else if app.search.is_active() && app.search.matches.contains(idx) {
+            (match_color, 2.5)
+        } else {
+            (theme.node_color, 1.5)
+        };
+        painter.circle_filled(transform.to_minimap(*location), radius, color);
+    }
+    let view_rect = Rect::from_two_pos(transform.to_minimap(visible.min), transform.to_minimap(visible.max));
+    painter.rect(view_rect, 0.0, Color32::TRANSPARENT, Stroke::new(1.5, theme.selection_stroke), egui::StrokeKind::Outside);
+
+    let response = ui.interact(frame, ui.id().with("minimap"), Sense::click_and_drag());
+    let target = if response.dragged() {
+        // Dragging moves the viewport rectangle along with the pointer
+        Some(visible.center() + response.drag_delta() / transform.scale)
+    } else if response.clicked() {
+        response.interact_pointer_pos().map(|pos| transform.to_canvas(pos))
+    } else {
+        None
+    };
+    let Some(target) = target else { return false };
+    meta.pan = viewport.center().to_vec2() - target.to_vec2() * meta.zoom;
+    meta.save(ui);
+    true
+}
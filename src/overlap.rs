@@ -0,0 +1,160 @@
+use std::collections::HashMap;
+use egui::{Context, FontId, Pos2, Rect, Vec2};
+use fdg::ForceGraph;
+use petgraph::stable_graph::{DefaultIx, NodeIndex};
+use crate::app::{NodePayload, EdgePayload};
+
+/// Scan-line overlap removal.
+///
+/// Every node is treated as a box covering its circle and the label drawn above it. Each pass
+/// sweeps the boxes as they were at its start, left to right; each overlapping pair is separated
+/// along the axis with the smaller overlap, which is the minimal displacement that resolves it.
+/// Pinned nodes stay put and their partner takes the whole push; two pinned nodes are left alone.
+pub struct OverlapRemoval {
+    pub continuous: bool,
+    pub padding: f32,
+    pub max_passes: usize,
+    pub node_radius: f32,
+    pub zoom: f32, // Canvas zoom of the last drawn frame, set by the graph view
+    boxes: HashMap<NodeIndex<DefaultIx>, Rect>, // Relative to the node position, canvas units
+}
+
+impl Default for OverlapRemoval {
+    fn default() -> Self {
+        Self {
+            continuous: false,
+            padding: 4.0,
+            max_passes: 50,
+            node_radius: 5.0,
+            zoom: 1.0,
+            boxes: HashMap::new(),
+        }
+    }
+}
+
+impl OverlapRemoval {
+    // Label extents depend on the loaded fonts, so they are measured through the egui context.
+    // Labels are laid out on screen at `label_size * zoom` pixels, like the node shapes do,
+    // and brought back to canvas units, where the simulation positions live.
+    pub fn measure(&mut self, ctx: &Context, sim: &ForceGraph<f32, 2, NodePayload, EdgePayload>, with_labels: bool, label_size: f32) {
+        self.boxes.clear();
+        let zoom = self.zoom.max(f32::EPSILON);
+        let font_id = FontId::proportional(label_size * zoom);
+        let circle = Rect::from_center_size(Pos2::ZERO, Vec2::splat(self.node_radius * 2.0));
+        for idx in sim.node_indices() {
+            let area = if with_labels {
+                let label = &sim[idx].0.label;
+                let text = ctx.fonts(|f| f.layout_no_wrap(label.clone(), font_id.clone(), egui::Color32::WHITE).size()) / zoom;
+                // The label sits centred on top of the node
+                let label = Rect::from_min_size(Pos2::new(-text.x * 0.5, circle.top() - text.y), text);
+                circle.union(label)
+            } else {
+                circle
+            };
+            self.boxes.insert(idx, area.expand(self.padding * 0.5));
+        }
+    }
+
+    fn rect_of(&self, sim: &ForceGraph<f32, 2, NodePayload, EdgePayload>, idx: NodeIndex<DefaultIx>) -> Rect {
+        let area = self.boxes.get(&idx).copied().unwrap_or(Rect::from_center_size(Pos2::ZERO, Vec2::splat(self.node_radius * 2.0)));
+        let p = sim[idx].1.coords;
+        area.translate(Vec2::new(p.x, p.y))
+    }
+
+    /// Runs up to `passes` sweeps and returns the number of overlaps found in the last one.
+    pub fn apply(&self, sim: &mut ForceGraph<f32, 2, NodePayload, EdgePayload>, passes: usize) -> usize {
+        let mut overlaps = 0;
+        for _ in 0..passes {
+            overlaps = self.sweep(sim);
+            if overlaps == 0 {
+                break;
+            }
+        }
+        overlaps
+    }
+
+    // Pushes are applied to the simulation but the scan works on a copy of the boxes taken at the
+    // start, so its ordering stays valid; overlaps caused by the pushes are left to the next pass.
+    fn sweep(&self, sim: &mut ForceGraph<f32, 2, NodePayload, EdgePayload>) -> usize {
+        let mut rects: Vec<(NodeIndex<DefaultIx>, Rect)> = sim.node_indices().map(|idx| (idx, self.rect_of(sim, idx))).collect();
+        rects.sort_by(|(_, a), (_, b)| a.left().total_cmp(&b.left()));
+
+        let mut overlaps = 0;
+        for i in 0..rects.len() {
+            for j in (i + 1)..rects.len() {
+                let ((a, ra), (b, rb)) = (rects[i], rects[j]);
+                // The scan line: once b starts right of a, nothing further right can touch a
+                if rb.left() >= ra.right() {
+                    break;
+                }
+                let overlap = ra.intersect(rb);
+                if overlap.width() <= 0.0 || overlap.height() <= 0.0 {
+                    continue;
+                }
+                let (share_a, share_b) = match (sim[a].0.pinned, sim[b].0.pinned) {
+                    (true, true) => continue, // Neither can move, so it is not an overlap this can fix
+                    (true, false) => (0.0, 1.0),
+                    (false, true) => (1.0, 0.0),
+                    (false, false) => (0.5, 0.5),
+                };
+                overlaps += 1;
+                let delta = rb.center() - ra.center();
+                let push = if overlap.width() < overlap.height() {
+                    Vec2::new(overlap.width() * if delta.x >= 0.0 { 1.0 } else { -1.0 }, 0.0)
+                } else {
+                    Vec2::new(0.0, overlap.height() * if delta.y >= 0.0 { 1.0 } else { -1.0 })
+                };
+                sim[a].1.coords.x -= push.x * share_a;
+                sim[a].1.coords.y -= push.y * share_a;
+                sim[b].1.coords.x += push.x * share_b;
+                sim[b].1.coords.y += push.y * share_b;
+            }
+        }
+        overlaps
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use petgraph::stable_graph::StableGraph;
+    use petgraph::Directed;
+    use super::*;
+
+    // Nodes at the given positions, boxes without labels
+    fn sim_at(nodes: &[(f32, f32, bool)]) -> ForceGraph<f32, 2, NodePayload, EdgePayload> {
+        let mut pet = StableGraph::<NodePayload, EdgePayload, Directed>::new();
+        for (_, _, pinned) in nodes {
+            pet.add_node(NodePayload { pinned: *pinned, ..Default::default() });
+        }
+        let mut sim = fdg::init_force_graph_uniform(pet, 100.0);
+        for (idx, (x, y, _)) in sim.node_indices().collect::<Vec<_>>().into_iter().zip(nodes) {
+            sim[idx].1.coords.x = *x;
+            sim[idx].1.coords.y = *y;
+        }
+        sim
+    }
+
+    #[test]
+    fn overlapping_pinned_nodes_are_neither_moved_nor_counted() {
+        let mut sim = sim_at(&[(0.0, 0.0, true), (2.0, 0.0, true)]);
+        let removal = OverlapRemoval::default();
+        assert_eq!(removal.apply(&mut sim, 1), 0);
+        let first = sim.node_indices().next().unwrap();
+        assert_eq!(sim[first].1.coords.x, 0.0);
+    }
+
+    #[test]
+    fn a_crowded_cluster_ends_up_without_overlaps() {
+        let nodes: Vec<(f32, f32, bool)> = (0..12).map(|i| ((i % 4) as f32 * 3.0, (i / 4) as f32 * 2.0, i == 5)).collect();
+        let mut sim = sim_at(&nodes);
+        let removal = OverlapRemoval::default();
+        removal.apply(&mut sim, 200);
+        let indices: Vec<_> = sim.node_indices().collect();
+        for (i, a) in indices.iter().enumerate() {
+            for b in &indices[i + 1..] {
+                let overlap = removal.rect_of(&sim, *a).intersect(removal.rect_of(&sim, *b));
+                assert!(overlap.width() <= 1e-3 || overlap.height() <= 1e-3);
+            }
+        }
+    }
+}
@@ -0,0 +1,198 @@
+use std::collections::HashMap;
+use fdg::{Force, ForceGraph};
+use fdg::fruchterman_reingold::FruchtermanReingoldConfiguration;
+use fdg::nalgebra::SVector;
+use petgraph::stable_graph::{DefaultIx, NodeIndex};
+use crate::app::{NodePayload, EdgePayload};
+use crate::i18n::tr;
+
+// Weights are clamped to this before mapping so that log/inverse never blow up on 0.
+const MIN_WEIGHT: f32 = 0.01;
+// Lower bound for distances between nodes, avoids division by zero when two nodes overlap.
+const MIN_DISTANCE: f32 = 0.01;
+
+/// How a raw `weight` value is turned into a physical factor.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WeightMapping {
+    Linear,
+    Log,
+    Inverse,
+}
+
+impl WeightMapping {
+    pub const ALL: [WeightMapping; 3] = [WeightMapping::Linear, WeightMapping::Log, WeightMapping::Inverse];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            WeightMapping::Linear => tr("weight_mapping.linear"),
+            WeightMapping::Log => tr("weight_mapping.log"),
+            WeightMapping::Inverse => tr("weight_mapping.inverse"),
+        }
+    }
+
+    pub fn map(&self, weight: f32) -> f32 {
+        let w = weight.max(MIN_WEIGHT);
+        match self {
+            WeightMapping::Linear => w,
+            // ln(1 + w) keeps the factor positive for weights below 1
+            WeightMapping::Log => (1.0 + w).ln(),
+            WeightMapping::Inverse => 1.0 / w,
+        }
+    }
+}
+
+/// What `EdgePayload::weight` controls in the layout.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EdgeWeightEffect {
+    None,
+    SpringStrength,
+    IdealLength,
+}
+
+impl EdgeWeightEffect {
+    pub const ALL: [EdgeWeightEffect; 3] = [EdgeWeightEffect::None, EdgeWeightEffect::SpringStrength, EdgeWeightEffect::IdealLength];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            EdgeWeightEffect::None => tr("common.none"),
+            EdgeWeightEffect::SpringStrength => tr("edge_effect.spring_strength"),
+            EdgeWeightEffect::IdealLength => tr("edge_effect.ideal_length"),
+        }
+    }
+}
+
+/// What `NodePayload::weight` controls in the layout.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NodeWeightEffect {
+    None,
+    Mass,
+    Charge,
+}
+
+impl NodeWeightEffect {
+    pub const ALL: [NodeWeightEffect; 3] = [NodeWeightEffect::None, NodeWeightEffect::Mass, NodeWeightEffect::Charge];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            NodeWeightEffect::None => tr("common.none"),
+            NodeWeightEffect::Mass => tr("node_effect.mass"),
+            NodeWeightEffect::Charge => tr("node_effect.charge"),
+        }
+    }
+}
+
+/// Fruchterman-Reingold with node and edge weights taken into account.
+///
+/// Behaves exactly like `fdg::fruchterman_reingold::FruchtermanReingold` when both
+/// effects are `None` and no node is pinned. Weights are read from the simulation
+/// payloads on every step, so changing them through `update_fdg_node_payload`/
+/// `update_fdg_edge_payload` takes effect on the next frame without rebuilding anything.
+pub struct WeightedFruchtermanReingold<const D: usize> {
+    pub conf: FruchtermanReingoldConfiguration<f32>,
+    pub velocities: HashMap<NodeIndex<DefaultIx>, SVector<f32, D>>,
+    pub edge_effect: EdgeWeightEffect,
+    pub edge_mapping: WeightMapping,
+    pub node_effect: NodeWeightEffect,
+    pub node_mapping: WeightMapping,
+    pub last_energy: f32, // Kinetic energy (sum of m*v^2/2) after the last step
+    pub last_mean_displacement: f32, // Average distance a free node moved in the last step
+}
+
+impl<const D: usize> WeightedFruchtermanReingold<D> {
+    pub fn new(dt: f32, cooloff_factor: f32, scale: f32) -> Self {
+        Self {
+            conf: FruchtermanReingoldConfiguration { dt, cooloff_factor, scale },
+            velocities: HashMap::default(),
+            edge_effect: EdgeWeightEffect::None,
+            edge_mapping: WeightMapping::Linear,
+            node_effect: NodeWeightEffect::None,
+            node_mapping: WeightMapping::Linear,
+            last_energy: 0.0,
+            last_mean_displacement: 0.0,
+        }
+    }
+
+    fn node_mass(&self, payload: &NodePayload) -> f32 {
+        match self.node_effect {
+            NodeWeightEffect::Mass => self.node_mapping.map(payload.weight),
+            _ => 1.0,
+        }
+    }
+
+    fn node_charge(&self, payload: &NodePayload) -> f32 {
+        match self.node_effect {
+            NodeWeightEffect::Charge => self.node_mapping.map(payload.weight),
+            _ => 1.0,
+        }
+    }
+}
+
+impl<const D: usize> Force<f32, D, NodePayload, EdgePayload> for WeightedFruchtermanReingold<D> {
+    fn apply(&mut self, graph: &mut ForceGraph<f32, D, NodePayload, EdgePayload>) {
+        let k = self.conf.scale;
+
+        let nodes: Vec<(NodeIndex<DefaultIx>, SVector<f32, D>, f32)> = graph
+            .node_indices()
+            .map(|idx| {
+                let (payload, pos) = &graph[idx];
+                (idx, pos.coords, self.node_charge(payload))
+            })
+            .collect();
+        let slot_of: HashMap<NodeIndex<DefaultIx>, usize> = nodes.iter().enumerate().map(|(i, (idx, _, _))| (*idx, i)).collect();
+        let mut forces = vec![SVector::<f32, D>::zeros(); nodes.len()];
+
+        // Repulsion between every pair: k^2 / d, scaled by both charges
+        for i in 0..nodes.len() {
+            for j in (i + 1)..nodes.len() {
+                let delta = nodes[i].1 - nodes[j].1;
+                let dist = delta.norm().max(MIN_DISTANCE);
+                let magnitude = k * k / dist * nodes[i].2 * nodes[j].2;
+                let f = delta / dist * magnitude;
+                forces[i] += f;
+                forces[j] -= f;
+            }
+        }
+
+        // Attraction along edges: d^2 / k, adjusted by the edge weight.
+        // Direction is irrelevant for the layout, so both ends are pulled together.
+        for edge_idx in graph.edge_indices() {
+            let Some((a, b)) = graph.edge_endpoints(edge_idx) else { continue };
+            if a == b {
+                continue;
+            }
+            let (Some(&sa), Some(&sb)) = (slot_of.get(&a), slot_of.get(&b)) else { continue };
+            let factor = self.edge_mapping.map(graph[edge_idx].weight);
+            let delta = nodes[sb].1 - nodes[sa].1;
+            let dist = delta.norm().max(MIN_DISTANCE);
+            let magnitude = match self.edge_effect {
+                EdgeWeightEffect::None => dist * dist / k,
+                EdgeWeightEffect::SpringStrength => dist * dist / k * factor,
+                EdgeWeightEffect::IdealLength => dist * dist / (k * factor),
+            };
+            let f = delta / dist * magnitude;
+            forces[sa] += f;
+            forces[sb] -= f;
+        }
+
+        let mut energy = 0.0;
+        let mut displacement = 0.0;
+        let mut free_nodes = 0;
+        for (slot, (idx, _, _)) in nodes.iter().enumerate() {
+            // Pinned nodes still push and pull the others but never move themselves
+            if graph[*idx].0.pinned {
+                self.velocities.remove(idx);
+                continue;
+            }
+            let mass = self.node_mass(&graph[*idx].0);
+            let velocity = self.velocities.entry(*idx).or_insert_with(SVector::zeros);
+            *velocity = (*velocity + forces[slot] / mass * self.conf.dt) * self.conf.cooloff_factor;
+            let step = *velocity * self.conf.dt;
+            energy += 0.5 * mass * velocity.norm_squared();
+            displacement += step.norm();
+            free_nodes += 1;
+            graph[*idx].1.coords += step;
+        }
+        self.last_energy = energy;
+        self.last_mean_displacement = if free_nodes > 0 { displacement / free_nodes as f32 } else { 0.0 };
+    }
+}
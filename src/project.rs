@@ -0,0 +1,311 @@
+use std::collections::HashSet;
+use std::fs;
+use egui::Pos2;
+use serde::{Deserialize, Serialize};
+use crate::app::{BasicApp, EdgePayload, NodePayload};
+use crate::attributes::Attributes;
+use crate::groups::{Collapsed, HiddenEdge, HiddenNode, NodeGroup};
+use crate::i18n::{tr, trf};
+use crate::ids::{EdgeId, NodeId};
+use crate::shapes::{EdgeOverrides, NodeOverrides, NodeShapeKind};
+
+// Tags project files, so a style sheet or clipboard dump is rejected instead of misread
+const FORMAT_TAG: &str = "graph-project/1";
+
+#[derive(Serialize, Deserialize)]
+struct ProjectNode {
+    id: NodeId,
+    label: String,
+    weight: f32,
+    pinned: bool,
+    shape: Option<NodeShapeKind>,
+    icon_path: Option<String>,
+    position: [f32; 2], // Canvas coordinates, or the offset from the meta-node for a collapsed member
+    #[serde(default)]
+    overrides: NodeOverrides,
+    #[serde(default, skip_serializing_if = "Attributes::is_empty")]
+    attributes: Attributes,
+}
+
+impl ProjectNode {
+    fn new(payload: &NodePayload, position: Pos2) -> Self {
+        Self {
+            id: payload.id,
+            label: payload.label.clone(),
+            weight: payload.weight,
+            pinned: payload.pinned,
+            shape: payload.shape,
+            icon_path: payload.icon_path.clone(),
+            position: [position.x, position.y],
+            overrides: payload.overrides,
+            attributes: payload.attributes.clone(),
+        }
+    }
+
+    fn into_payload(self) -> (NodePayload, Pos2) {
+        let position = Pos2::new(self.position[0], self.position[1]);
+        let payload = NodePayload {
+            id: self.id,
+            label: self.label,
+            weight: self.weight,
+            pinned: self.pinned,
+            shape: self.shape,
+            icon_path: self.icon_path,
+            overrides: self.overrides,
+            attributes: self.attributes,
+            ..Default::default()
+        };
+        (payload, position)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct ProjectEdge {
+    id: EdgeId,
+    source: NodeId,
+    target: NodeId,
+    label: String,
+    weight: f32,
+    #[serde(default)]
+    overrides: EdgeOverrides,
+    #[serde(default, skip_serializing_if = "Attributes::is_empty")]
+    attributes: Attributes,
+}
+
+impl ProjectEdge {
+    fn new(source: NodeId, target: NodeId, payload: EdgePayload) -> Self {
+        Self {
+            id: payload.id,
+            source,
+            target,
+            label: payload.label,
+            weight: payload.weight,
+            overrides: payload.overrides,
+            attributes: payload.attributes,
+        }
+    }
+
+    fn into_payload(self) -> (NodeId, NodeId, EdgePayload) {
+        let payload = EdgePayload {
+            id: self.id,
+            label: self.label,
+            weight: self.weight,
+            overrides: self.overrides,
+            attributes: self.attributes,
+            ..Default::default()
+        };
+        (self.source, self.target, payload)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct ProjectCollapsed {
+    meta: NodeId,
+    nodes: Vec<ProjectNode>,
+    edges: Vec<ProjectEdge>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ProjectGroup {
+    name: String,
+    members: Vec<NodeId>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    collapsed: Option<ProjectCollapsed>,
+}
+
+impl ProjectGroup {
+    fn new(group: &NodeGroup) -> Self {
+        let collapsed = group.collapsed.as_ref().map(|c| ProjectCollapsed {
+            meta: c.meta,
+            nodes: c.nodes.iter().map(|n| ProjectNode::new(&n.payload, n.offset.to_pos2())).collect(),
+            edges: c.edges.iter().map(|e| ProjectEdge::new(e.source, e.target, e.payload.clone())).collect(),
+        });
+        Self { name: group.name.clone(), members: group.members.clone(), collapsed }
+    }
+
+    fn into_group(self) -> NodeGroup {
+        let collapsed = self.collapsed.map(|c| Collapsed {
+            meta: c.meta,
+            nodes: c
+                .nodes
+                .into_iter()
+                .map(|n| {
+                    let (payload, offset) = n.into_payload();
+                    HiddenNode { payload, offset: offset.to_vec2() }
+                })
+                .collect(),
+            edges: c
+                .edges
+                .into_iter()
+                .map(|e| {
+                    let (source, target, payload) = e.into_payload();
+                    HiddenEdge { source, target, payload }
+                })
+                .collect(),
+        });
+        NodeGroup { name: self.name, members: self.members, collapsed }
+    }
+}
+
+/// The whole graph as saved to disk. Ids are kept, so edges and groups refer to nodes by id.
+/// Collapsed groups carry the members and edges they hide, so they can be expanded after loading.
+#[derive(Serialize, Deserialize)]
+struct Project {
+    format: String,
+    directed: bool,
+    nodes: Vec<ProjectNode>,
+    edges: Vec<ProjectEdge>,
+    #[serde(default)]
+    groups: Vec<ProjectGroup>,
+}
+
+impl Project {
+    fn capture(app: &BasicApp) -> Self {
+        let nodes = app
+            .g
+            .node_indices()
+            .into_iter()
+            .filter_map(|idx| Some(ProjectNode::new(app.g.node_payload(idx)?, app.g.node_location(idx)?)))
+            .collect();
+        let edges = app
+            .g
+            .edges()
+            .into_iter()
+            .filter_map(|(source, target, payload)| Some(ProjectEdge::new(app.node_id(source)?, app.node_id(target)?, payload)))
+            .collect();
+        let groups = app.groups.groups.iter().map(ProjectGroup::new).collect();
+        Self { format: FORMAT_TAG.to_owned(), directed: app.is_directed, nodes, edges, groups }
+    }
+
+    fn hidden_nodes(&self) -> impl Iterator<Item = &ProjectNode> {
+        self.groups.iter().filter_map(|g| g.collapsed.as_ref()).flat_map(|c| &c.nodes)
+    }
+
+    fn hidden_edges(&self) -> impl Iterator<Item = &ProjectEdge> {
+        self.groups.iter().filter_map(|g| g.collapsed.as_ref()).flat_map(|c| &c.edges)
+    }
+
+    /// Every element, hidden ones included, needs its own id: edges and groups are wired up
+    /// through them. A collapsed group's meta-node must exist, or the group could never expand.
+    fn check_ids(&self) -> Result<(), String> {
+        let mut node_ids = HashSet::new();
+        let mut edge_ids = HashSet::new();
+        let nodes_ok = self.nodes.iter().chain(self.hidden_nodes()).all(|n| n.id != NodeId::default() && node_ids.insert(n.id));
+        let edges_ok = self.edges.iter().chain(self.hidden_edges()).all(|e| e.id != EdgeId::default() && edge_ids.insert(e.id));
+        let metas_ok = self.groups.iter().filter_map(|g| g.collapsed.as_ref()).all(|c| node_ids.contains(&c.meta));
+        if nodes_ok && edges_ok && metas_ok {
+            Ok(())
+        } else {
+            Err(tr("project.bad_ids").to_owned())
+        }
+    }
+}
+
+/// Project file path and the outcome of the last save/load.
+#[derive(Default)]
+pub struct ProjectFile {
+    pub path: String,
+    pub status: Option<String>,
+}
+
+fn save(app: &BasicApp, path: &str) -> Result<(), String> {
+    let text = serde_json::to_string_pretty(&Project::capture(app)).map_err(|e| e.to_string())?;
+    fs::write(path, text).map_err(|e| e.to_string())
+}
+
+fn load(app: &mut BasicApp, path: &str) -> Result<(), String> {
+    let text = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let project: Project = serde_json::from_str(&text).map_err(|e| trf("project.invalid", &[&e]))?;
+    if project.format != FORMAT_TAG {
+        return Err(trf("project.invalid", &[&project.format]));
+    }
+    project.check_ids()?;
+    let hidden_nodes: Vec<NodeId> = project.hidden_nodes().map(|n| n.id).collect();
+    let hidden_edges: Vec<EdgeId> = project.hidden_edges().map(|e| e.id).collect();
+    let nodes = project.nodes.into_iter().map(ProjectNode::into_payload).collect();
+    let edges = project.edges.into_iter().map(ProjectEdge::into_payload).collect();
+    app.replace_graph(project.directed, nodes, edges);
+    // Hidden members come back with their ids when expanded, so those ids stay taken
+    for id in hidden_nodes {
+        app.ids.reserve_node(id);
+    }
+    for id in hidden_edges {
+        app.ids.reserve_edge(id);
+    }
+    app.groups.groups = project.groups.into_iter().map(ProjectGroup::into_group).collect();
+    Ok(())
+}
+
+pub fn save_project(app: &mut BasicApp) {
+    let path = app.project.path.trim().to_owned();
+    app.project.status = Some(match save(app, &path) {
+        Ok(()) => trf("project.saved", &[&path]),
+        Err(e) => trf("project.save_failed", &[&e]),
+    });
+}
+
+/// Replaces the current graph with the one in the project file. Nothing changes if it cannot be read.
+pub fn load_project(app: &mut BasicApp) {
+    let path = app.project.path.trim().to_owned();
+    app.project.status = Some(match load(app, &path) {
+        Ok(()) => trf("project.loaded", &[&path]),
+        Err(e) => trf("project.load_failed", &[&e]),
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pins_and_collapsed_groups_survive_save_and_load() {
+        let mut app = BasicApp::empty_for_tests();
+        let a = app.insert_node(NodePayload { label: "a".to_owned(), pinned: true, ..Default::default() }, Pos2::new(0.0, 0.0));
+        let b = app.insert_node(NodePayload { label: "b".to_owned(), ..Default::default() }, Pos2::new(40.0, 0.0));
+        let c = app.insert_node(NodePayload { label: "c".to_owned(), ..Default::default() }, Pos2::new(0.0, 40.0));
+        app.insert_edge(a, b, EdgePayload { label: "ab".to_owned(), weight: 2.0, ..Default::default() });
+        app.insert_edge(b, c, EdgePayload { label: "bc".to_owned(), weight: 3.0, ..Default::default() });
+        let (a_id, b_id) = (app.node_id(a).unwrap(), app.node_id(b).unwrap());
+        app.groups.groups.push(NodeGroup { name: "g".to_owned(), members: vec![a_id, b_id], collapsed: None });
+        app.collapse_group(0);
+
+        let path = std::env::temp_dir().join(format!("graph-project-test-{}.json", std::process::id()));
+        let path = path.to_str().unwrap();
+        save(&app, path).unwrap();
+        let mut loaded = BasicApp::empty_for_tests();
+        let result = load(&mut loaded, path);
+        let _ = fs::remove_file(path);
+        result.unwrap();
+
+        assert_eq!(loaded.ids.node_count(), 2); // The meta-node and `c`
+        assert!(loaded.groups.groups[0].collapsed.is_some());
+        assert_eq!(loaded.ids.egui_node(a_id), None);
+
+        loaded.expand_group(0);
+        let a = loaded.ids.egui_node(a_id).unwrap();
+        let b = loaded.ids.egui_node(b_id).unwrap();
+        assert!(loaded.g.node_payload(a).unwrap().pinned);
+        assert!(!loaded.g.node_payload(b).unwrap().pinned);
+        let ab = loaded.g.find_edge(a, b).unwrap();
+        assert_eq!(loaded.g.edge_payload(ab).unwrap().label, "ab");
+        let c = loaded.find_node_by_label("c").unwrap();
+        assert_eq!(loaded.g.edge_payload(loaded.g.find_edge(b, c).unwrap()).unwrap().weight, 3.0);
+    }
+
+    #[test]
+    fn repeated_ids_are_refused_without_touching_the_graph() {
+        let mut app = BasicApp::empty_for_tests();
+        let node = ProjectNode::new(&NodePayload { id: NodeId(1), label: "a".to_owned(), ..Default::default() }, Pos2::ZERO);
+        let twin = ProjectNode::new(&NodePayload { id: NodeId(1), label: "b".to_owned(), ..Default::default() }, Pos2::ZERO);
+        let project = Project { format: FORMAT_TAG.to_owned(), directed: true, nodes: vec![node, twin], edges: Vec::new(), groups: Vec::new() };
+        assert!(project.check_ids().is_err());
+
+        let path = std::env::temp_dir().join(format!("graph-project-bad-{}.json", std::process::id()));
+        let path = path.to_str().unwrap();
+        fs::write(path, serde_json::to_string(&project).unwrap()).unwrap();
+        let result = load(&mut app, path);
+        let _ = fs::remove_file(path);
+        assert!(result.is_err());
+        assert_eq!(app.ids.node_count(), 0);
+    }
+}
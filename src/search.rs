@@ -0,0 +1,247 @@
+use std::collections::HashSet;
+use egui::{Pos2, Rect};
+use petgraph::EdgeType;
+use petgraph::stable_graph::{DefaultIx, EdgeIndex, NodeIndex};
+use crate::app::{AppGraph, AppGraphOf, NodePayload};
+use crate::attributes::AttrValue;
+use crate::i18n::tr;
+use crate::shapes::NodeShapeKind;
+
+/// What happens to the elements that don't match.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FilterMode {
+    #[default]
+    Dim,
+    Hide,
+    Select, // Matches are selected, nothing is hidden
+}
+
+impl FilterMode {
+    pub const ALL: [FilterMode; 3] = [FilterMode::Dim, FilterMode::Hide, FilterMode::Select];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            FilterMode::Dim => tr("filter_mode.dim"),
+            FilterMode::Hide => tr("filter_mode.hide"),
+            FilterMode::Select => tr("filter_mode.select"),
+        }
+    }
+}
+
+/// How an attribute predicate tests the value stored under its key.
+#[derive(Clone, Debug, PartialEq)]
+pub enum AttrTest {
+    Present,
+    Equals(String), // Against the value's text form, so it works for every type
+    Contains(String),
+    Range { min: f64, max: f64 }, // Numbers and booleans (0/1)
+}
+
+impl AttrTest {
+    pub const TEMPLATES: [AttrTest; 4] = [
+        AttrTest::Present,
+        AttrTest::Equals(String::new()),
+        AttrTest::Contains(String::new()),
+        AttrTest::Range { min: 0.0, max: 10.0 },
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            AttrTest::Present => tr("attr_test.present"),
+            AttrTest::Equals(_) => tr("attr_test.equals"),
+            AttrTest::Contains(_) => tr("attr_test.contains"),
+            AttrTest::Range { .. } => tr("attr_test.range"),
+        }
+    }
+
+    fn matches(&self, value: Option<&AttrValue>) -> bool {
+        let Some(value) = value else { return false };
+        match self {
+            AttrTest::Present => true,
+            AttrTest::Equals(text) => value.to_string().eq_ignore_ascii_case(text.trim()),
+            AttrTest::Contains(text) => value.contains(text),
+            AttrTest::Range { min, max } => value.as_number().is_some_and(|v| (*min..=*max).contains(&v)),
+        }
+    }
+}
+
+/// One condition of the filter builder. A node matches when every predicate holds.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Predicate {
+    Weight { min: f32, max: f32 },
+    Degree { min: usize, max: usize },
+    Pinned(bool),
+    Shape(NodeShapeKind), // The shape the node is drawn with, after global defaults
+    Attribute { key: String, test: AttrTest },
+}
+
+impl Predicate {
+    // Templates offered by the "add condition" menu
+    pub const TEMPLATES: [Predicate; 5] = [
+        Predicate::Weight { min: 0.0, max: 10.0 },
+        Predicate::Degree { min: 1, max: 10 },
+        Predicate::Pinned(true),
+        Predicate::Shape(NodeShapeKind::Circle),
+        Predicate::Attribute { key: String::new(), test: AttrTest::Present },
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Predicate::Weight { .. } => tr("predicate.weight"),
+            Predicate::Degree { .. } => tr("predicate.degree"),
+            Predicate::Pinned(_) => tr("predicate.pinned"),
+            Predicate::Shape(_) => tr("predicate.shape"),
+            Predicate::Attribute { .. } => tr("predicate.attribute"),
+        }
+    }
+
+    fn matches(&self, payload: &NodePayload, degree: usize) -> bool {
+        match self {
+            Predicate::Weight { min, max } => (*min..=*max).contains(&payload.weight),
+            Predicate::Degree { min, max } => (*min..=*max).contains(&degree),
+            Predicate::Pinned(pinned) => payload.pinned == *pinned,
+            Predicate::Shape(shape) => payload.style.shape == *shape,
+            Predicate::Attribute { key, test } => test.matches(payload.attributes.get(key)),
+        }
+    }
+}
+
+/// Subsequence match of `query` in `text`, ignoring case and whitespace in the query.
+/// `None` when some query character is missing; otherwise higher is better.
+pub fn fuzzy_score(query: &str, text: &str) -> Option<i32> {
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+    let mut score = 0;
+    let mut next = 0;
+    let mut previous: Option<usize> = None;
+    for qc in query.to_lowercase().chars().filter(|c| !c.is_whitespace()) {
+        let pos = (next..text.len()).find(|&i| text[i] == qc)?;
+        score += 1;
+        if previous.is_some_and(|p| p + 1 == pos) {
+            score += 5; // Runs of consecutive characters
+        }
+        if pos == 0 {
+            score += 3;
+        }
+        next = pos + 1;
+        previous = Some(pos);
+    }
+    // Among equal matches the shorter label is the closer one, e.g. 节点734 before 节点7340
+    Some(score * 4 - text.len() as i32)
+}
+
+/// Search bar query plus filter builder, resolved into `NodeStyle`/`EdgeStyle` every frame.
+#[derive(Default)]
+pub struct SearchFilter {
+    pub query: String,
+    pub predicates: Vec<Predicate>,
+    pub mode: FilterMode,
+    pub matches: Vec<NodeIndex<DefaultIx>>, // Best match first
+    selected_matches: Vec<NodeIndex<DefaultIx>>, // What `Select` mode last selected
+}
+
+impl SearchFilter {
+    pub fn is_active(&self) -> bool {
+        !self.query.trim().is_empty() || !self.predicates.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.query.clear();
+        self.predicates.clear();
+        self.matches.clear();
+        self.selected_matches.clear();
+    }
+
+    pub fn apply(&mut self, g: &mut AppGraph) {
+        match g {
+            AppGraph::Directed(g) => self.apply_specific(g),
+            AppGraph::Undirected(g) => self.apply_specific(g),
+        }
+    }
+
+    fn apply_specific<Ty: EdgeType>(&mut self, g: &mut AppGraphOf<Ty>) {
+        let active = self.is_active();
+        let mut scored: Vec<(i32, NodeIndex<DefaultIx>)> = Vec::new();
+        if active {
+            let query = self.query.trim();
+            for idx in g.g.node_indices() {
+                let Some(node) = g.node(idx) else { continue };
+                let payload = node.payload();
+                let degree = g.g.neighbors_undirected(idx).count();
+                if !self.predicates.iter().all(|p| p.matches(payload, degree)) {
+                    continue;
+                }
+                let score = if query.is_empty() { Some(0) } else { fuzzy_score(query, &payload.label) };
+                if let Some(score) = score {
+                    scored.push((score, idx));
+                }
+            }
+        }
+        scored.sort_by(|a, b| b.0.cmp(&a.0));
+        self.matches = scored.into_iter().map(|(_, idx)| idx).collect();
+        let match_set: HashSet<NodeIndex<DefaultIx>> = self.matches.iter().copied().collect();
+
+        let styled = active && self.mode != FilterMode::Select;
+        let is_out = |idx: &NodeIndex<DefaultIx>| styled && !match_set.contains(idx);
+        let nodes: Vec<NodeIndex<DefaultIx>> = g.g.node_indices().collect();
+        for idx in &nodes {
+            let out = is_out(idx);
+            let Some(node) = g.node_mut(*idx) else { continue };
+            let style = &mut node.payload_mut().style;
+            style.dimmed = out && self.mode == FilterMode::Dim;
+            style.hidden = out && self.mode == FilterMode::Hide;
+        }
+        let edges: Vec<EdgeIndex<DefaultIx>> = g.g.edge_indices().collect();
+        for idx in edges {
+            let out = g.g.edge_endpoints(idx).is_some_and(|(a, b)| is_out(&a) || is_out(&b));
+            let Some(edge) = g.edge_mut(idx) else { continue };
+            let style = &mut edge.payload_mut().style;
+            style.dimmed = out && self.mode == FilterMode::Dim;
+            style.hidden = out && self.mode == FilterMode::Hide;
+        }
+
+        // Selection is only pushed when the result set changes, so clicks still work in between
+        if active && self.mode == FilterMode::Select && self.matches != self.selected_matches {
+            for idx in &nodes {
+                if let Some(node) = g.node_mut(*idx) {
+                    node.set_selected(match_set.contains(idx));
+                }
+            }
+            g.set_selected_nodes(self.matches.clone());
+            self.selected_matches = self.matches.clone();
+        }
+        if !active || self.mode != FilterMode::Select {
+            self.selected_matches.clear();
+        }
+    }
+
+    /// Canvas bounding box of the current matches.
+    pub fn matches_bounds(&self, g: &AppGraph) -> Option<Rect> {
+        let mut points = self.matches.iter().filter_map(|idx| g.node_location(*idx));
+        let first: Pos2 = points.next()?;
+        Some(points.fold(Rect::from_min_max(first, first), |rect, p| rect.union(Rect::from_min_max(p, p))))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fuzzy_score_needs_every_query_character_in_order() {
+        assert!(fuzzy_score("nd3", "Node 3").is_some());
+        assert!(fuzzy_score("3nd", "Node 3").is_none());
+        assert!(fuzzy_score("x", "Node 3").is_none());
+    }
+
+    #[test]
+    fn fuzzy_score_prefers_runs_prefixes_and_shorter_labels() {
+        assert!(fuzzy_score("nod", "node") > fuzzy_score("nod", "n-o-d"));
+        assert!(fuzzy_score("a", "ab") > fuzzy_score("a", "ba"));
+        assert!(fuzzy_score("节点734", "节点734") > fuzzy_score("节点734", "节点7340"));
+    }
+
+    #[test]
+    fn fuzzy_score_ignores_case_and_query_whitespace() {
+        assert_eq!(fuzzy_score("N O", "node"), fuzzy_score("no", "node"));
+    }
+}
@@ -0,0 +1,213 @@
+use std::collections::HashSet;
+use std::hash::Hash;
+use egui::{Color32, Modifiers, Painter, Pos2, Rect, Shape, Stroke};
+use egui_graphs::Metadata;
+use petgraph::EdgeType;
+use petgraph::stable_graph::{DefaultIx, EdgeIndex, NodeIndex};
+use crate::app::{AppGraph, AppGraphOf};
+use crate::i18n::tr;
+
+// Lasso points closer than this (in screen pixels) are merged while drawing
+const LASSO_MIN_STEP: f32 = 3.0;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SelectionTool {
+    #[default]
+    Pointer, // egui_graphs' own click selection, dragging and panning
+    Rectangle,
+    Lasso,
+    Edge, // Drag from a source node to a target node to connect them
+}
+
+impl SelectionTool {
+    pub const ALL: [SelectionTool; 4] = [SelectionTool::Pointer, SelectionTool::Rectangle, SelectionTool::Lasso, SelectionTool::Edge];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            SelectionTool::Pointer => tr("selection.pointer"),
+            SelectionTool::Rectangle => tr("selection.rectangle"),
+            SelectionTool::Lasso => tr("selection.lasso"),
+            SelectionTool::Edge => tr("selection.edge"),
+        }
+    }
+
+    pub fn hint(&self) -> &'static str {
+        match self {
+            SelectionTool::Edge => tr("selection.edge_hint"),
+            _ => tr("selection.hint"),
+        }
+    }
+
+    pub fn is_area(&self) -> bool {
+        matches!(self, SelectionTool::Rectangle | SelectionTool::Lasso)
+    }
+}
+
+/// How a finished rectangle/lasso combines with the existing selection.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SelectionOp {
+    Replace,
+    Add,       // Shift
+    Subtract,  // Ctrl
+    Intersect, // Shift + Ctrl
+}
+
+impl SelectionOp {
+    pub fn from_modifiers(modifiers: Modifiers) -> Self {
+        match (modifiers.shift, modifiers.command) {
+            (true, true) => SelectionOp::Intersect,
+            (true, false) => SelectionOp::Add,
+            (false, true) => SelectionOp::Subtract,
+            (false, false) => SelectionOp::Replace,
+        }
+    }
+
+    fn combine<T: Copy + Eq + Hash>(&self, current: &[T], hits: &HashSet<T>) -> Vec<T> {
+        let current_set: HashSet<T> = current.iter().copied().collect();
+        match self {
+            SelectionOp::Replace => hits.iter().copied().collect(),
+            SelectionOp::Add => current.iter().copied().chain(hits.iter().copied().filter(|h| !current_set.contains(h))).collect(),
+            SelectionOp::Subtract => current.iter().copied().filter(|c| !hits.contains(c)).collect(),
+            SelectionOp::Intersect => current.iter().copied().filter(|c| hits.contains(c)).collect(),
+        }
+    }
+}
+
+/// Rubber-band state of the canvas selection tools. Points are in screen coordinates.
+#[derive(Default)]
+pub struct CanvasSelection {
+    pub tool: SelectionTool,
+    pub include_edges: bool, // Also select edges with both endpoints inside
+    path: Vec<Pos2>,
+}
+
+impl CanvasSelection {
+    /// While a tool is active, egui_graphs must not pan or drag nodes with the same gesture.
+    pub fn captures_drag(&self) -> bool {
+        self.tool != SelectionTool::Pointer
+    }
+
+    pub fn begin(&mut self, pos: Pos2) {
+        self.path.clear();
+        self.path.push(pos);
+    }
+
+    pub fn extend(&mut self, pos: Pos2) {
+        match self.tool {
+            SelectionTool::Rectangle => {
+                self.path.truncate(1);
+                self.path.push(pos);
+            }
+            SelectionTool::Lasso => {
+                if self.path.last().is_none_or(|last| last.distance(pos) >= LASSO_MIN_STEP) {
+                    self.path.push(pos);
+                }
+            }
+            SelectionTool::Pointer | SelectionTool::Edge => {}
+        }
+    }
+
+    pub fn is_dragging(&self) -> bool {
+        !self.path.is_empty()
+    }
+
+    fn contains(&self, p: Pos2) -> bool {
+        match self.tool {
+            SelectionTool::Rectangle if self.path.len() == 2 => Rect::from_two_pos(self.path[0], self.path[1]).contains(p),
+            SelectionTool::Lasso if self.path.len() >= 3 => point_in_polygon(p, &self.path),
+            _ => false,
+        }
+    }
+
+    pub fn draw(&self, painter: &Painter, color: Color32) {
+        let stroke = Stroke::new(1.0, color);
+        let fill = color.gamma_multiply(0.1);
+        match self.tool {
+            SelectionTool::Rectangle if self.path.len() == 2 => {
+                let rect = Rect::from_two_pos(self.path[0], self.path[1]);
+                painter.rect_filled(rect, 0.0, fill);
+                painter.extend(Shape::dashed_line(&[rect.left_top(), rect.right_top(), rect.right_bottom(), rect.left_bottom(), rect.left_top()], stroke, 4.0, 3.0));
+            }
+            SelectionTool::Lasso if self.path.len() >= 2 => {
+                let mut closed = self.path.clone();
+                closed.push(self.path[0]);
+                painter.extend(Shape::dashed_line(&closed, stroke, 4.0, 3.0));
+            }
+            _ => {}
+        }
+    }
+
+    /// Ends the gesture and applies it to the graph's node (and optionally edge) selection.
+    pub fn finish(&mut self, g: &mut AppGraph, meta: &Metadata, op: SelectionOp) {
+        match g {
+            AppGraph::Directed(g) => self.finish_specific(g, meta, op),
+            AppGraph::Undirected(g) => self.finish_specific(g, meta, op),
+        }
+        self.path.clear();
+    }
+
+    fn finish_specific<Ty: EdgeType>(&self, g: &mut AppGraphOf<Ty>, meta: &Metadata, op: SelectionOp) {
+        let nodes: Vec<NodeIndex<DefaultIx>> = g.g.node_indices().collect();
+        let inside: HashSet<NodeIndex<DefaultIx>> = nodes
+            .iter()
+            .copied()
+            .filter(|idx| g.node(*idx).is_some_and(|n| !n.payload().style.hidden && self.contains(meta.canvas_to_screen_pos(n.location()))))
+            .collect();
+        let selected_nodes = op.combine(g.selected_nodes(), &inside);
+        let selected_set: HashSet<NodeIndex<DefaultIx>> = selected_nodes.iter().copied().collect();
+        for idx in &nodes {
+            if let Some(node) = g.node_mut(*idx) {
+                node.set_selected(selected_set.contains(idx));
+            }
+        }
+        g.set_selected_nodes(selected_nodes);
+
+        if !self.include_edges {
+            return;
+        }
+        let edges: Vec<EdgeIndex<DefaultIx>> = g.g.edge_indices().collect();
+        let edges_inside: HashSet<EdgeIndex<DefaultIx>> = edges
+            .iter()
+            .copied()
+            .filter(|idx| g.g.edge_endpoints(*idx).is_some_and(|(a, b)| inside.contains(&a) && inside.contains(&b)))
+            .collect();
+        let selected_edges = op.combine(g.selected_edges(), &edges_inside);
+        let selected_set: HashSet<EdgeIndex<DefaultIx>> = selected_edges.iter().copied().collect();
+        for idx in &edges {
+            if let Some(edge) = g.edge_mut(*idx) {
+                edge.set_selected(selected_set.contains(idx));
+            }
+        }
+        g.set_selected_edges(selected_edges);
+    }
+}
+
+// Even-odd rule, so self-intersecting lassos behave predictably
+fn point_in_polygon(p: Pos2, polygon: &[Pos2]) -> bool {
+    let mut inside = false;
+    let mut j = polygon.len() - 1;
+    for i in 0..polygon.len() {
+        let (a, b) = (polygon[i], polygon[j]);
+        if (a.y > p.y) != (b.y > p.y) && p.x < (b.x - a.x) * (p.y - a.y) / (b.y - a.y) + a.x {
+            inside = !inside;
+        }
+        j = i;
+    }
+    inside
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn point_in_polygon_uses_the_even_odd_rule() {
+        let square = [Pos2::new(0.0, 0.0), Pos2::new(10.0, 0.0), Pos2::new(10.0, 10.0), Pos2::new(0.0, 10.0)];
+        assert!(point_in_polygon(Pos2::new(5.0, 5.0), &square));
+        assert!(!point_in_polygon(Pos2::new(15.0, 5.0), &square));
+        // A bow tie crosses itself at the centre: its side lobes are inside, the gap below the crossing is not
+        let bow_tie = [Pos2::new(0.0, 0.0), Pos2::new(10.0, 10.0), Pos2::new(10.0, 0.0), Pos2::new(0.0, 10.0)];
+        assert!(point_in_polygon(Pos2::new(2.0, 5.0), &bow_tie));
+        assert!(!point_in_polygon(Pos2::new(5.0, 2.0), &bow_tie));
+    }
+}
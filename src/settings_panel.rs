@@ -1,11 +1,34 @@
 use egui::{Context, ScrollArea, Ui};
+use petgraph::stable_graph::{EdgeIndex, NodeIndex};
 use crate::app::BasicApp; // Assuming BasicApp is in app.rs
+use crate::physics::{EdgeWeightEffect, NodeWeightEffect, WeightMapping};
+use crate::convergence::draw_energy_plot;
+use crate::constraints::Axis;
+use crate::visual_mapping::{ColorRamp, EdgeAttribute, NodeAttribute};
+use crate::shapes::{ArrowStyle, DashStyle, EdgeLabelMode, EdgeRouting, LabelFont, NodeShapeKind};
+use crate::theme::ThemePreset;
+use crate::search::{AttrTest, Predicate};
+use crate::attributes::{self, AttrType, Schema};
+use crate::focus::FocusFade;
+use crate::i18n::{tr, trf, Language};
 
 pub fn draw_settings_panel(app: &mut BasicApp, ctx: &Context) {
     egui::SidePanel::right("config_panel")
         .min_width(250.0)
         .show(ctx, |ui| {
-            ui.heading("配置面板");
+            ui.heading(tr("panel.title"));
+            ui.horizontal(|ui| {
+                ui.label(tr("panel.language"));
+                let mut language = app.language;
+                egui::ComboBox::from_id_salt("language").selected_text(language.native_name()).show_ui(ui, |ui| {
+                    for option in Language::ALL {
+                        ui.selectable_value(&mut language, option, option.native_name());
+                    }
+                });
+                if language != app.language {
+                    app.set_language(ctx, language);
+                }
+            });
             ui.separator();
             
             ScrollArea::vertical().show(ui, |ui_scroll| {
@@ -13,12 +36,24 @@ pub fn draw_settings_panel(app: &mut BasicApp, ctx: &Context) {
                 ui_scroll.separator();
                 draw_style_settings(app, ui_scroll);
                 ui_scroll.separator();
+                draw_font_settings(app, ui_scroll);
+                ui_scroll.separator();
+                draw_visual_mapping_settings(app, ui_scroll);
+                ui_scroll.separator();
+                draw_filter_settings(app, ui_scroll);
+                ui_scroll.separator();
                 draw_navigation_settings(app, ui_scroll);
                 ui_scroll.separator();
                 draw_interaction_settings(app, ui_scroll);
                 ui_scroll.separator();
                 draw_simulation_settings(app, ui_scroll);
                 ui_scroll.separator();
+                draw_constraint_settings(app, ui_scroll);
+                ui_scroll.separator();
+                draw_group_settings(app, ui_scroll);
+                ui_scroll.separator();
+                draw_focus_settings(app, ui_scroll);
+                ui_scroll.separator();
                 draw_node_edge_management(app, ui_scroll);
                 ui_scroll.separator();
                 draw_debug_info(app, ui_scroll);
@@ -27,69 +62,472 @@ pub fn draw_settings_panel(app: &mut BasicApp, ctx: &Context) {
 }
 
 fn draw_graph_properties_settings(app: &mut BasicApp, ui: &mut Ui) {
-    ui.collapsing("图属性", |ui| {
-        if ui.checkbox(&mut app.is_directed, "有向图").changed() {
+    ui.collapsing(tr("graph.section"), |ui| {
+        if ui.checkbox(&mut app.is_directed, tr("graph.directed")).changed() {
             // Call the new conversion function instead of reset
             app.convert_graph_direction();
         }
         ui.horizontal(|ui| {
-            ui.label("节点数:");
+            ui.label(tr("graph.node_count"));
             if ui.add(egui::DragValue::new(&mut app.graph_nodes_count).speed(1.0).range(0..=1000)).changed() {
                 app.reset_graph_and_simulation();
             }
         });
         ui.horizontal(|ui| {
-            ui.label("边数:");
+            ui.label(tr("graph.edge_count"));
             if ui.add(egui::DragValue::new(&mut app.graph_edges_count).speed(1.0).range(0..=2000)).changed() {
                 app.reset_graph_and_simulation();
             }
         });
-        if ui.button("重置图").on_hover_text("使用当前节点和边数重新生成图").clicked() {
+        if ui.button(tr("graph.reset")).on_hover_text(tr("graph.reset_hint")).clicked() {
             app.reset_graph_and_simulation();
         }
+        ui.separator();
+        ui.horizontal(|ui| {
+            ui.label(tr("project.file"));
+            ui.text_edit_singleline(&mut app.project.path);
+        });
+        ui.horizontal(|ui| {
+            let has_path = !app.project.path.trim().is_empty();
+            if ui.add_enabled(has_path, egui::Button::new(tr("project.save"))).clicked() {
+                crate::project::save_project(app);
+            }
+            if ui.add_enabled(has_path, egui::Button::new(tr("project.load"))).on_hover_text(tr("project.load_hint")).clicked() {
+                crate::project::load_project(app);
+            }
+        });
+        if let Some(status) = &app.project.status {
+            ui.label(status);
+        }
     });
 }
 
 fn draw_style_settings(app: &mut BasicApp, ui: &mut Ui) {
-    ui.collapsing("样式设置", |ui| {
-        ui.checkbox(&mut app.style_labels_always, "总是显示标签");
+    ui.collapsing(tr("style.section"), |ui| {
+        draw_theme_settings(app, ui);
+        ui.separator();
+        ui.checkbox(&mut app.style_labels_always, tr("style.labels_always"));
+        ui.horizontal(|ui| {
+            ui.label(tr("style.node_shape"));
+            egui::ComboBox::from_id_salt("default_node_shape")
+                .selected_text(app.node_shapes.default_shape.label())
+                .show_ui(ui, |ui| {
+                    for shape in NodeShapeKind::ALL {
+                        ui.selectable_value(&mut app.node_shapes.default_shape, shape, shape.label());
+                    }
+                });
+        });
+        if app.node_shapes.default_shape == NodeShapeKind::Icon {
+            ui.horizontal(|ui| {
+                ui.label(tr("style.icon_file"));
+                ui.text_edit_singleline(&mut app.node_shapes.default_icon_path);
+                if ui.button(tr("style.reload")).clicked() {
+                    let path = app.node_shapes.default_icon_path.clone();
+                    app.node_shapes.icons.forget(&path);
+                }
+            });
+        }
+        ui.separator();
+        draw_edge_style_settings(app, ui);
+        ui.separator();
+        let mut view_3d = app.view_3d;
+        if ui.checkbox(&mut view_3d, tr("view3d.toggle")).on_hover_text(tr("view3d.hint")).changed() {
+            if view_3d { app.enter_3d_mode(); } else { app.leave_3d_mode(); }
+        }
+        ui.add_enabled_ui(app.view_3d, |ui| {
+            ui.checkbox(&mut app.camera.depth_cueing, tr("view3d.depth_cueing"));
+            ui.add(egui::Slider::new(&mut app.camera.distance, 100.0..=5000.0).logarithmic(true).text(tr("view3d.distance")));
+            if ui.button(tr("view3d.flatten")).clicked() {
+                app.leave_3d_mode();
+            }
+        });
+    });
+}
+
+fn draw_font_settings(app: &mut BasicApp, ui: &mut Ui) {
+    ui.collapsing(tr("fonts.section"), |ui| {
+        let fonts = &mut app.fonts;
+        ui.label(tr("fonts.loaded"));
+        if fonts.loaded.is_empty() {
+            ui.colored_label(egui::Color32::from_rgb(220, 120, 60), tr("fonts.none_found"));
+        }
+        for path in &fonts.loaded {
+            ui.label(format!("• {}", path));
+        }
+        ui.label(trf("fonts.discovered", &[&fonts.discovered.len()]))
+            .on_hover_text(fonts.discovered.iter().map(|p| p.display().to_string()).collect::<Vec<_>>().join("\n"));
+        ui.separator();
+
+        ui.horizontal(|ui| {
+            ui.label(tr("fonts.user_font"));
+            ui.text_edit_singleline(&mut fonts.user_font_path);
+        });
+        ui.label(tr("fonts.fallback_chain"));
+        let mut remove_index = None;
+        for (i, path) in fonts.fallback_chain.iter().enumerate() {
+            ui.horizontal(|ui| {
+                ui.label(format!("{}. {}", i + 1, path));
+                if ui.small_button(tr("common.remove")).clicked() {
+                    remove_index = Some(i);
+                }
+            });
+        }
+        if let Some(i) = remove_index {
+            fonts.fallback_chain.remove(i);
+        }
+        ui.horizontal(|ui| {
+            ui.text_edit_singleline(&mut fonts.input_fallback_path);
+            if ui.button(tr("common.add")).clicked() && !fonts.input_fallback_path.trim().is_empty() {
+                fonts.fallback_chain.push(fonts.input_fallback_path.trim().to_owned());
+                fonts.input_fallback_path.clear();
+            }
+        });
+        if ui.button(tr("fonts.rescan")).clicked() {
+            fonts.install(ui.ctx());
+        }
+    });
+}
+
+fn draw_theme_settings(app: &mut BasicApp, ui: &mut Ui) {
+    let theme_settings = &mut app.theme;
+    ui.horizontal(|ui| {
+        ui.label(tr("theme.preset"));
+        egui::ComboBox::from_id_salt("theme_preset").selected_text(theme_settings.preset.label()).show_ui(ui, |ui| {
+            for preset in ThemePreset::ALL {
+                ui.selectable_value(&mut theme_settings.preset, preset, preset.label());
+            }
+        });
+    });
+
+    // Editing any value switches to a custom theme based on the current one
+    let mut theme = theme_settings.active();
+    egui::Grid::new("theme_grid").num_columns(2).show(ui, |ui| {
+        ui.label(tr("theme.dark_mode"));
+        ui.checkbox(&mut theme.dark_mode, "");
+        ui.end_row();
+        ui.label(tr("theme.background"));
+        ui.color_edit_button_srgba(&mut theme.background);
+        ui.end_row();
+        ui.label(tr("theme.node_color"));
+        ui.color_edit_button_srgba(&mut theme.node_color);
+        ui.end_row();
+        ui.label(tr("theme.edge_color"));
+        ui.color_edit_button_srgba(&mut theme.edge_color);
+        ui.end_row();
+        ui.label(tr("theme.selection_fill"));
+        ui.color_edit_button_srgba(&mut theme.selection_fill);
+        ui.end_row();
+        ui.label(tr("theme.selection_stroke"));
+        ui.color_edit_button_srgba(&mut theme.selection_stroke);
+        ui.end_row();
+        ui.label(tr("theme.label_color"));
+        ui.color_edit_button_srgba(&mut theme.label_color);
+        ui.end_row();
+        ui.label(tr("theme.label_font"));
+        egui::ComboBox::from_id_salt("theme_label_font").selected_text(theme.label_font.label()).show_ui(ui, |ui| {
+            for font in LabelFont::ALL {
+                ui.selectable_value(&mut theme.label_font, font, font.label());
+            }
+        });
+        ui.end_row();
+        ui.label(tr("theme.label_size"));
+        ui.add(egui::DragValue::new(&mut theme.label_size).range(4.0..=48.0).speed(0.2));
+        ui.end_row();
+    });
+    if theme != theme_settings.active() {
+        *theme_settings.customize() = theme;
+    }
+
+    ui.horizontal(|ui| {
+        ui.label(tr("theme.style_sheet"));
+        ui.text_edit_singleline(&mut theme_settings.style_sheet_path);
+    });
+    ui.horizontal(|ui| {
+        let has_path = !theme_settings.style_sheet_path.trim().is_empty();
+        if ui.add_enabled(has_path, egui::Button::new(tr("theme.save"))).clicked() {
+            theme_settings.save_style_sheet();
+        }
+        if ui.add_enabled(has_path, egui::Button::new(tr("theme.load"))).clicked() {
+            theme_settings.load_style_sheet();
+        }
+    });
+    if let Some(status) = &theme_settings.status {
+        ui.label(status);
+    }
+}
+
+fn draw_edge_style_settings(app: &mut BasicApp, ui: &mut Ui) {
+    let edge_render = &mut app.edge_render;
+    ui.label(tr("edge_style.section"));
+    egui::Grid::new("edge_style_grid").num_columns(2).show(ui, |ui| {
+        ui.label(tr("edge_style.routing"));
+        egui::ComboBox::from_id_salt("edge_routing").selected_text(edge_render.routing.label()).show_ui(ui, |ui| {
+            for routing in EdgeRouting::ALL {
+                ui.selectable_value(&mut edge_render.routing, routing, routing.label());
+            }
+        });
+        ui.end_row();
+        ui.label(tr("edge_style.arrow"));
+        egui::ComboBox::from_id_salt("edge_arrow").selected_text(edge_render.arrow.label()).show_ui(ui, |ui| {
+            for arrow in ArrowStyle::ALL {
+                ui.selectable_value(&mut edge_render.arrow, arrow, arrow.label());
+            }
+        });
+        ui.end_row();
+        ui.label(tr("edge_style.dash"));
+        egui::ComboBox::from_id_salt("edge_dash").selected_text(edge_render.dash.label()).show_ui(ui, |ui| {
+            for dash in DashStyle::ALL {
+                ui.selectable_value(&mut edge_render.dash, dash, dash.label());
+            }
+        });
+        ui.end_row();
+        ui.label(tr("edge_style.label"));
+        egui::ComboBox::from_id_salt("edge_label_mode").selected_text(edge_render.label_mode.label()).show_ui(ui, |ui| {
+            for mode in EdgeLabelMode::ALL {
+                ui.selectable_value(&mut edge_render.label_mode, mode, mode.label());
+            }
+        });
+        ui.end_row();
+    });
+    if edge_render.routing == EdgeRouting::Bundled {
+        ui.add(egui::Slider::new(&mut edge_render.bundle_strength, 0.0..=1.0).text(tr("edge_style.bundle_strength")))
+            .on_hover_text(tr("edge_style.bundle_hint"));
+    }
+}
+
+// Attributes a channel can be bound to: numeric ones, plus colours on colour channels
+fn mappable_keys(schema: &Schema, color_channel: bool) -> impl Iterator<Item = &String> {
+    schema.iter().filter(move |(_, ty)| ty.is_numeric() || (color_channel && **ty == AttrType::Color)).map(|(key, _)| key)
+}
+
+fn node_attribute_combo(ui: &mut Ui, id: &str, value: &mut NodeAttribute, schema: &Schema, color_channel: bool) {
+    egui::ComboBox::from_id_salt(id).selected_text(value.label().to_owned()).show_ui(ui, |ui| {
+        for attr in NodeAttribute::ALL {
+            let label = attr.label().to_owned();
+            ui.selectable_value(value, attr, label);
+        }
+        for key in mappable_keys(schema, color_channel) {
+            ui.selectable_value(value, NodeAttribute::Custom(key.clone()), key);
+        }
+    });
+}
+
+fn edge_attribute_combo(ui: &mut Ui, id: &str, value: &mut EdgeAttribute, schema: &Schema, color_channel: bool) {
+    egui::ComboBox::from_id_salt(id).selected_text(value.label().to_owned()).show_ui(ui, |ui| {
+        for attr in EdgeAttribute::ALL {
+            let label = attr.label().to_owned();
+            ui.selectable_value(value, attr, label);
+        }
+        for key in mappable_keys(schema, color_channel) {
+            ui.selectable_value(value, EdgeAttribute::Custom(key.clone()), key);
+        }
+    });
+}
+
+fn color_ramp_combo(ui: &mut Ui, id: &str, value: &mut ColorRamp) {
+    egui::ComboBox::from_id_salt(id).selected_text(value.label()).show_ui(ui, |ui| {
+        for ramp in ColorRamp::ALL {
+            ui.selectable_value(value, ramp, ramp.label());
+        }
+    });
+}
+
+fn range_drag(ui: &mut Ui, range: &mut (f32, f32), limits: std::ops::RangeInclusive<f32>, speed: f64) {
+    ui.add(egui::DragValue::new(&mut range.0).speed(speed).range(limits.clone()));
+    ui.label("–");
+    ui.add(egui::DragValue::new(&mut range.1).speed(speed).range(limits));
+}
+
+fn draw_visual_mapping_settings(app: &mut BasicApp, ui: &mut Ui) {
+    ui.collapsing(tr("mapping.section"), |ui| {
+        let node_schema = attributes::node_schema(&app.g);
+        let edge_schema = attributes::edge_schema(&app.g);
+        let mapping = &mut app.visual_mapping;
+        egui::Grid::new("visual_mapping_grid").num_columns(2).show(ui, |ui| {
+            ui.label(tr("mapping.node_size"));
+            node_attribute_combo(ui, "node_size_attr", &mut mapping.node_size_attr, &node_schema, false);
+            ui.end_row();
+            ui.label(tr("mapping.radius_range"));
+            ui.horizontal(|ui| range_drag(ui, &mut mapping.node_size_range, 1.0..=50.0, 0.1));
+            ui.end_row();
+            ui.label(tr("mapping.node_color"));
+            node_attribute_combo(ui, "node_color_attr", &mut mapping.node_color_attr, &node_schema, true);
+            ui.end_row();
+            ui.label(tr("mapping.ramp"));
+            color_ramp_combo(ui, "node_ramp", &mut mapping.node_ramp);
+            ui.end_row();
+
+            ui.label(tr("mapping.edge_width"));
+            edge_attribute_combo(ui, "edge_width_attr", &mut mapping.edge_width_attr, &edge_schema, false);
+            ui.end_row();
+            ui.label(tr("mapping.width_range"));
+            ui.horizontal(|ui| range_drag(ui, &mut mapping.edge_width_range, 0.5..=20.0, 0.1));
+            ui.end_row();
+            ui.label(tr("mapping.edge_opacity"));
+            edge_attribute_combo(ui, "edge_opacity_attr", &mut mapping.edge_opacity_attr, &edge_schema, false);
+            ui.end_row();
+            ui.label(tr("mapping.opacity_range"));
+            ui.horizontal(|ui| range_drag(ui, &mut mapping.edge_opacity_range, 0.0..=1.0, 0.01));
+            ui.end_row();
+            ui.label(tr("mapping.edge_color"));
+            edge_attribute_combo(ui, "edge_color_attr", &mut mapping.edge_color_attr, &edge_schema, true);
+            ui.end_row();
+            ui.label(tr("mapping.ramp"));
+            color_ramp_combo(ui, "edge_ramp", &mut mapping.edge_ramp);
+            ui.end_row();
+        });
+        ui.checkbox(&mut mapping.show_legend, tr("mapping.show_legend"));
+    });
+}
+
+// Results listed in the panel; the rest are only counted
+const MAX_LISTED_RESULTS: usize = 10;
+
+fn draw_filter_settings(app: &mut BasicApp, ui: &mut Ui) {
+    ui.collapsing(tr("filter.section"), |ui| {
+        let schema = attributes::node_schema(&app.g);
+        let search = &mut app.search;
+        if search.predicates.is_empty() {
+            ui.label(tr("filter.empty"));
+        }
+        let mut remove_index = None;
+        for (i, predicate) in search.predicates.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                ui.label(predicate.label());
+                match predicate {
+                    Predicate::Weight { min, max } => {
+                        ui.add(egui::DragValue::new(min).speed(0.1));
+                        ui.label("–");
+                        ui.add(egui::DragValue::new(max).speed(0.1));
+                    }
+                    Predicate::Degree { min, max } => {
+                        ui.add(egui::DragValue::new(min).speed(0.1));
+                        ui.label("–");
+                        ui.add(egui::DragValue::new(max).speed(0.1));
+                    }
+                    Predicate::Pinned(pinned) => {
+                        ui.checkbox(pinned, "");
+                    }
+                    Predicate::Shape(shape) => {
+                        egui::ComboBox::from_id_salt(("filter_shape", i)).selected_text(shape.label()).show_ui(ui, |ui| {
+                            for option in NodeShapeKind::ALL {
+                                ui.selectable_value(shape, option, option.label());
+                            }
+                        });
+                    }
+                    Predicate::Attribute { key, test } => draw_attribute_predicate(ui, i, key, test, &schema),
+                }
+                if ui.small_button(tr("common.remove")).clicked() {
+                    remove_index = Some(i);
+                }
+            });
+        }
+        if let Some(i) = remove_index {
+            search.predicates.remove(i);
+        }
+        ui.menu_button(tr("filter.add"), |ui| {
+            for mut template in Predicate::TEMPLATES {
+                if let Predicate::Attribute { key, .. } = &mut template {
+                    // Starts on the first attribute the graph has
+                    *key = schema.keys().next().cloned().unwrap_or_default();
+                }
+                if ui.button(template.label()).clicked() {
+                    search.predicates.push(template);
+                    ui.close_menu();
+                }
+            }
+        });
+
+        if search.is_active() {
+            ui.separator();
+            ui.label(tr("filter.results"));
+            for idx in search.matches.iter().take(MAX_LISTED_RESULTS) {
+                if let Some(payload) = app.g.node_payload(*idx) {
+                    ui.label(format!("• {}", payload.label));
+                }
+            }
+            if search.matches.len() > MAX_LISTED_RESULTS {
+                ui.label(trf("filter.more", &[&(search.matches.len() - MAX_LISTED_RESULTS)]));
+            }
+        }
+    });
+}
+
+fn draw_attribute_predicate(ui: &mut Ui, i: usize, key: &mut String, test: &mut AttrTest, schema: &Schema) {
+    let selected = if key.is_empty() { tr("common.none").to_owned() } else { key.clone() };
+    egui::ComboBox::from_id_salt(("filter_attr_key", i)).selected_text(selected).width(80.0).show_ui(ui, |ui| {
+        for option in schema.keys() {
+            ui.selectable_value(key, option.clone(), option);
+        }
+    });
+    egui::ComboBox::from_id_salt(("filter_attr_test", i)).selected_text(test.label()).width(60.0).show_ui(ui, |ui| {
+        for option in AttrTest::TEMPLATES {
+            let label = option.label();
+            // Keeps the typed text when only the kind of comparison changes
+            let option = match (&option, &*test) {
+                (AttrTest::Equals(_), AttrTest::Contains(text)) => AttrTest::Equals(text.clone()),
+                (AttrTest::Contains(_), AttrTest::Equals(text)) => AttrTest::Contains(text.clone()),
+                _ => option,
+            };
+            let current = std::mem::discriminant(&*test) == std::mem::discriminant(&option);
+            if ui.selectable_label(current, label).clicked() && !current {
+                *test = option;
+            }
+        }
     });
+    match test {
+        AttrTest::Present => {}
+        AttrTest::Equals(text) | AttrTest::Contains(text) => {
+            ui.add(egui::TextEdit::singleline(text).desired_width(60.0));
+        }
+        AttrTest::Range { min, max } => {
+            ui.add(egui::DragValue::new(min).speed(0.1));
+            ui.label("–");
+            ui.add(egui::DragValue::new(max).speed(0.1));
+        }
+    }
 }
 
 fn draw_navigation_settings(app: &mut BasicApp, ui: &mut Ui) {
-    ui.collapsing("导航设置", |ui| {
-        if ui.checkbox(&mut app.nav_fit_to_screen, "适应屏幕").changed() {
+    ui.collapsing(tr("nav.section"), |ui| {
+        if ui.checkbox(&mut app.nav_fit_to_screen, tr("nav.fit")).changed() {
             if app.nav_fit_to_screen {
                 app.nav_zoom_and_pan = false;
             }
         }
-        ui.label("启用后，图表将始终缩放以适应屏幕。");
+        ui.label(tr("nav.fit_hint"));
         ui.add_space(5.0);
         ui.add_enabled_ui(!app.nav_fit_to_screen, |ui| {
-            ui.checkbox(&mut app.nav_zoom_and_pan, "缩放与平移");
-            ui.label("启用后，可用Ctrl+滚轮缩放，鼠标中键拖拽平移。");
-            ui.add(egui::Slider::new(&mut app.nav_zoom_speed, 0.01..=0.5).text("缩放速度"));
+            ui.checkbox(&mut app.nav_zoom_and_pan, tr("nav.zoom_pan"));
+            ui.label(tr("nav.zoom_pan_hint"));
+            ui.add(egui::Slider::new(&mut app.nav_zoom_speed, 0.01..=0.5).text(tr("nav.zoom_speed")));
         });
+        ui.add_space(5.0);
+        ui.checkbox(&mut app.minimap.enabled, tr("nav.minimap")).on_hover_text(tr("nav.minimap_hint"));
     });
 }
 
 fn draw_interaction_settings(app: &mut BasicApp, ui: &mut Ui) {
-    ui.collapsing("交互设置", |ui| {
-        if ui.checkbox(&mut app.ia_dragging_enabled, "节点可拖拽").on_hover_text("启用后，可按住鼠标左键拖拽节点。").changed() {
+    ui.collapsing(tr("interaction.section"), |ui| {
+        if ui.button(tr("keymap.open")).on_hover_text(tr("keymap.open_hint")).clicked() {
+            app.keymap.show_editor = true;
+        }
+        ui.add_space(5.0);
+        if ui.checkbox(&mut app.ia_dragging_enabled, tr("interaction.node_drag")).on_hover_text(tr("interaction.node_drag_hint")).changed() {
             if app.ia_dragging_enabled { app.ia_node_clicking_enabled = true; }
         }
         ui.add_space(5.0);
         ui.add_enabled_ui(!(app.ia_dragging_enabled || app.ia_node_selection_enabled || app.ia_node_selection_multi_enabled), |ui| {
-            ui.checkbox(&mut app.ia_node_clicking_enabled, "节点可点击").on_hover_text("启用后，可捕获节点点击事件。");
-        }).response.on_disabled_hover_text("节点拖拽或选择已启用时，点击自动启用");
+            ui.checkbox(&mut app.ia_node_clicking_enabled, tr("interaction.node_click")).on_hover_text(tr("interaction.node_click_hint"));
+        }).response.on_disabled_hover_text(tr("interaction.node_click_forced"));
         ui.add_space(5.0);
         ui.add_enabled_ui(!app.ia_node_selection_multi_enabled, |ui| {
-            if ui.checkbox(&mut app.ia_node_selection_enabled, "节点可选择").on_hover_text("启用后，可单击选择/取消选择节点。").changed() {
+            if ui.checkbox(&mut app.ia_node_selection_enabled, tr("interaction.node_select")).on_hover_text(tr("interaction.node_select_hint")).changed() {
                  if app.ia_node_selection_enabled { app.ia_node_clicking_enabled = true; }
             }
-        }).response.on_disabled_hover_text("节点多选已启用时，单选自动启用");
+        }).response.on_disabled_hover_text(tr("interaction.node_select_forced"));
         ui.add_space(5.0);
-        if ui.checkbox(&mut app.ia_node_selection_multi_enabled, "节点可多选").on_hover_text("启用后，可按住Ctrl点击多选节点。").changed() {
+        if ui.checkbox(&mut app.ia_node_selection_multi_enabled, tr("interaction.node_multi")).on_hover_text(tr("interaction.node_multi_hint")).changed() {
             if app.ia_node_selection_multi_enabled {
                 app.ia_node_clicking_enabled = true;
                 app.ia_node_selection_enabled = true;
@@ -97,16 +535,16 @@ fn draw_interaction_settings(app: &mut BasicApp, ui: &mut Ui) {
         }
         ui.add_space(10.0);
         ui.add_enabled_ui(!(app.ia_edge_selection_enabled || app.ia_edge_selection_multi_enabled), |ui| {
-             ui.checkbox(&mut app.ia_edge_clicking_enabled, "边可点击").on_hover_text("启用后，可捕获边点击事件。");
-        }).response.on_disabled_hover_text("边选择已启用时，点击自动启用");
+             ui.checkbox(&mut app.ia_edge_clicking_enabled, tr("interaction.edge_click")).on_hover_text(tr("interaction.edge_click_hint"));
+        }).response.on_disabled_hover_text(tr("interaction.edge_click_forced"));
         ui.add_space(5.0);
         ui.add_enabled_ui(!app.ia_edge_selection_multi_enabled, |ui| {
-            if ui.checkbox(&mut app.ia_edge_selection_enabled, "边可选择").on_hover_text("启用后，可单击选择/取消选择边。").changed() {
+            if ui.checkbox(&mut app.ia_edge_selection_enabled, tr("interaction.edge_select")).on_hover_text(tr("interaction.edge_select_hint")).changed() {
                 if app.ia_edge_selection_enabled { app.ia_edge_clicking_enabled = true; }
             }
-        }).response.on_disabled_hover_text("边多选已启用时，单选自动启用");
+        }).response.on_disabled_hover_text(tr("interaction.edge_select_forced"));
         ui.add_space(5.0);
-        if ui.checkbox(&mut app.ia_edge_selection_multi_enabled, "边可多选").on_hover_text("启用后，可按住Ctrl点击多选边。").changed() {
+        if ui.checkbox(&mut app.ia_edge_selection_multi_enabled, tr("interaction.edge_multi")).on_hover_text(tr("interaction.edge_multi_hint")).changed() {
             if app.ia_edge_selection_multi_enabled {
                 app.ia_edge_clicking_enabled = true;
                 app.ia_edge_selection_enabled = true;
@@ -116,18 +554,249 @@ fn draw_interaction_settings(app: &mut BasicApp, ui: &mut Ui) {
 }
 
 fn draw_simulation_settings(app: &mut BasicApp, ui: &mut Ui) {
-    ui.collapsing("模拟设置", |ui| {
-        ui.checkbox(&mut app.simulation_stopped, "停止模拟");
+    ui.collapsing(tr("sim.section"), |ui| {
+        ui.checkbox(&mut app.simulation_stopped, tr("sim.stop"));
         ui.add_enabled_ui(!app.simulation_stopped, |ui| {
-            if ui.add(egui::Slider::new(&mut app.sim_dt, 0.001..=0.1).text("时间步长 (dt)")).changed() ||
-               ui.add(egui::Slider::new(&mut app.sim_cooloff_factor, 0.5..=0.999).text("冷却因子")).changed() ||
-               ui.add(egui::Slider::new(&mut app.sim_scale, 10.0..=500.0).text("缩放尺度")).changed() {
+            if ui.add(egui::Slider::new(&mut app.sim_dt, 0.001..=0.1).text(tr("sim.dt"))).changed() ||
+               ui.add(egui::Slider::new(&mut app.sim_cooloff_factor, 0.5..=0.999).text(tr("sim.cooloff"))).changed() ||
+               ui.add(egui::Slider::new(&mut app.sim_scale, 10.0..=500.0).text(tr("sim.scale"))).changed() {
                 // Update simulation parameters if they are changed
                 app.force_algo.conf.dt = app.sim_dt;
                 app.force_algo.conf.cooloff_factor = app.sim_cooloff_factor;
                 app.force_algo.conf.scale = app.sim_scale;
+                app.sync_force_settings_to_3d();
+                app.wake_simulation();
             }
         });
+        ui.add_space(5.0);
+        draw_overlap_settings(app, ui);
+        ui.add_space(5.0);
+        draw_convergence_settings(app, ui);
+        ui.add_space(5.0);
+        draw_weight_physics_settings(app, ui);
+    });
+}
+
+fn draw_overlap_settings(app: &mut BasicApp, ui: &mut Ui) {
+    ui.horizontal(|ui| {
+        if ui.button(tr("overlap.remove")).on_hover_text(tr("overlap.remove_hint")).clicked() {
+            app.remove_overlaps(ui.ctx());
+        }
+        ui.checkbox(&mut app.overlap.continuous, tr("overlap.continuous"));
+    });
+    ui.add(egui::Slider::new(&mut app.overlap.padding, 0.0..=30.0).text(tr("overlap.padding")));
+}
+
+fn draw_convergence_settings(app: &mut BasicApp, ui: &mut Ui) {
+    ui.horizontal(|ui| {
+        ui.label(trf("sim.energy", &[&format!("{:.2}", app.force_algo.last_energy)]));
+        ui.label(trf("sim.mean_displacement", &[&format!("{:.3}", app.force_algo.last_mean_displacement)]));
+    });
+    draw_energy_plot(ui, &app.convergence);
+    if app.convergence.converged {
+        ui.horizontal(|ui| {
+            ui.colored_label(egui::Color32::from_rgb(80, 180, 80), tr("sim.converged"));
+            if ui.button(tr("sim.resume")).clicked() {
+                app.wake_simulation();
+            }
+        });
+    }
+    if ui.checkbox(&mut app.convergence.auto_stop, tr("sim.auto_stop")).changed() && !app.convergence.auto_stop {
+        app.wake_simulation();
+    }
+    ui.add_enabled_ui(app.convergence.auto_stop, |ui| {
+        ui.add(egui::Slider::new(&mut app.convergence.threshold, 0.001..=1.0).logarithmic(true).text(tr("sim.threshold")));
+    });
+}
+
+fn draw_weight_physics_settings(app: &mut BasicApp, ui: &mut Ui) {
+    let before = (app.force_algo.edge_effect, app.force_algo.edge_mapping, app.force_algo.node_effect, app.force_algo.node_mapping);
+    ui.label(tr("weights.section"));
+    egui::Grid::new("weight_physics_grid").num_columns(2).show(ui, |ui| {
+        ui.label(tr("weights.edge_effect"));
+        egui::ComboBox::from_id_salt("edge_weight_effect")
+            .selected_text(app.force_algo.edge_effect.label())
+            .show_ui(ui, |ui| {
+                for effect in EdgeWeightEffect::ALL {
+                    ui.selectable_value(&mut app.force_algo.edge_effect, effect, effect.label());
+                }
+            });
+        ui.end_row();
+        ui.label(tr("weights.edge_mapping"));
+        egui::ComboBox::from_id_salt("edge_weight_mapping")
+            .selected_text(app.force_algo.edge_mapping.label())
+            .show_ui(ui, |ui| {
+                for mapping in WeightMapping::ALL {
+                    ui.selectable_value(&mut app.force_algo.edge_mapping, mapping, mapping.label());
+                }
+            });
+        ui.end_row();
+        ui.label(tr("weights.node_effect"));
+        egui::ComboBox::from_id_salt("node_weight_effect")
+            .selected_text(app.force_algo.node_effect.label())
+            .show_ui(ui, |ui| {
+                for effect in NodeWeightEffect::ALL {
+                    ui.selectable_value(&mut app.force_algo.node_effect, effect, effect.label());
+                }
+            });
+        ui.end_row();
+        ui.label(tr("weights.node_mapping"));
+        egui::ComboBox::from_id_salt("node_weight_mapping")
+            .selected_text(app.force_algo.node_mapping.label())
+            .show_ui(ui, |ui| {
+                for mapping in WeightMapping::ALL {
+                    ui.selectable_value(&mut app.force_algo.node_mapping, mapping, mapping.label());
+                }
+            });
+        ui.end_row();
+    });
+    let after = (app.force_algo.edge_effect, app.force_algo.edge_mapping, app.force_algo.node_effect, app.force_algo.node_mapping);
+    if before != after {
+        app.sync_force_settings_to_3d();
+        app.wake_simulation();
+    }
+}
+
+fn draw_constraint_settings(app: &mut BasicApp, ui: &mut Ui) {
+    ui.collapsing(tr("constraints.section"), |ui| {
+        ui.label(tr("constraints.from_selection"));
+        ui.horizontal(|ui| {
+            if ui.button(tr("constraints.align_horizontal")).clicked() {
+                app.add_alignment_constraint(Axis::Horizontal);
+            }
+            if ui.button(tr("constraints.align_vertical")).clicked() {
+                app.add_alignment_constraint(Axis::Vertical);
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.label(tr("constraints.distance"));
+            ui.add(egui::DragValue::new(&mut app.input_constraint_distance).speed(1.0).range(0.0..=1000.0));
+            if ui.button(tr("constraints.min_separation")).clicked() {
+                app.add_separation_constraint();
+            }
+        });
+        if ui.button(tr("constraints.bounding_box")).on_hover_text(tr("constraints.bounding_box_hint")).clicked() {
+            app.add_bounding_box_constraint();
+        }
+        ui.horizontal(|ui| {
+            if ui.button(trf("constraints.set_group_a", &[&app.constraints.group_a.len()])).clicked() {
+                app.constraints.group_a = app.selected_node_ids();
+            }
+            if ui.button(trf("constraints.set_group_b", &[&app.constraints.group_b.len()])).clicked() {
+                app.constraints.group_b = app.selected_node_ids();
+            }
+        });
+        ui.horizontal(|ui| {
+            if ui.button(tr("constraints.a_left_of_b")).clicked() {
+                app.add_order_constraint(Axis::Horizontal);
+            }
+            if ui.button(tr("constraints.a_above_b")).clicked() {
+                app.add_order_constraint(Axis::Vertical);
+            }
+        });
+        ui.add(egui::Slider::new(&mut app.constraints.iterations, 1..=20).text(tr("constraints.iterations")));
+
+        ui.separator();
+        if app.constraints.constraints.is_empty() {
+            ui.label(tr("constraints.empty"));
+        }
+        let mut to_remove = None;
+        let mut toggled = false;
+        for (i, constraint) in app.constraints.constraints.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                toggled |= ui.checkbox(&mut constraint.enabled, "").changed();
+                ui.label(constraint.description());
+                if ui.small_button(tr("common.delete")).clicked() {
+                    to_remove = Some(i);
+                }
+            });
+        }
+        if let Some(i) = to_remove {
+            app.constraints.constraints.remove(i);
+        }
+        if toggled || to_remove.is_some() {
+            app.wake_simulation();
+        }
+        ui.checkbox(&mut app.show_constraint_guides, tr("constraints.show_guides"));
+    });
+}
+
+fn draw_focus_settings(app: &mut BasicApp, ui: &mut Ui) {
+    ui.collapsing(tr("focus.section"), |ui| {
+        if ui.checkbox(&mut app.focus.enabled, tr("focus.enabled")).on_hover_text(tr("focus.enabled_hint")).changed() && app.focus.enabled {
+            if let [id] = app.selected_node_ids()[..] {
+                app.focus.focus_on(id);
+            }
+        }
+        ui.add(egui::Slider::new(&mut app.focus.k, 1..=6).text(tr("focus.k")));
+        ui.horizontal(|ui| {
+            ui.label(tr("focus.fade"));
+            for fade in FocusFade::ALL {
+                ui.selectable_value(&mut app.focus.fade, fade, fade.label());
+            }
+        });
+        ui.add_space(5.0);
+        let selected = app.g.selected_nodes();
+        ui.add_enabled_ui(!selected.is_empty(), |ui| {
+            ui.label(tr("focus.expand_selection"));
+            crate::graph_view::draw_neighbourhood_commands(app, ui, &selected);
+        });
+    });
+}
+
+fn draw_group_settings(app: &mut BasicApp, ui: &mut Ui) {
+    ui.collapsing(tr("group.section"), |ui| {
+        ui.horizontal(|ui| {
+            ui.add(egui::TextEdit::singleline(&mut app.groups.input_name).hint_text(tr("group.name_hint")).desired_width(120.0));
+            if ui.add_enabled(!app.g.selected_nodes().is_empty(), egui::Button::new(tr("group.create"))).clicked() {
+                app.group_selected_nodes();
+            }
+        });
+        ui.label(tr("group.nesting_hint"));
+        ui.add_space(5.0);
+
+        let mut collapse = None;
+        let mut expand = None;
+        let mut dissolve = None;
+        let mut select = None;
+        for (i, group) in app.groups.groups.iter().enumerate() {
+            ui.horizontal(|ui| {
+                ui.label(group.description());
+                match &group.collapsed {
+                    Some(collapsed) => {
+                        // Nested groups can only be opened once the enclosing group is
+                        let visible = app.ids.egui_node(collapsed.meta).is_some();
+                        if ui.add_enabled(visible, egui::Button::new(tr("group.expand")).small()).clicked() {
+                            expand = Some(i);
+                        }
+                    }
+                    None => {
+                        if ui.small_button(tr("group.collapse")).clicked() {
+                            collapse = Some(i);
+                        }
+                        if ui.small_button(tr("group.select")).clicked() {
+                            select = Some(i);
+                        }
+                    }
+                }
+                if ui.small_button(tr("group.dissolve_button")).on_hover_text(tr("group.dissolve")).clicked() {
+                    dissolve = Some(i);
+                }
+            });
+        }
+        if let Some(i) = collapse {
+            app.collapse_group(i);
+        }
+        if let Some(i) = expand {
+            app.expand_group(i);
+        }
+        if let Some(i) = dissolve {
+            app.dissolve_group(i);
+        }
+        if let Some(i) = select {
+            let nodes = app.groups.groups[i].members.iter().filter_map(|id| app.ids.egui_node(*id)).collect();
+            app.select_nodes(nodes);
+        }
     });
 }
 
@@ -138,62 +807,81 @@ fn draw_node_edge_management(app: &mut BasicApp, ui: &mut Ui) {
     // Let's add input_node_weight and input_edge_weight to BasicApp for now.
     // These should be initialized in BasicApp::new()
 
-    ui.collapsing("节点/边管理", |ui| {
-        ui.label("添加节点:");
+    ui.collapsing(tr("edit.section"), |ui| {
+        if app.view_3d {
+            ui.label(tr("edit.disabled_in_3d"));
+            return;
+        }
+        ui.label(tr("edit.add_node_heading"));
         ui.horizontal(|ui| {
-            ui.label("标签:");
+            ui.label(tr("edit.label"));
             ui.text_edit_singleline(&mut app.input_node_to_add);
         });
         ui.horizontal(|ui| {
-            ui.label("权重:");
+            ui.label(tr("edit.weight"));
             // Assuming app has input_node_weight: f32
             ui.add(egui::DragValue::new(&mut app.input_node_weight).speed(0.1).range(0.0..=100.0));
         });
-        if ui.button("添加节点").clicked() {
+        if ui.button(tr("edit.add_node")).clicked() {
             app.add_node_ui(app.input_node_to_add.clone(), app.input_node_weight);
             app.input_node_to_add.clear();
             // app.input_node_weight = 1.0; // Reset to default
         }
         ui.add_space(5.0);
 
-        ui.label("删除节点:");
+        ui.label(tr("edit.remove_node_heading"));
         ui.horizontal(|ui| {
-            ui.label("标签:");
+            ui.label(tr("edit.label"));
             ui.text_edit_singleline(&mut app.input_node_to_remove);
-            if ui.button("删除").clicked() {
-                app.remove_node_ui(app.input_node_to_remove.clone());
-                app.input_node_to_remove.clear();
+            if ui.button(tr("common.delete")).clicked() {
+                app.edit_error = app.remove_node_ui(app.input_node_to_remove.clone()).err();
+                if app.edit_error.is_none() {
+                    app.input_node_to_remove.clear();
+                }
             }
         });
         
         ui.separator();
-        ui.label("添加边 (通过标签):");
+        ui.label(tr("edit.add_edge_heading"));
         ui.horizontal(|ui| {
-            ui.label("从:");
+            ui.label(tr("edit.from"));
             ui.text_edit_singleline(&mut app.input_node_from);
-            ui.label("到:");
+            ui.label(tr("edit.to"));
             ui.text_edit_singleline(&mut app.input_node_to);
         });
         ui.horizontal(|ui| {
-            ui.label("权重:");
+            ui.label(tr("edit.weight"));
             // Assuming app has input_edge_weight: f32
             ui.add(egui::DragValue::new(&mut app.input_edge_weight).speed(0.1).range(0.0..=100.0));
         });
-        if ui.button("添加边").clicked() {
-            app.add_edge_ui(app.input_node_from.clone(), app.input_node_to.clone(), app.input_edge_weight);
-            app.input_node_from.clear();
-            app.input_node_to.clear();
+        if ui.button(tr("edit.add_edge")).clicked() {
+            app.edit_error = app.add_edge_ui(app.input_node_from.clone(), app.input_node_to.clone(), app.input_edge_weight).err();
+            if app.edit_error.is_none() {
+                app.input_node_from.clear();
+                app.input_node_to.clear();
+            }
             // app.input_edge_weight = 1.0; // Reset to default
         }
+        if let Some(error) = &app.edit_error {
+            ui.colored_label(ui.visuals().error_fg_color, error);
+        }
         ui.add_space(5.0);
         
         ui.separator();
-        if ui.button("在选中节点间添加边").on_hover_text("选择两个节点后点击此按钮添加边").clicked() {
+        if ui.button(tr("edit.add_edge_selected")).on_hover_text(tr("edit.add_edge_selected_hint")).clicked() {
             app.add_edge_between_selected_nodes();
         }
-        if ui.button("删除选中的边").on_hover_text("选择一条或多条边后点击此按钮删除").clicked() {
+        if ui.button(tr("edit.remove_selected_edges")).on_hover_text(tr("edit.remove_selected_edges_hint")).clicked() {
             app.remove_selected_edges_ui();
         }
+        ui.horizontal(|ui| {
+            if ui.button(tr("pin.pin_selected")).on_hover_text(tr("pin.pin_selected_hint")).clicked() {
+                app.set_selected_nodes_pinned(true);
+            }
+            if ui.button(tr("pin.unpin")).clicked() {
+                app.set_selected_nodes_pinned(false);
+            }
+        });
         ui.separator();
         draw_selected_element_properties(app, ui); // New function to draw selected element props
     });
@@ -201,7 +889,7 @@ fn draw_node_edge_management(app: &mut BasicApp, ui: &mut Ui) {
 
 // New function to display/edit properties of selected node/edge
 fn draw_selected_element_properties(app: &mut BasicApp, ui: &mut Ui) {
-    ui.label("选中元素属性:");
+    ui.label(tr("props.heading")).on_hover_text(tr("props.rename_hint"));
 
     let selected_nodes: Vec<_> = match &app.g {
         crate::app::AppGraph::Directed(g) => g.selected_nodes().iter().copied().collect(),
@@ -215,50 +903,96 @@ fn draw_selected_element_properties(app: &mut BasicApp, ui: &mut Ui) {
     if selected_nodes.len() == 1 && selected_edges.is_empty() {
         let node_idx = selected_nodes[0];
         if let Some(node_payload) = app.get_node_payload_mut(node_idx) {
-            ui.label(format!("节点: {}", node_payload.label));
-            if ui.add(egui::DragValue::new(&mut node_payload.weight).speed(0.1).prefix("权重: ")).changed() {
+            ui.label(trf("props.node", &[&node_payload.label]));
+            if ui.add(egui::DragValue::new(&mut node_payload.weight).speed(0.1).prefix(tr("props.weight_prefix"))).changed() {
                 // Clone the payload *after* DragValue has modified it, then pass the clone.
                 // This releases the mutable borrow of node_payload before calling another &mut self method.
                 let updated_payload = node_payload.clone();
                 app.update_fdg_node_payload(node_idx, updated_payload);
+                return;
+            }
+            ui.horizontal(|ui| {
+                ui.label(tr("props.shape"));
+                let current = node_payload.shape.map_or(tr("props.follow_global"), |s| s.label());
+                egui::ComboBox::from_id_salt("node_shape_override").selected_text(current).show_ui(ui, |ui| {
+                    ui.selectable_value(&mut node_payload.shape, None, tr("props.follow_global"));
+                    for shape in NodeShapeKind::ALL {
+                        ui.selectable_value(&mut node_payload.shape, Some(shape), shape.label());
+                    }
+                });
+            });
+            if node_payload.shape == Some(NodeShapeKind::Icon) {
+                ui.horizontal(|ui| {
+                    ui.label(tr("style.icon_file"));
+                    let mut path = node_payload.icon_path.clone().unwrap_or_default();
+                    if ui.text_edit_singleline(&mut path).changed() {
+                        node_payload.icon_path = if path.is_empty() { None } else { Some(path) };
+                    }
+                });
             }
         }
+        draw_node_attributes(app, ui, node_idx);
     } else if selected_edges.len() == 1 && selected_nodes.is_empty() {
         let edge_idx = selected_edges[0];
          if let Some(edge_payload) = app.get_edge_payload_mut(edge_idx) {
-            ui.label(format!("边: {}", edge_payload.label));
-            if ui.add(egui::DragValue::new(&mut edge_payload.weight).speed(0.1).prefix("权重: ")).changed() {
+            ui.label(trf("props.edge", &[&edge_payload.label]));
+            if ui.add(egui::DragValue::new(&mut edge_payload.weight).speed(0.1).prefix(tr("props.weight_prefix"))).changed() {
                 // Clone the payload *after* DragValue has modified it.
                 let updated_payload = edge_payload.clone();
                 app.update_fdg_edge_payload(edge_idx, updated_payload);
             }
         }
+        draw_edge_attributes(app, ui, edge_idx);
     } else if selected_nodes.len() > 1 || selected_edges.len() > 1 || (!selected_nodes.is_empty() && !selected_edges.is_empty()) {
-        ui.label("请只选择单个节点或单个边以编辑属性。");
+        crate::bulk_edit::draw_bulk_editor(app, ui, &selected_nodes, &selected_edges);
     } else {
-        ui.label("未选择任何元素或选择不明确。");
+        ui.label(tr("props.none"));
     }
 }
 
+// The draft row is taken out of the app while the payload is borrowed
+fn draw_node_attributes(app: &mut BasicApp, ui: &mut Ui, node_idx: NodeIndex) {
+    ui.label(tr("attr.section"));
+    let mut draft = std::mem::take(&mut app.attribute_draft);
+    if let Some(payload) = app.get_node_payload_mut(node_idx) {
+        if attributes::draw_attribute_table(ui, "node", &mut payload.attributes, &mut draft) {
+            let payload = payload.clone();
+            app.update_fdg_node_payload(node_idx, payload);
+        }
+    }
+    app.attribute_draft = draft;
+}
+
+fn draw_edge_attributes(app: &mut BasicApp, ui: &mut Ui, edge_idx: EdgeIndex) {
+    ui.label(tr("attr.section"));
+    let mut draft = std::mem::take(&mut app.attribute_draft);
+    if let Some(payload) = app.get_edge_payload_mut(edge_idx) {
+        if attributes::draw_attribute_table(ui, "edge", &mut payload.attributes, &mut draft) {
+            let payload = payload.clone();
+            app.update_fdg_edge_payload(edge_idx, payload);
+        }
+    }
+    app.attribute_draft = draft;
+}
 
 fn draw_debug_info(app: &BasicApp, ui: &mut Ui) {
-    ui.collapsing("调试信息", |ui| {
+    ui.collapsing(tr("debug.section"), |ui| {
         let (num_nodes, num_edges) = match &app.g {
             crate::app::AppGraph::Directed(g) => (g.node_count(), g.edge_count()),
             crate::app::AppGraph::Undirected(g) => (g.node_count(), g.edge_count()),
         };
-        ui.label(format!("Egui图: {} 节点, {} 边", num_nodes, num_edges));
-        ui.label(format!("Fdg图: {} 节点, {} 边", app.sim.node_count(), app.sim.edge_count()));
-        ui.label(format!("标签映射数量: {}", app.node_label_to_index_map.len()));
+        ui.label(trf("debug.egui_graph", &[&num_nodes, &num_edges]));
+        ui.label(trf("debug.fdg_graph", &[&app.sim.node_count(), &app.sim.edge_count()]));
+        ui.label(trf("debug.id_map", &[&app.ids.node_count(), &app.ids.edge_count()]));
         
         ui.separator();
-        ui.label("选中的节点:");
+        ui.label(tr("debug.selected_nodes"));
         let selected_nodes_labels: Vec<String> = match &app.g {
             crate::app::AppGraph::Directed(g) => g.selected_nodes().iter().filter_map(|idx| g.node(*idx).map(|n| n.payload().label.clone())).collect(),
             crate::app::AppGraph::Undirected(g) => g.selected_nodes().iter().filter_map(|idx| g.node(*idx).map(|n| n.payload().label.clone())).collect(),
         };
         if selected_nodes_labels.is_empty() {
-            ui.label("无");
+            ui.label(tr("common.none"));
         } else {
             for label in selected_nodes_labels {
                 ui.label(format!("- {}", label));
@@ -266,13 +1000,13 @@ fn draw_debug_info(app: &BasicApp, ui: &mut Ui) {
         }
 
         ui.separator();
-        ui.label("选中的边:");
+        ui.label(tr("debug.selected_edges"));
         let selected_edges_details: Vec<String> = match &app.g {
             crate::app::AppGraph::Directed(g) => g.selected_edges().iter().filter_map(|idx| g.edge(*idx).map(|e| e.payload().label.clone())).collect(),
             crate::app::AppGraph::Undirected(g) => g.selected_edges().iter().filter_map(|idx| g.edge(*idx).map(|e| e.payload().label.clone())).collect(),
         };
         if selected_edges_details.is_empty() {
-            ui.label("无");
+            ui.label(tr("common.none"));
         } else {
             for detail in selected_edges_details {
                 ui.label(format!("- {}", detail));
@@ -0,0 +1,854 @@
+use std::collections::HashMap;
+use egui::{Color32, ColorImage, Context, FontFamily, FontId, Pos2, Rect, Shape, Stroke, StrokeKind, TextureHandle, TextureId, TextureOptions, Vec2};
+use egui::epaint::{CircleShape, RectShape, TextShape};
+use egui_graphs::{DisplayEdge, DisplayNode, DrawContext, EdgeProps, Node, NodeProps};
+use petgraph::EdgeType;
+use petgraph::stable_graph::{DefaultIx, EdgeIndex, IndexType, NodeIndex};
+use serde::{Deserialize, Serialize};
+use crate::app::{AppGraph, AppGraphOf, NodePayload, EdgePayload};
+use crate::i18n::tr;
+
+pub const DEFAULT_NODE_RADIUS: f32 = 5.0;
+pub const DEFAULT_EDGE_WIDTH: f32 = 2.0;
+// Screen-space sizes of the arrow tip
+const TIP_SIZE: f32 = 12.0;
+const TIP_ANGLE: f32 = std::f32::consts::FRAC_PI_6;
+// Offset of the control point per parallel edge, in canvas units
+const CURVE_SIZE: f32 = 20.0;
+const LOOP_SIZE: f32 = 3.0;
+// Extra canvas distance an edge can be clicked from
+const EDGE_HIT_TOLERANCE: f32 = 3.0;
+const CURVE_SEGMENTS: usize = 16;
+// Extra space around a label inside a label-sized outline, in canvas units
+const LABEL_PADDING: f32 = 3.0;
+// Opacity of elements dimmed by the search filter
+const DIMMED_ALPHA: f32 = 0.15;
+
+/// Outline a node is drawn with.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum NodeShapeKind {
+    #[default]
+    Circle,
+    Square,
+    Diamond,
+    Hexagon,
+    RoundedRect, // Sized to fit the label, which is drawn inside
+    Icon,        // Image loaded from a local file
+}
+
+impl NodeShapeKind {
+    pub const ALL: [NodeShapeKind; 6] = [
+        NodeShapeKind::Circle,
+        NodeShapeKind::Square,
+        NodeShapeKind::Diamond,
+        NodeShapeKind::Hexagon,
+        NodeShapeKind::RoundedRect,
+        NodeShapeKind::Icon,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            NodeShapeKind::Circle => tr("shape.circle"),
+            NodeShapeKind::Square => tr("shape.square"),
+            NodeShapeKind::Diamond => tr("shape.diamond"),
+            NodeShapeKind::Hexagon => tr("shape.hexagon"),
+            NodeShapeKind::RoundedRect => tr("shape.rounded_rect"),
+            NodeShapeKind::Icon => tr("shape.icon"),
+        }
+    }
+}
+
+/// Font family used for node and edge labels.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LabelFont {
+    #[default]
+    Monospace,
+    Proportional,
+}
+
+impl LabelFont {
+    pub const ALL: [LabelFont; 2] = [LabelFont::Monospace, LabelFont::Proportional];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            LabelFont::Monospace => tr("font.monospace"),
+            LabelFont::Proportional => tr("font.proportional"),
+        }
+    }
+
+    pub fn family(&self) -> FontFamily {
+        match self {
+            LabelFont::Monospace => FontFamily::Monospace,
+            LabelFont::Proportional => FontFamily::Proportional,
+        }
+    }
+}
+
+/// Per-node drawing parameters. `None` means "use the theme default".
+/// Resolved every frame from the visual mapping, the shape settings and the theme.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct NodeStyle {
+    pub radius: Option<f32>,
+    pub color: Option<Color32>,
+    pub shape: NodeShapeKind,
+    pub icon: Option<TextureId>,
+    pub font_size: Option<f32>,
+    pub label_color: Option<Color32>,
+    pub label_font: LabelFont,
+    pub dimmed: bool, // Set by the search filter
+    pub hidden: bool,
+}
+
+/// Per-edge drawing parameters. `None` means "use the theme default".
+/// Width and colour come from the visual mapping, the rest from `EdgeRenderSettings`.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct EdgeStyle {
+    pub width: Option<f32>,
+    pub color: Option<Color32>,
+    pub routing: EdgeRouting,
+    pub arrow: ArrowStyle,
+    pub dash: DashStyle,
+    pub label_mode: EdgeLabelMode,
+    pub fan_offset: f32, // Signed slot among edges joining the same two nodes, 0 for a lone edge
+    pub bundle: Option<[Pos2; 3]>, // Hierarchy control points when bundling
+    pub bundle_strength: f32,
+    pub font_size: Option<f32>,
+    pub label_color: Option<Color32>,
+    pub label_font: LabelFont,
+    pub dimmed: bool,
+    pub hidden: bool,
+}
+
+/// Style set by hand on individual nodes. Unlike `NodeStyle` it is not recomputed every frame
+/// and wins over the visual mapping and the theme.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NodeOverrides {
+    pub radius: Option<f32>,
+    pub color: Option<Color32>,
+    pub label_color: Option<Color32>,
+}
+
+/// Style set by hand on individual edges, see `NodeOverrides`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EdgeOverrides {
+    pub width: Option<f32>,
+    pub color: Option<Color32>,
+    pub dash: Option<DashStyle>,
+}
+
+/// Copies the per-element overrides into the resolved styles. Runs after every pass that writes styles.
+pub fn apply_style_overrides(g: &mut AppGraph) {
+    fn apply_specific<Ty: EdgeType>(g: &mut AppGraphOf<Ty>) {
+        let nodes: Vec<NodeIndex<DefaultIx>> = g.g.node_indices().collect();
+        for idx in nodes {
+            let Some(node) = g.node_mut(idx) else { continue };
+            let payload = node.payload_mut();
+            let overrides = payload.overrides;
+            let style = &mut payload.style;
+            style.radius = overrides.radius.or(style.radius);
+            style.color = overrides.color.or(style.color);
+            style.label_color = overrides.label_color.or(style.label_color);
+        }
+        let edges: Vec<EdgeIndex<DefaultIx>> = g.g.edge_indices().collect();
+        for idx in edges {
+            let Some(edge) = g.edge_mut(idx) else { continue };
+            let payload = edge.payload_mut();
+            let overrides = payload.overrides;
+            let style = &mut payload.style;
+            style.width = overrides.width.or(style.width);
+            style.color = overrides.color.or(style.color);
+            style.dash = overrides.dash.unwrap_or(style.dash);
+        }
+    }
+    match g {
+        AppGraph::Directed(g) => apply_specific(g),
+        AppGraph::Undirected(g) => apply_specific(g),
+    }
+}
+
+/// Textures for icon nodes, loaded once per path. Failed loads are remembered too
+/// so a bad path doesn't hit the disk every frame.
+#[derive(Default)]
+pub struct IconCache {
+    textures: HashMap<String, Option<TextureHandle>>,
+}
+
+impl IconCache {
+    pub fn get(&mut self, ctx: &Context, path: &str) -> Option<TextureId> {
+        self.textures
+            .entry(path.to_owned())
+            .or_insert_with(|| match load_icon(ctx, path) {
+                Ok(texture) => Some(texture),
+                Err(e) => {
+                    eprintln!("Error loading icon '{}': {}", path, e);
+                    None
+                }
+            })
+            .as_ref()
+            .map(|t| t.id())
+    }
+
+    // Lets the user retry after fixing a file
+    pub fn forget(&mut self, path: &str) {
+        self.textures.remove(path);
+    }
+}
+
+fn load_icon(ctx: &Context, path: &str) -> Result<TextureHandle, image::ImageError> {
+    let rgba = image::open(path)?.to_rgba8();
+    let size = [rgba.width() as usize, rgba.height() as usize];
+    let color_image = ColorImage::from_rgba_unmultiplied(size, rgba.as_raw());
+    Ok(ctx.load_texture(path, color_image, TextureOptions::LINEAR))
+}
+
+/// Global node shape choice, overridden per node by `NodePayload::shape`/`icon_path`.
+pub struct NodeShapeSettings {
+    pub default_shape: NodeShapeKind,
+    pub default_icon_path: String,
+    pub icons: IconCache,
+}
+
+impl Default for NodeShapeSettings {
+    fn default() -> Self {
+        Self { default_shape: NodeShapeKind::Circle, default_icon_path: String::new(), icons: IconCache::default() }
+    }
+}
+
+impl NodeShapeSettings {
+    pub fn apply(&mut self, ctx: &Context, g: &mut AppGraph) {
+        match g {
+            AppGraph::Directed(g) => self.apply_specific(ctx, g),
+            AppGraph::Undirected(g) => self.apply_specific(ctx, g),
+        }
+    }
+
+    fn apply_specific<Ty: EdgeType>(&mut self, ctx: &Context, g: &mut AppGraphOf<Ty>) {
+        let nodes: Vec<_> = g.g.node_indices().collect();
+        for idx in nodes {
+            let Some(node) = g.node_mut(idx) else { continue };
+            let payload = node.payload_mut();
+            let shape = payload.shape.unwrap_or(self.default_shape);
+            let icon_path = payload.icon_path.as_deref().unwrap_or(&self.default_icon_path);
+            let icon = if shape == NodeShapeKind::Icon && !icon_path.is_empty() { self.icons.get(ctx, icon_path) } else { None };
+            payload.style.shape = shape;
+            payload.style.icon = icon;
+        }
+    }
+}
+
+/// Node shape that reads its outline, size and colour from `NodePayload::style`.
+#[derive(Clone)]
+pub struct AppNodeShape {
+    pub pos: Pos2,
+    pub selected: bool,
+    pub dragged: bool,
+    pub hovered: bool,
+    pub label_text: String,
+    pub style: NodeStyle,
+    // Canvas size of the label, measured while drawing. Needed for label-sized outlines.
+    label_size: Vec2,
+}
+
+impl AppNodeShape {
+    pub fn radius(&self) -> f32 {
+        self.style.radius.unwrap_or(DEFAULT_NODE_RADIUS)
+    }
+
+    /// Half extents of the outline's bounding box in canvas units.
+    pub fn half_size(&self) -> Vec2 {
+        let r = self.radius();
+        match self.style.shape {
+            NodeShapeKind::RoundedRect => Vec2::new(
+                (self.label_size.x * 0.5 + LABEL_PADDING).max(r),
+                (self.label_size.y * 0.5 + LABEL_PADDING).max(r),
+            ),
+            _ => Vec2::splat(r),
+        }
+    }
+
+    /// Outline vertices for the polygonal shapes, in canvas coordinates.
+    fn polygon(&self) -> Option<Vec<Pos2>> {
+        let r = self.radius();
+        let c = self.pos;
+        match self.style.shape {
+            NodeShapeKind::Circle => None,
+            NodeShapeKind::Square | NodeShapeKind::Icon | NodeShapeKind::RoundedRect => {
+                let h = self.half_size();
+                Some(vec![c + Vec2::new(-h.x, -h.y), c + Vec2::new(h.x, -h.y), c + Vec2::new(h.x, h.y), c + Vec2::new(-h.x, h.y)])
+            }
+            NodeShapeKind::Diamond => Some(vec![c + Vec2::new(0.0, -r), c + Vec2::new(r, 0.0), c + Vec2::new(0.0, r), c + Vec2::new(-r, 0.0)]),
+            NodeShapeKind::Hexagon => Some(
+                (0..6).map(|i| c + Vec2::angled(i as f32 * std::f32::consts::FRAC_PI_3) * r).collect(),
+            ),
+        }
+    }
+}
+
+// Where a ray from `center` along `dir` leaves a convex polygon
+fn ray_polygon_exit(center: Pos2, dir: Vec2, polygon: &[Pos2]) -> Pos2 {
+    let mut best = f32::MAX;
+    for i in 0..polygon.len() {
+        let a = polygon[i];
+        let b = polygon[(i + 1) % polygon.len()];
+        let edge = b - a;
+        let denom = dir.x * edge.y - dir.y * edge.x;
+        if denom.abs() < f32::EPSILON {
+            continue;
+        }
+        let ac = a - center;
+        let t = (ac.x * edge.y - ac.y * edge.x) / denom;
+        let u = (ac.x * dir.y - ac.y * dir.x) / denom;
+        if t >= 0.0 && (0.0..=1.0).contains(&u) {
+            best = best.min(t);
+        }
+    }
+    if best == f32::MAX { center } else { center + dir * best }
+}
+
+fn point_in_convex_polygon(p: Pos2, polygon: &[Pos2]) -> bool {
+    let mut sign = 0.0;
+    for i in 0..polygon.len() {
+        let a = polygon[i];
+        let b = polygon[(i + 1) % polygon.len()];
+        let cross = (b - a).x * (p - a).y - (b - a).y * (p - a).x;
+        if cross != 0.0 {
+            if sign != 0.0 && cross.signum() != sign {
+                return false;
+            }
+            sign = cross.signum();
+        }
+    }
+    true
+}
+
+impl From<NodeProps<NodePayload>> for AppNodeShape {
+    fn from(props: NodeProps<NodePayload>) -> Self {
+        Self {
+            pos: props.location(),
+            selected: props.selected,
+            dragged: props.dragged,
+            hovered: props.hovered,
+            label_text: props.label.to_string(),
+            style: props.payload.style,
+            label_size: Vec2::ZERO,
+        }
+    }
+}
+
+impl<Ty: EdgeType, Ix: IndexType> DisplayNode<NodePayload, EdgePayload, Ty, Ix> for AppNodeShape {
+    fn closest_boundary_point(&self, dir: Vec2) -> Pos2 {
+        let dir = dir.normalized();
+        match self.polygon() {
+            Some(polygon) => ray_polygon_exit(self.pos, dir, &polygon),
+            None => self.pos + dir * self.radius(),
+        }
+    }
+
+    fn shapes(&mut self, ctx: &DrawContext) -> Vec<Shape> {
+        if self.style.hidden {
+            return Vec::new();
+        }
+        let visuals = &ctx.ctx.style().visuals;
+        let interacted = self.selected || self.dragged;
+        let alpha = if self.style.dimmed { DIMMED_ALPHA } else { 1.0 };
+        let default_color = if interacted { visuals.widgets.active.fg_stroke.color } else { visuals.widgets.inactive.fg_stroke.color };
+        let fill = self.style.color.unwrap_or(default_color).gamma_multiply(alpha);
+        let stroke = if interacted { Stroke::new(2.0, visuals.selection.stroke.color) } else { Stroke::NONE };
+
+        // Label size doesn't follow the radius so mapped sizes don't blow up the text
+        let font_size = ctx.meta.canvas_to_screen_size(self.style.font_size.unwrap_or(DEFAULT_NODE_RADIUS * 2.0));
+        let text_color = if interacted {
+            visuals.widgets.active.fg_stroke.color
+        } else {
+            self.style.label_color.unwrap_or(visuals.text_color()).gamma_multiply(alpha)
+        };
+        let font = FontId::new(font_size, self.style.label_font.family());
+        let galley = ctx.ctx.fonts(|f| f.layout_no_wrap(self.label_text.clone(), font, text_color));
+        self.label_size = galley.size() / ctx.meta.zoom;
+
+        let center = ctx.meta.canvas_to_screen_pos(self.pos);
+        let radius = ctx.meta.canvas_to_screen_size(self.radius());
+        let half = self.half_size() * ctx.meta.zoom;
+        let mut res: Vec<Shape> = Vec::with_capacity(2);
+        match (self.style.shape, self.style.icon) {
+            (NodeShapeKind::Circle, _) => res.push(CircleShape { center, radius, fill, stroke }.into()),
+            (NodeShapeKind::RoundedRect, _) => {
+                let rect = Rect::from_center_size(center, half * 2.0);
+                res.push(Shape::Rect(RectShape::new(rect, half.y.min(half.x) * 0.5, fill, stroke, StrokeKind::Inside)));
+            }
+            (NodeShapeKind::Icon, Some(icon)) => {
+                let rect = Rect::from_center_size(center, half * 2.0);
+                let uv = Rect::from_min_max(Pos2::ZERO, Pos2::new(1.0, 1.0));
+                res.push(Shape::image(icon, rect, uv, Color32::WHITE.gamma_multiply(alpha)));
+                if interacted {
+                    res.push(Shape::rect_stroke(rect, 0.0, stroke, StrokeKind::Outside));
+                }
+            }
+            _ => {
+                let points = self.polygon().unwrap_or_default().into_iter().map(|p| ctx.meta.canvas_to_screen_pos(p)).collect();
+                res.push(Shape::convex_polygon(points, fill, stroke));
+            }
+        }
+
+        if self.style.shape == NodeShapeKind::RoundedRect {
+            // The label lives inside the outline, drawn in a colour that contrasts with the fill
+            let luminance = 0.299 * fill.r() as f32 + 0.587 * fill.g() as f32 + 0.114 * fill.b() as f32;
+            let inside_color = if luminance > 128.0 { Color32::BLACK } else { Color32::WHITE };
+            let label_pos = center - galley.size() * 0.5;
+            res.push(TextShape::new(label_pos, galley, inside_color).into());
+            return res;
+        }
+        if !(ctx.style.labels_always || self.selected || self.dragged || self.hovered) {
+            return res;
+        }
+        let label_pos = Pos2::new(center.x - galley.size().x / 2.0, center.y - half.y - galley.size().y);
+        res.push(TextShape::new(label_pos, galley, text_color).into());
+        res
+    }
+
+    fn update(&mut self, state: &NodeProps<NodePayload>) {
+        self.pos = state.location();
+        self.selected = state.selected;
+        self.dragged = state.dragged;
+        self.hovered = state.hovered;
+        self.label_text = state.label.to_string();
+        self.style = state.payload.style;
+    }
+
+    fn is_inside(&self, pos: Pos2) -> bool {
+        if self.style.hidden {
+            return false;
+        }
+        match self.polygon() {
+            Some(polygon) => point_in_convex_polygon(pos, &polygon),
+            None => (pos - self.pos).length() <= self.radius(),
+        }
+    }
+}
+
+/// How an edge is routed between its endpoints.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum EdgeRouting {
+    Straight,
+    #[default]
+    Curved, // Parallel and reciprocal edges fan out as arcs
+    Orthogonal,
+    Bundled, // Hierarchical edge bundling over a spatial cluster hierarchy
+}
+
+impl EdgeRouting {
+    pub const ALL: [EdgeRouting; 4] = [EdgeRouting::Straight, EdgeRouting::Curved, EdgeRouting::Orthogonal, EdgeRouting::Bundled];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            EdgeRouting::Straight => tr("routing.straight"),
+            EdgeRouting::Curved => tr("routing.curved"),
+            EdgeRouting::Orthogonal => tr("routing.orthogonal"),
+            EdgeRouting::Bundled => tr("routing.bundled"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ArrowStyle {
+    #[default]
+    Filled,
+    Open,
+    Circle,
+    None,
+}
+
+impl ArrowStyle {
+    pub const ALL: [ArrowStyle; 4] = [ArrowStyle::Filled, ArrowStyle::Open, ArrowStyle::Circle, ArrowStyle::None];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ArrowStyle::Filled => tr("arrow.filled"),
+            ArrowStyle::Open => tr("arrow.open"),
+            ArrowStyle::Circle => tr("arrow.circle"),
+            ArrowStyle::None => tr("common.none"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DashStyle {
+    #[default]
+    Solid,
+    Dashed,
+    Dotted,
+}
+
+impl DashStyle {
+    pub const ALL: [DashStyle; 3] = [DashStyle::Solid, DashStyle::Dashed, DashStyle::Dotted];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            DashStyle::Solid => tr("dash.solid"),
+            DashStyle::Dashed => tr("dash.dashed"),
+            DashStyle::Dotted => tr("dash.dotted"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum EdgeLabelMode {
+    #[default]
+    None,
+    Label,
+    Weight,
+}
+
+impl EdgeLabelMode {
+    pub const ALL: [EdgeLabelMode; 3] = [EdgeLabelMode::None, EdgeLabelMode::Label, EdgeLabelMode::Weight];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            EdgeLabelMode::None => tr("edge_label.none"),
+            EdgeLabelMode::Label => tr("edge_label.label"),
+            EdgeLabelMode::Weight => tr("edge_label.weight"),
+        }
+    }
+}
+
+/// Global edge rendering options, resolved into every `EdgeStyle` once per frame.
+pub struct EdgeRenderSettings {
+    pub routing: EdgeRouting,
+    pub arrow: ArrowStyle,
+    pub dash: DashStyle,
+    pub label_mode: EdgeLabelMode,
+    pub bundle_strength: f32, // 0 = straight lines, 1 = follow the hierarchy exactly
+}
+
+impl Default for EdgeRenderSettings {
+    fn default() -> Self {
+        Self {
+            routing: EdgeRouting::Curved,
+            arrow: ArrowStyle::Filled,
+            dash: DashStyle::Solid,
+            label_mode: EdgeLabelMode::None,
+            bundle_strength: 0.8,
+        }
+    }
+}
+
+// Cells per side of the spatial grid used as the first level of the bundling hierarchy
+const BUNDLE_GRID: usize = 3;
+
+impl EdgeRenderSettings {
+    pub fn apply(&self, g: &mut AppGraph) {
+        match g {
+            AppGraph::Directed(g) => self.apply_specific(g),
+            AppGraph::Undirected(g) => self.apply_specific(g),
+        }
+    }
+
+    fn apply_specific<Ty: EdgeType>(&self, g: &mut AppGraphOf<Ty>) {
+        // Fan-out: every edge between the same unordered pair gets a slot, centred on 0.
+        // Offsets are expressed relative to the low->high index direction and flipped for
+        // edges running the other way, so reciprocal edges end up on opposite sides.
+        let mut pairs: HashMap<(usize, usize), Vec<EdgeIndex<DefaultIx>>> = HashMap::new();
+        for edge_idx in g.g.edge_indices() {
+            if let Some((a, b)) = g.g.edge_endpoints(edge_idx) {
+                let key = (a.index().min(b.index()), a.index().max(b.index()));
+                pairs.entry(key).or_default().push(edge_idx);
+            }
+        }
+
+        let hierarchy = (self.routing == EdgeRouting::Bundled).then(|| BundleHierarchy::build(g));
+
+        for (_, edges) in pairs {
+            let count = edges.len();
+            for (slot, edge_idx) in edges.into_iter().enumerate() {
+                let Some((a, b)) = g.g.edge_endpoints(edge_idx) else { continue };
+                let canonical = a.index() <= b.index();
+                let offset = slot as f32 - (count - 1) as f32 * 0.5;
+                let bundle = hierarchy.as_ref().and_then(|h| h.control_points(a, b));
+                let Some(edge) = g.edge_mut(edge_idx) else { continue };
+                let style = &mut edge.payload_mut().style;
+                style.routing = self.routing;
+                style.arrow = self.arrow;
+                style.dash = self.dash;
+                style.label_mode = self.label_mode;
+                style.fan_offset = if canonical { offset } else { -offset };
+                style.bundle = bundle;
+                style.bundle_strength = self.bundle_strength;
+            }
+        }
+    }
+}
+
+/// Two-level hierarchy for edge bundling: nodes -> grid cell centroid -> graph centroid.
+struct BundleHierarchy {
+    root: Pos2,
+    cell_of: HashMap<NodeIndex<DefaultIx>, usize>,
+    cell_centroids: Vec<Pos2>,
+}
+
+impl BundleHierarchy {
+    fn build<Ty: EdgeType>(g: &AppGraphOf<Ty>) -> Self {
+        let positions: Vec<(NodeIndex<DefaultIx>, Pos2)> = g.g.node_indices().filter_map(|i| g.node(i).map(|n| (i, n.location()))).collect();
+        let bounds = positions.iter().fold(Rect::NOTHING, |r, (_, p)| r.union(Rect::from_min_max(*p, *p)));
+        let root = if positions.is_empty() { Pos2::ZERO } else { bounds.center() };
+
+        let mut sums = vec![(Vec2::ZERO, 0usize); BUNDLE_GRID * BUNDLE_GRID];
+        let mut cell_of = HashMap::new();
+        for (idx, p) in &positions {
+            let cell_coord = |v: f32, min: f32, size: f32| {
+                if size <= f32::EPSILON { 0 } else { (((v - min) / size * BUNDLE_GRID as f32) as usize).min(BUNDLE_GRID - 1) }
+            };
+            let cx = cell_coord(p.x, bounds.left(), bounds.width());
+            let cy = cell_coord(p.y, bounds.top(), bounds.height());
+            let cell = cy * BUNDLE_GRID + cx;
+            sums[cell].0 += p.to_vec2();
+            sums[cell].1 += 1;
+            cell_of.insert(*idx, cell);
+        }
+        let cell_centroids = sums.into_iter().map(|(sum, n)| if n > 0 { (sum / n as f32).to_pos2() } else { root }).collect();
+        Self { root, cell_of, cell_centroids }
+    }
+
+    // Path through the hierarchy; edges inside one cell only go up to the cell centroid
+    fn control_points(&self, a: NodeIndex<DefaultIx>, b: NodeIndex<DefaultIx>) -> Option<[Pos2; 3]> {
+        let (ca, cb) = (*self.cell_of.get(&a)?, *self.cell_of.get(&b)?);
+        let (pa, pb) = (self.cell_centroids[ca], self.cell_centroids[cb]);
+        if ca == cb {
+            Some([pa, pa, pa])
+        } else {
+            Some([pa, self.root, pb])
+        }
+    }
+}
+
+// Distance between fanned-out parallel edges at their midpoint, in canvas units
+const FAN_SPACING: f32 = CURVE_SIZE;
+// Chaikin subdivision rounds used to smooth bundled edges
+const SMOOTHING_ROUNDS: usize = 3;
+
+/// Edge shape that reads its width, colour and routing from `EdgePayload::style`.
+#[derive(Clone)]
+pub struct AppEdgeShape {
+    pub order: usize,
+    pub selected: bool,
+    pub label_text: String,
+    pub weight: f32,
+    pub style: EdgeStyle,
+}
+
+impl From<EdgeProps<EdgePayload>> for AppEdgeShape {
+    fn from(props: EdgeProps<EdgePayload>) -> Self {
+        Self {
+            order: props.order,
+            selected: props.selected,
+            label_text: props.payload.label.clone(),
+            weight: props.payload.weight,
+            style: props.payload.style,
+        }
+    }
+}
+
+impl AppEdgeShape {
+    pub fn width(&self) -> f32 {
+        self.style.width.unwrap_or(DEFAULT_EDGE_WIDTH)
+    }
+
+    /// Polyline of the edge in canvas coordinates, from the start node outline to the end node outline.
+    fn canvas_path<Ty: EdgeType, Ix: IndexType>(
+        &self,
+        start: &Node<NodePayload, EdgePayload, Ty, Ix, AppNodeShape>,
+        end: &Node<NodePayload, EdgePayload, Ty, Ix, AppNodeShape>,
+    ) -> Vec<Pos2> {
+        if start.id() == end.id() {
+            // Self-loop: a circle sitting on top of the node
+            let node = start.display();
+            let radius = node.radius() * (LOOP_SIZE + self.order as f32);
+            let center = node.pos - Vec2::new(0.0, radius);
+            return (0..=CURVE_SEGMENTS * 2)
+                .map(|i| {
+                    let angle = i as f32 / (CURVE_SEGMENTS * 2) as f32 * std::f32::consts::TAU + std::f32::consts::FRAC_PI_2;
+                    center + Vec2::angled(angle) * radius
+                })
+                .collect();
+        }
+
+        let (a, b) = (start.location(), end.location());
+        let dir = (b - a).normalized();
+        let normal = Vec2::new(-dir.y, dir.x);
+        match self.style.routing {
+            EdgeRouting::Straight => vec![start.display().closest_boundary_point(dir), end.display().closest_boundary_point(-dir)],
+            EdgeRouting::Curved => {
+                if self.style.fan_offset == 0.0 {
+                    return vec![start.display().closest_boundary_point(dir), end.display().closest_boundary_point(-dir)];
+                }
+                // The apex of a quadratic curve sits halfway to its control point
+                let control = a + (b - a) * 0.5 + normal * FAN_SPACING * self.style.fan_offset * 2.0;
+                let from = start.display().closest_boundary_point(control - a);
+                let to = end.display().closest_boundary_point(control - b);
+                quadratic_bezier(from, control, to)
+            }
+            EdgeRouting::Orthogonal => {
+                // Horizontal - vertical - horizontal, parallel edges get their own vertical channel
+                let mid_x = (a.x + b.x) * 0.5 + FAN_SPACING * self.style.fan_offset;
+                let sign = |v: f32| if v >= 0.0 { 1.0 } else { -1.0 };
+                let from = start.display().closest_boundary_point(Vec2::new(sign(mid_x - a.x), 0.0));
+                let to = end.display().closest_boundary_point(Vec2::new(sign(mid_x - b.x), 0.0));
+                vec![from, Pos2::new(mid_x, from.y), Pos2::new(mid_x, to.y), to]
+            }
+            EdgeRouting::Bundled => {
+                let Some(points) = self.style.bundle else {
+                    return vec![start.display().closest_boundary_point(dir), end.display().closest_boundary_point(-dir)];
+                };
+                // Holten's straightening: blend the hierarchy path with the straight line
+                let beta = self.style.bundle_strength.clamp(0.0, 1.0);
+                let mut control: Vec<Pos2> = vec![a];
+                for (i, p) in points.iter().enumerate() {
+                    let t = (i + 1) as f32 / (points.len() + 1) as f32;
+                    control.push(a.lerp(b, t).lerp(*p, beta));
+                }
+                control.push(b);
+                let mut path = chaikin(control, SMOOTHING_ROUNDS);
+                let last = path.len() - 1;
+                path[0] = start.display().closest_boundary_point(path[1] - a);
+                path[last] = end.display().closest_boundary_point(path[last - 1] - b);
+                path
+            }
+        }
+    }
+}
+
+fn quadratic_bezier(from: Pos2, control: Pos2, to: Pos2) -> Vec<Pos2> {
+    (0..=CURVE_SEGMENTS)
+        .map(|i| {
+            let t = i as f32 / CURVE_SEGMENTS as f32;
+            let a = from.lerp(control, t);
+            let b = control.lerp(to, t);
+            a.lerp(b, t)
+        })
+        .collect()
+}
+
+// Corner cutting; converges to a quadratic B-spline and keeps both end points
+fn chaikin(mut points: Vec<Pos2>, rounds: usize) -> Vec<Pos2> {
+    for _ in 0..rounds {
+        if points.len() < 3 {
+            break;
+        }
+        let mut next = Vec::with_capacity(points.len() * 2);
+        next.push(points[0]);
+        for w in points.windows(2) {
+            next.push(w[0].lerp(w[1], 0.25));
+            next.push(w[0].lerp(w[1], 0.75));
+        }
+        next.push(points[points.len() - 1]);
+        points = next;
+    }
+    points
+}
+
+fn distance_to_segment(p: Pos2, a: Pos2, b: Pos2) -> f32 {
+    let ab = b - a;
+    let t = if ab.length_sq() > 0.0 { ((p - a).dot(ab) / ab.length_sq()).clamp(0.0, 1.0) } else { 0.0 };
+    (p - (a + ab * t)).length()
+}
+
+// Point halfway along a polyline, measured by length
+fn polyline_midpoint(points: &[Pos2]) -> Pos2 {
+    let total: f32 = points.windows(2).map(|w| (w[1] - w[0]).length()).sum();
+    let mut remaining = total * 0.5;
+    for w in points.windows(2) {
+        let len = (w[1] - w[0]).length();
+        if len >= remaining && len > 0.0 {
+            return w[0].lerp(w[1], remaining / len);
+        }
+        remaining -= len;
+    }
+    points[0]
+}
+
+fn arrow_tip(style: ArrowStyle, tip: Pos2, dir: Vec2, color: Color32, width: f32) -> Option<Shape> {
+    let back = -dir * TIP_SIZE;
+    let left = tip + egui::emath::Rot2::from_angle(TIP_ANGLE) * back;
+    let right = tip + egui::emath::Rot2::from_angle(-TIP_ANGLE) * back;
+    match style {
+        ArrowStyle::Filled => Some(Shape::convex_polygon(vec![tip, left, right], color, Stroke::NONE)),
+        ArrowStyle::Open => Some(Shape::line(vec![left, tip, right], Stroke::new(width, color))),
+        ArrowStyle::Circle => Some(Shape::circle_filled(tip - dir * TIP_SIZE * 0.3, TIP_SIZE * 0.3, color)),
+        ArrowStyle::None => None,
+    }
+}
+
+impl<Ty: EdgeType, Ix: IndexType> DisplayEdge<NodePayload, EdgePayload, Ty, Ix, AppNodeShape> for AppEdgeShape {
+    fn shapes(
+        &mut self,
+        start: &Node<NodePayload, EdgePayload, Ty, Ix, AppNodeShape>,
+        end: &Node<NodePayload, EdgePayload, Ty, Ix, AppNodeShape>,
+        ctx: &DrawContext,
+    ) -> Vec<Shape> {
+        if self.style.hidden {
+            return Vec::new();
+        }
+        let visuals = &ctx.ctx.style().visuals;
+        let alpha = if self.style.dimmed { DIMMED_ALPHA } else { 1.0 };
+        let color = if self.selected {
+            visuals.selection.stroke.color
+        } else {
+            self.style.color.unwrap_or(visuals.widgets.inactive.fg_stroke.color).gamma_multiply(alpha)
+        };
+        let width = ctx.meta.canvas_to_screen_size(self.width());
+        let points: Vec<Pos2> = self.canvas_path(start, end).into_iter().map(|p| ctx.meta.canvas_to_screen_pos(p)).collect();
+        if points.len() < 2 {
+            return Vec::new();
+        }
+
+        let stroke = Stroke::new(width, color);
+        let mut res = match self.style.dash {
+            DashStyle::Solid => vec![Shape::line(points.clone(), stroke)],
+            DashStyle::Dashed => Shape::dashed_line(&points, stroke, width * 4.0 + 4.0, width * 2.0 + 3.0),
+            DashStyle::Dotted => Shape::dotted_line(&points, color, width * 2.0 + 3.0, width * 0.6),
+        };
+        if ctx.is_directed && start.id() != end.id() {
+            let tip = points[points.len() - 1];
+            let dir = (tip - points[points.len() - 2]).normalized();
+            res.extend(arrow_tip(self.style.arrow, tip, dir, color, width));
+        }
+
+        let text = match self.style.label_mode {
+            EdgeLabelMode::None => None,
+            EdgeLabelMode::Label => Some(self.label_text.clone()),
+            EdgeLabelMode::Weight => Some(format!("{:.2}", self.weight)),
+        };
+        if let Some(text) = text.filter(|t| !t.is_empty()) {
+            let font_size = ctx.meta.canvas_to_screen_size(self.style.font_size.unwrap_or(DEFAULT_NODE_RADIUS * 2.0));
+            let text_color = self.style.label_color.unwrap_or(visuals.text_color()).gamma_multiply(alpha);
+            let galley = ctx.ctx.fonts(|f| f.layout_no_wrap(text, FontId::new(font_size, self.style.label_font.family()), text_color));
+            let mid = polyline_midpoint(&points);
+            let rect = Rect::from_center_size(mid, galley.size() + Vec2::splat(2.0));
+            res.push(Shape::rect_filled(rect, 2.0, visuals.extreme_bg_color.gamma_multiply(0.8)));
+            res.push(TextShape::new(rect.min + Vec2::splat(1.0), galley, text_color).into());
+        }
+        res
+    }
+
+    fn update(&mut self, state: &EdgeProps<EdgePayload>) {
+        self.order = state.order;
+        self.selected = state.selected;
+        self.label_text = state.payload.label.clone();
+        self.weight = state.payload.weight;
+        self.style = state.payload.style;
+    }
+
+    fn is_inside(
+        &self,
+        start: &Node<NodePayload, EdgePayload, Ty, Ix, AppNodeShape>,
+        end: &Node<NodePayload, EdgePayload, Ty, Ix, AppNodeShape>,
+        pos: Pos2,
+    ) -> bool {
+        if self.style.hidden {
+            return false;
+        }
+        let tolerance = self.width() * 0.5 + EDGE_HIT_TOLERANCE;
+        self.canvas_path(start, end).windows(2).any(|w| distance_to_segment(pos, w[0], w[1]) <= tolerance)
+    }
+}
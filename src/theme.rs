@@ -0,0 +1,201 @@
+use std::fs;
+use egui::{Color32, Context, Visuals};
+use petgraph::EdgeType;
+use serde::{Deserialize, Serialize};
+use crate::app::{AppGraph, AppGraphOf};
+use crate::shapes::{LabelFont, DEFAULT_NODE_RADIUS};
+use crate::i18n::{tr, trf};
+
+/// Key of the theme settings in eframe storage.
+pub const THEME_STORAGE_KEY: &str = "graph_theme";
+
+/// Colours and label font of the canvas. Also what a style sheet file contains.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Theme {
+    pub dark_mode: bool, // Base egui visuals for the panels around the canvas
+    pub background: Color32,
+    pub node_color: Color32,
+    pub edge_color: Color32,
+    pub selection_fill: Color32,
+    pub selection_stroke: Color32,
+    pub label_color: Color32,
+    pub label_font: LabelFont,
+    pub label_size: f32, // Canvas units, scales with zoom
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::dark()
+    }
+}
+
+impl Theme {
+    pub fn light() -> Self {
+        Self {
+            dark_mode: false,
+            background: Color32::from_rgb(248, 248, 248),
+            node_color: Color32::from_rgb(70, 70, 70),
+            edge_color: Color32::from_rgb(130, 130, 130),
+            selection_fill: Color32::from_rgb(144, 209, 255),
+            selection_stroke: Color32::from_rgb(0, 83, 125),
+            label_color: Color32::from_rgb(40, 40, 40),
+            label_font: LabelFont::Monospace,
+            label_size: DEFAULT_NODE_RADIUS * 2.0,
+        }
+    }
+
+    pub fn dark() -> Self {
+        Self {
+            dark_mode: true,
+            background: Color32::from_rgb(27, 27, 27),
+            node_color: Color32::from_rgb(180, 180, 180),
+            edge_color: Color32::from_rgb(140, 140, 140),
+            selection_fill: Color32::from_rgb(0, 92, 128),
+            selection_stroke: Color32::from_rgb(192, 222, 255),
+            label_color: Color32::from_rgb(210, 210, 210),
+            label_font: LabelFont::Monospace,
+            label_size: DEFAULT_NODE_RADIUS * 2.0,
+        }
+    }
+
+    pub fn high_contrast() -> Self {
+        Self {
+            dark_mode: true,
+            background: Color32::BLACK,
+            node_color: Color32::WHITE,
+            edge_color: Color32::YELLOW,
+            selection_fill: Color32::from_rgb(0, 120, 255),
+            selection_stroke: Color32::from_rgb(0, 255, 255),
+            label_color: Color32::WHITE,
+            label_font: LabelFont::Proportional,
+            label_size: DEFAULT_NODE_RADIUS * 3.0,
+        }
+    }
+
+    pub fn load(path: &str) -> Result<Self, String> {
+        let text = fs::read_to_string(path).map_err(|e| e.to_string())?;
+        serde_json::from_str(&text).map_err(|e| e.to_string())
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), String> {
+        let text = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        fs::write(path, text).map_err(|e| e.to_string())
+    }
+
+    fn visuals(&self) -> Visuals {
+        let mut visuals = if self.dark_mode { Visuals::dark() } else { Visuals::light() };
+        visuals.selection.bg_fill = self.selection_fill;
+        visuals.selection.stroke.color = self.selection_stroke;
+        visuals
+    }
+
+    // Fills in whatever the visual mapping left at the default
+    fn apply_specific<Ty: EdgeType>(&self, g: &mut AppGraphOf<Ty>) {
+        let nodes: Vec<_> = g.g.node_indices().collect();
+        for idx in nodes {
+            let Some(node) = g.node_mut(idx) else { continue };
+            let style = &mut node.payload_mut().style;
+            style.color = Some(style.color.unwrap_or(self.node_color));
+            style.font_size = Some(self.label_size);
+            style.label_color = Some(self.label_color);
+            style.label_font = self.label_font;
+        }
+        let edges: Vec<_> = g.g.edge_indices().collect();
+        for idx in edges {
+            let Some(edge) = g.edge_mut(idx) else { continue };
+            let style = &mut edge.payload_mut().style;
+            style.color = Some(style.color.unwrap_or(self.edge_color));
+            style.font_size = Some(self.label_size);
+            style.label_color = Some(self.label_color);
+            style.label_font = self.label_font;
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ThemePreset {
+    Light,
+    #[default]
+    Dark,
+    HighContrast,
+    Custom,
+}
+
+impl ThemePreset {
+    pub const ALL: [ThemePreset; 4] = [ThemePreset::Light, ThemePreset::Dark, ThemePreset::HighContrast, ThemePreset::Custom];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ThemePreset::Light => tr("theme.light"),
+            ThemePreset::Dark => tr("theme.dark"),
+            ThemePreset::HighContrast => tr("theme.high_contrast"),
+            ThemePreset::Custom => tr("theme.custom"),
+        }
+    }
+}
+
+/// Theme choice plus the user's custom style sheet. Persisted through eframe storage.
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ThemeSettings {
+    pub preset: ThemePreset,
+    pub custom: Theme,
+    pub style_sheet_path: String,
+    #[serde(skip)]
+    pub status: Option<String>, // Result of the last style sheet load/save
+    #[serde(skip)]
+    applied_visuals: Option<Theme>,
+}
+
+impl ThemeSettings {
+    pub fn active(&self) -> Theme {
+        match self.preset {
+            ThemePreset::Light => Theme::light(),
+            ThemePreset::Dark => Theme::dark(),
+            ThemePreset::HighContrast => Theme::high_contrast(),
+            ThemePreset::Custom => self.custom,
+        }
+    }
+
+    /// Editable theme. Editing a preset starts a custom theme from it.
+    pub fn customize(&mut self) -> &mut Theme {
+        if self.preset != ThemePreset::Custom {
+            self.custom = self.active();
+            self.preset = ThemePreset::Custom;
+        }
+        &mut self.custom
+    }
+
+    pub fn save_style_sheet(&mut self) {
+        let result = self.active().save(&self.style_sheet_path);
+        self.status = Some(match result {
+            Ok(()) => trf("theme.saved", &[&self.style_sheet_path]),
+            Err(e) => trf("theme.save_failed", &[&e]),
+        });
+    }
+
+    pub fn load_style_sheet(&mut self) {
+        self.status = Some(match Theme::load(&self.style_sheet_path) {
+            Ok(theme) => {
+                self.custom = theme;
+                self.preset = ThemePreset::Custom;
+                trf("theme.loaded", &[&self.style_sheet_path])
+            }
+            Err(e) => trf("theme.load_failed", &[&e]),
+        });
+    }
+
+    pub fn apply(&mut self, ctx: &Context, g: &mut AppGraph) {
+        let theme = self.active();
+        // Visuals are only pushed to egui when the theme actually changed
+        if self.applied_visuals != Some(theme) {
+            ctx.set_visuals(theme.visuals());
+            self.applied_visuals = Some(theme);
+        }
+        match g {
+            AppGraph::Directed(g) => theme.apply_specific(g),
+            AppGraph::Undirected(g) => theme.apply_specific(g),
+        }
+    }
+}
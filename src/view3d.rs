@@ -0,0 +1,136 @@
+use std::collections::HashMap;
+use egui::{Color32, Pos2, Rect, Sense, Stroke, Ui, Vec2};
+use fdg::nalgebra::{Point3, Rotation3, Vector3};
+use petgraph::stable_graph::{DefaultIx, NodeIndex};
+use crate::app::BasicApp;
+
+const NODE_RADIUS: f32 = 5.0;
+// Depth cueing never fades elements below this fraction of full opacity
+const MIN_DEPTH_ALPHA: f32 = 0.25;
+
+/// Orbit camera looking at the origin of the 3D simulation.
+pub struct OrbitCamera {
+    pub yaw: f32,
+    pub pitch: f32,
+    pub distance: f32, // Distance from the target, controls the perspective strength
+    pub zoom: f32,
+    pub depth_cueing: bool,
+}
+
+impl Default for OrbitCamera {
+    fn default() -> Self {
+        Self { yaw: 0.0, pitch: 0.0, distance: 800.0, zoom: 1.0, depth_cueing: true }
+    }
+}
+
+impl OrbitCamera {
+    fn rotation(&self) -> Rotation3<f32> {
+        Rotation3::from_axis_angle(&Vector3::x_axis(), self.pitch) * Rotation3::from_axis_angle(&Vector3::y_axis(), self.yaw)
+    }
+
+    /// Point in camera space: x right, y down, z away from the viewer.
+    pub fn to_camera_space(&self, p: &Point3<f32>) -> Vector3<f32> {
+        self.rotation() * p.coords
+    }
+
+    /// Screen position and perspective scale, or `None` when the point is behind the camera.
+    pub fn project(&self, p: &Point3<f32>, viewport: Rect) -> Option<(Pos2, f32, f32)> {
+        let cam = self.to_camera_space(p);
+        let denom = self.distance + cam.z;
+        if denom <= 1.0 {
+            return None;
+        }
+        let scale = self.distance / denom * self.zoom;
+        let screen = viewport.center() + Vec2::new(cam.x, cam.y) * scale;
+        Some((screen, scale, cam.z))
+    }
+}
+
+fn fade(color: Color32, alpha: f32) -> Color32 {
+    color.gamma_multiply(alpha.clamp(MIN_DEPTH_ALPHA, 1.0))
+}
+
+pub fn draw_3d_view(app: &mut BasicApp, ui: &mut Ui) {
+    let (rect, response) = ui.allocate_exact_size(ui.available_size(), Sense::click_and_drag());
+
+    // Left drag orbits, scroll zooms
+    if response.dragged() {
+        let delta = response.drag_delta();
+        app.camera.yaw += delta.x * 0.01;
+        app.camera.pitch = (app.camera.pitch - delta.y * 0.01).clamp(-1.5, 1.5);
+    }
+    if response.hovered() {
+        let scroll = ui.input(|i| i.smooth_scroll_delta.y);
+        if scroll != 0.0 {
+            app.camera.zoom = (app.camera.zoom * (1.0 + scroll * 0.001)).clamp(0.05, 20.0);
+        }
+    }
+
+    let painter = ui.painter_at(rect);
+    let theme = app.theme.active();
+    let node_color = theme.node_color;
+    let selected_color = theme.selection_fill;
+    let edge_color = theme.edge_color;
+    let text_color = theme.label_color;
+
+    let mut projected: Vec<(NodeIndex<DefaultIx>, Pos2, f32, f32)> = app
+        .sim_3d
+        .node_indices()
+        .filter_map(|idx| app.camera.project(&app.sim_3d[idx].1, rect).map(|(pos, scale, depth)| (idx, pos, scale, depth)))
+        .collect();
+    if projected.is_empty() {
+        return;
+    }
+    let (near, far) = projected.iter().fold((f32::MAX, f32::MIN), |(lo, hi), (_, _, _, d)| (lo.min(*d), hi.max(*d)));
+    let depth_alpha = |depth: f32| {
+        if !app.camera.depth_cueing || far - near < f32::EPSILON {
+            1.0
+        } else {
+            1.0 - (depth - near) / (far - near) * (1.0 - MIN_DEPTH_ALPHA)
+        }
+    };
+
+    let screen_of: HashMap<NodeIndex<DefaultIx>, (Pos2, f32)> = projected.iter().map(|(idx, pos, _, depth)| (*idx, (*pos, *depth))).collect();
+    // Edges sit at the mean depth of their ends
+    let mut edges: Vec<(Pos2, Pos2, f32)> = app
+        .sim_3d
+        .edge_indices()
+        .filter_map(|edge_idx| {
+            let (a, b) = app.sim_3d.edge_endpoints(edge_idx)?;
+            let (&(pa, da), &(pb, db)) = (screen_of.get(&a)?, screen_of.get(&b)?);
+            Some((pa, pb, (da + db) * 0.5))
+        })
+        .collect();
+
+    // Painter's algorithm: far edges and nodes first so near ones are drawn on top
+    edges.sort_by(|a, b| b.2.total_cmp(&a.2));
+    projected.sort_by(|a, b| b.3.total_cmp(&a.3));
+    let mut edges = edges.into_iter().peekable();
+    let mut draw_edges_behind = |depth: f32| {
+        while let Some((pa, pb, edge_depth)) = edges.next_if(|(_, _, d)| *d >= depth) {
+            painter.line_segment([pa, pb], Stroke::new(1.0, fade(edge_color, depth_alpha(edge_depth))));
+        }
+    };
+    // The selection lives in the egui graph; compare by id, not by index
+    let selected = app.selected_node_ids();
+    for (idx, pos, scale, depth) in &projected {
+        draw_edges_behind(*depth);
+        let alpha = depth_alpha(*depth);
+        let base = if selected.contains(&app.sim_3d[*idx].0.id) { selected_color } else { node_color };
+        let radius = (NODE_RADIUS * scale).max(1.5);
+        painter.circle_filled(*pos, radius, fade(base, alpha));
+        if app.sim_3d[*idx].0.pinned {
+            painter.circle_stroke(*pos, radius + 2.0, Stroke::new(1.5, fade(Color32::from_rgb(220, 60, 60), alpha)));
+        }
+        if app.style_labels_always {
+            painter.text(
+                *pos - Vec2::new(0.0, radius + 2.0),
+                egui::Align2::CENTER_BOTTOM,
+                &app.sim_3d[*idx].0.label,
+                egui::FontId::new(theme.label_size * scale, theme.label_font.family()),
+                fade(text_color, alpha),
+            );
+        }
+    }
+    draw_edges_behind(f32::MIN);
+}
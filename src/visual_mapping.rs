@@ -0,0 +1,359 @@
+use egui::{Color32, Pos2, Rect, Stroke, Ui, Vec2};
+use petgraph::EdgeType;
+use petgraph::stable_graph::{DefaultIx, EdgeIndex, NodeIndex};
+use crate::app::{AppGraph, AppGraphOf};
+use crate::attributes::AttrValue;
+use crate::i18n::tr;
+
+/// Numeric value a node visual channel can be bound to.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum NodeAttribute {
+    None,
+    Weight,
+    Degree,
+    Custom(String), // Key in the payload's attribute map
+}
+
+impl NodeAttribute {
+    pub const ALL: [NodeAttribute; 3] = [NodeAttribute::None, NodeAttribute::Weight, NodeAttribute::Degree];
+
+    pub fn label(&self) -> &str {
+        match self {
+            NodeAttribute::None => tr("common.none"),
+            NodeAttribute::Weight => tr("attr.weight"),
+            NodeAttribute::Degree => tr("attr.degree"),
+            NodeAttribute::Custom(key) => key,
+        }
+    }
+}
+
+/// Numeric value an edge visual channel can be bound to.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum EdgeAttribute {
+    None,
+    Weight,
+    Custom(String),
+}
+
+impl EdgeAttribute {
+    pub const ALL: [EdgeAttribute; 2] = [EdgeAttribute::None, EdgeAttribute::Weight];
+
+    pub fn label(&self) -> &str {
+        match self {
+            EdgeAttribute::None => tr("common.none"),
+            EdgeAttribute::Weight => tr("attr.weight"),
+            EdgeAttribute::Custom(key) => key,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ColorRamp {
+    BlueRed,
+    GreenYellow,
+    Grayscale,
+    Viridis,
+}
+
+impl ColorRamp {
+    pub const ALL: [ColorRamp; 4] = [ColorRamp::BlueRed, ColorRamp::GreenYellow, ColorRamp::Grayscale, ColorRamp::Viridis];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ColorRamp::BlueRed => tr("ramp.blue_red"),
+            ColorRamp::GreenYellow => tr("ramp.green_yellow"),
+            ColorRamp::Grayscale => tr("ramp.grayscale"),
+            ColorRamp::Viridis => "Viridis",
+        }
+    }
+
+    fn stops(&self) -> &'static [Color32] {
+        const BLUE_RED: [Color32; 3] = [Color32::from_rgb(50, 100, 220), Color32::from_rgb(230, 230, 230), Color32::from_rgb(220, 50, 50)];
+        const GREEN_YELLOW: [Color32; 2] = [Color32::from_rgb(40, 160, 80), Color32::from_rgb(250, 220, 40)];
+        const GRAYSCALE: [Color32; 2] = [Color32::from_gray(60), Color32::from_gray(230)];
+        const VIRIDIS: [Color32; 5] = [
+            Color32::from_rgb(68, 1, 84),
+            Color32::from_rgb(59, 82, 139),
+            Color32::from_rgb(33, 145, 140),
+            Color32::from_rgb(94, 201, 98),
+            Color32::from_rgb(253, 231, 37),
+        ];
+        match self {
+            ColorRamp::BlueRed => &BLUE_RED,
+            ColorRamp::GreenYellow => &GREEN_YELLOW,
+            ColorRamp::Grayscale => &GRAYSCALE,
+            ColorRamp::Viridis => &VIRIDIS,
+        }
+    }
+
+    /// Colour at `t` in [0, 1], linearly interpolated between the stops.
+    pub fn sample(&self, t: f32) -> Color32 {
+        let stops = self.stops();
+        let scaled = t.clamp(0.0, 1.0) * (stops.len() - 1) as f32;
+        let i = (scaled.floor() as usize).min(stops.len() - 2);
+        let local = scaled - i as f32;
+        let (a, b) = (stops[i], stops[i + 1]);
+        let mix = |x: u8, y: u8| (x as f32 + (y as f32 - x as f32) * local).round() as u8;
+        Color32::from_rgb(mix(a.r(), b.r()), mix(a.g(), b.g()), mix(a.b(), b.b()))
+    }
+}
+
+/// Value range seen in the data during the last `apply`, used for normalisation and the legend.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ValueRange {
+    pub min: f32,
+    pub max: f32,
+}
+
+impl ValueRange {
+    fn from_values(values: impl Iterator<Item = f32>) -> Option<Self> {
+        values.fold(None, |acc: Option<ValueRange>, v| match acc {
+            None => Some(ValueRange { min: v, max: v }),
+            Some(r) => Some(ValueRange { min: r.min.min(v), max: r.max.max(v) }),
+        })
+    }
+
+    fn normalize(&self, v: f32) -> f32 {
+        if self.max - self.min > f32::EPSILON { (v - self.min) / (self.max - self.min) } else { 0.5 }
+    }
+}
+
+/// Binds node/edge attributes to visual channels and writes the result into the payload styles.
+pub struct VisualMapping {
+    pub node_size_attr: NodeAttribute,
+    pub node_size_range: (f32, f32),
+    pub node_color_attr: NodeAttribute,
+    pub node_ramp: ColorRamp,
+    pub edge_width_attr: EdgeAttribute,
+    pub edge_width_range: (f32, f32),
+    pub edge_opacity_attr: EdgeAttribute,
+    pub edge_opacity_range: (f32, f32),
+    pub edge_color_attr: EdgeAttribute,
+    pub edge_ramp: ColorRamp,
+    pub show_legend: bool,
+
+    node_size_values: Option<ValueRange>,
+    node_color_values: Option<ValueRange>,
+    edge_width_values: Option<ValueRange>,
+    edge_opacity_values: Option<ValueRange>,
+    edge_color_values: Option<ValueRange>,
+}
+
+impl Default for VisualMapping {
+    fn default() -> Self {
+        Self {
+            node_size_attr: NodeAttribute::None,
+            node_size_range: (3.0, 15.0),
+            node_color_attr: NodeAttribute::None,
+            node_ramp: ColorRamp::Viridis,
+            edge_width_attr: EdgeAttribute::None,
+            edge_width_range: (1.0, 6.0),
+            edge_opacity_attr: EdgeAttribute::None,
+            edge_opacity_range: (0.2, 1.0),
+            edge_color_attr: EdgeAttribute::None,
+            edge_ramp: ColorRamp::BlueRed,
+            show_legend: true,
+            node_size_values: None,
+            node_color_values: None,
+            edge_width_values: None,
+            edge_opacity_values: None,
+            edge_color_values: None,
+        }
+    }
+}
+
+fn node_attribute<'a, Ty: EdgeType>(g: &'a AppGraphOf<Ty>, idx: NodeIndex<DefaultIx>, attr: &NodeAttribute) -> Option<&'a AttrValue> {
+    match attr {
+        NodeAttribute::Custom(key) => g.node(idx)?.payload().attributes.get(key),
+        _ => None,
+    }
+}
+
+fn edge_attribute<'a, Ty: EdgeType>(g: &'a AppGraphOf<Ty>, idx: EdgeIndex<DefaultIx>, attr: &EdgeAttribute) -> Option<&'a AttrValue> {
+    match attr {
+        EdgeAttribute::Custom(key) => g.edge(idx)?.payload().attributes.get(key),
+        _ => None,
+    }
+}
+
+fn node_value<Ty: EdgeType>(g: &AppGraphOf<Ty>, idx: NodeIndex<DefaultIx>, attr: &NodeAttribute) -> Option<f32> {
+    match attr {
+        NodeAttribute::None => None,
+        NodeAttribute::Weight => g.node(idx).map(|n| n.payload().weight),
+        NodeAttribute::Degree => Some(g.g.neighbors_undirected(idx).count() as f32),
+        NodeAttribute::Custom(_) => node_attribute(g, idx, attr)?.as_number().map(|v| v as f32),
+    }
+}
+
+fn edge_value<Ty: EdgeType>(g: &AppGraphOf<Ty>, idx: EdgeIndex<DefaultIx>, attr: &EdgeAttribute) -> Option<f32> {
+    match attr {
+        EdgeAttribute::None => None,
+        EdgeAttribute::Weight => g.edge(idx).map(|e| e.payload().weight),
+        EdgeAttribute::Custom(_) => edge_attribute(g, idx, attr)?.as_number().map(|v| v as f32),
+    }
+}
+
+// Colour attributes bound to a colour channel are drawn as they are, without the ramp
+fn literal_color(value: Option<&AttrValue>) -> Option<Color32> {
+    match value? {
+        AttrValue::Color(color) => Some(*color),
+        _ => None,
+    }
+}
+
+fn lerp(range: (f32, f32), t: f32) -> f32 {
+    range.0 + (range.1 - range.0) * t
+}
+
+impl VisualMapping {
+    /// Recomputes every mapped style. Cheap enough to run once per frame, which keeps
+    /// the visuals in sync with weight edits without any change tracking.
+    pub fn apply(&mut self, g: &mut AppGraph) {
+        match g {
+            AppGraph::Directed(g) => self.apply_specific(g),
+            AppGraph::Undirected(g) => self.apply_specific(g),
+        }
+    }
+
+    fn apply_specific<Ty: EdgeType>(&mut self, g: &mut AppGraphOf<Ty>) {
+        let nodes: Vec<NodeIndex<DefaultIx>> = g.g.node_indices().collect();
+        let edges: Vec<EdgeIndex<DefaultIx>> = g.g.edge_indices().collect();
+
+        self.node_size_values = ValueRange::from_values(nodes.iter().filter_map(|&i| node_value(g, i, &self.node_size_attr)));
+        self.node_color_values = ValueRange::from_values(nodes.iter().filter_map(|&i| node_value(g, i, &self.node_color_attr)));
+        self.edge_width_values = ValueRange::from_values(edges.iter().filter_map(|&i| edge_value(g, i, &self.edge_width_attr)));
+        self.edge_opacity_values = ValueRange::from_values(edges.iter().filter_map(|&i| edge_value(g, i, &self.edge_opacity_attr)));
+        self.edge_color_values = ValueRange::from_values(edges.iter().filter_map(|&i| edge_value(g, i, &self.edge_color_attr)));
+
+        for idx in nodes {
+            let radius = node_value(g, idx, &self.node_size_attr)
+                .zip(self.node_size_values)
+                .map(|(v, r)| lerp(self.node_size_range, r.normalize(v)));
+            let color = literal_color(node_attribute(g, idx, &self.node_color_attr)).or_else(|| {
+                node_value(g, idx, &self.node_color_attr)
+                    .zip(self.node_color_values)
+                    .map(|(v, r)| self.node_ramp.sample(r.normalize(v)))
+            });
+            if let Some(node) = g.node_mut(idx) {
+                let style = &mut node.payload_mut().style;
+                style.radius = radius;
+                style.color = color;
+            }
+        }
+
+        for idx in edges {
+            let width = edge_value(g, idx, &self.edge_width_attr)
+                .zip(self.edge_width_values)
+                .map(|(v, r)| lerp(self.edge_width_range, r.normalize(v)));
+            let opacity = edge_value(g, idx, &self.edge_opacity_attr)
+                .zip(self.edge_opacity_values)
+                .map(|(v, r)| lerp(self.edge_opacity_range, r.normalize(v)));
+            let mut color = literal_color(edge_attribute(g, idx, &self.edge_color_attr)).or_else(|| {
+                edge_value(g, idx, &self.edge_color_attr)
+                    .zip(self.edge_color_values)
+                    .map(|(v, r)| self.edge_ramp.sample(r.normalize(v)))
+            });
+            if let Some(alpha) = opacity {
+                // Without a colour mapping, opacity fades a neutral grey
+                color = Some(color.unwrap_or(Color32::GRAY).gamma_multiply(alpha));
+            }
+            if let Some(edge) = g.edge_mut(idx) {
+                let style = &mut edge.payload_mut().style;
+                style.width = width;
+                style.color = color;
+            }
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.node_size_attr != NodeAttribute::None
+            || self.node_color_attr != NodeAttribute::None
+            || self.edge_width_attr != EdgeAttribute::None
+            || self.edge_opacity_attr != EdgeAttribute::None
+            || self.edge_color_attr != EdgeAttribute::None
+    }
+
+    /// Legend in the bottom-left corner of the canvas listing every active binding.
+    pub fn draw_legend(&self, ui: &Ui, canvas: Rect) {
+        if !self.show_legend || !self.is_active() {
+            return;
+        }
+        let mut rows: Vec<(String, LegendKind, ValueRange)> = Vec::new();
+        if let Some(r) = self.node_size_values.filter(|_| self.node_size_attr != NodeAttribute::None) {
+            rows.push((format!("{}: {}", tr("mapping.node_size"), self.node_size_attr.label()), LegendKind::Size(self.node_size_range), r));
+        }
+        if let Some(r) = self.node_color_values.filter(|_| self.node_color_attr != NodeAttribute::None) {
+            rows.push((format!("{}: {}", tr("mapping.node_color"), self.node_color_attr.label()), LegendKind::Ramp(self.node_ramp), r));
+        }
+        if let Some(r) = self.edge_width_values.filter(|_| self.edge_width_attr != EdgeAttribute::None) {
+            rows.push((format!("{}: {}", tr("mapping.edge_width"), self.edge_width_attr.label()), LegendKind::Width(self.edge_width_range), r));
+        }
+        if let Some(r) = self.edge_opacity_values.filter(|_| self.edge_opacity_attr != EdgeAttribute::None) {
+            rows.push((format!("{}: {}", tr("mapping.edge_opacity"), self.edge_opacity_attr.label()), LegendKind::Opacity(self.edge_opacity_range), r));
+        }
+        if let Some(r) = self.edge_color_values.filter(|_| self.edge_color_attr != EdgeAttribute::None) {
+            rows.push((format!("{}: {}", tr("mapping.edge_color"), self.edge_color_attr.label()), LegendKind::Ramp(self.edge_ramp), r));
+        }
+        if rows.is_empty() {
+            return;
+        }
+
+        let row_height = 34.0;
+        let size = Vec2::new(180.0, rows.len() as f32 * row_height + 8.0);
+        let rect = Rect::from_min_size(Pos2::new(canvas.left() + 10.0, canvas.bottom() - size.y - 10.0), size);
+        let painter = ui.painter_at(canvas);
+        let visuals = ui.visuals();
+        painter.rect_filled(rect, 4.0, visuals.extreme_bg_color.gamma_multiply(0.9));
+        painter.rect_stroke(rect, 4.0, visuals.widgets.noninteractive.bg_stroke, egui::StrokeKind::Inside);
+
+        let font = egui::FontId::proportional(11.0);
+        for (i, (title, kind, range)) in rows.iter().enumerate() {
+            let top = rect.top() + 4.0 + i as f32 * row_height;
+            painter.text(Pos2::new(rect.left() + 6.0, top), egui::Align2::LEFT_TOP, title, font.clone(), visuals.text_color());
+            let bar = Rect::from_min_size(Pos2::new(rect.left() + 6.0, top + 16.0), Vec2::new(110.0, 10.0));
+            kind.paint(&painter, bar);
+            painter.text(
+                Pos2::new(bar.right() + 6.0, bar.center().y),
+                egui::Align2::LEFT_CENTER,
+                format!("{:.1}–{:.1}", range.min, range.max),
+                font.clone(),
+                visuals.weak_text_color(),
+            );
+        }
+    }
+}
+
+enum LegendKind {
+    Size((f32, f32)),
+    Ramp(ColorRamp),
+    Width((f32, f32)),
+    Opacity((f32, f32)),
+}
+
+impl LegendKind {
+    fn paint(&self, painter: &egui::Painter, bar: Rect) {
+        const STEPS: usize = 12;
+        let step_w = bar.width() / STEPS as f32;
+        for s in 0..STEPS {
+            let t = s as f32 / (STEPS - 1) as f32;
+            let x = bar.left() + s as f32 * step_w + step_w * 0.5;
+            match self {
+                LegendKind::Size(range) => {
+                    painter.circle_filled(Pos2::new(x, bar.center().y), (lerp(*range, t) * 0.4).min(bar.height()), Color32::GRAY);
+                }
+                LegendKind::Ramp(ramp) => {
+                    let cell = Rect::from_min_size(Pos2::new(bar.left() + s as f32 * step_w, bar.top()), Vec2::new(step_w + 0.5, bar.height()));
+                    painter.rect_filled(cell, 0.0, ramp.sample(t));
+                }
+                LegendKind::Width(range) => {
+                    let seg = [Pos2::new(x - step_w * 0.5, bar.center().y), Pos2::new(x + step_w * 0.5, bar.center().y)];
+                    painter.line_segment(seg, Stroke::new(lerp(*range, t).min(bar.height()), Color32::GRAY));
+                }
+                LegendKind::Opacity(range) => {
+                    let cell = Rect::from_min_size(Pos2::new(bar.left() + s as f32 * step_w, bar.top()), Vec2::new(step_w + 0.5, bar.height()));
+                    painter.rect_filled(cell, 0.0, Color32::GRAY.gamma_multiply(lerp(*range, t)));
+                }
+            }
+        }
+    }
+}